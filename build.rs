@@ -0,0 +1,43 @@
+use std::{env, fs, path::Path};
+
+#[path = "src/services/sparks/checksum.rs"]
+mod checksum;
+
+const SPARKS_DIR: &str = "src/services/sparks";
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", SPARKS_DIR);
+    println!("cargo:rerun-if-changed={}", checksum::SPARK_LOCK_FILE);
+
+    let sparks = checksum::spark_directories(Path::new(SPARKS_DIR)).unwrap_or_else(|e| panic!("Failed to scan {}: {}", SPARKS_DIR, e));
+
+    let checksums = sparks
+        .iter()
+        .map(|(name, dir)| {
+            let checksum = checksum::spark_checksum(dir).unwrap_or_else(|e| panic!("Failed to hash spark '{}': {}", name, e));
+            (name.as_str(), checksum)
+        })
+        .collect::<Vec<_>>();
+
+    let generated = checksums.iter().map(|(name, checksum)| format!("({:?}, {:?}),", name, checksum)).collect::<String>();
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    fs::write(Path::new(&out_dir).join("spark_checksums.rs"), format!("&[{}]", generated)).expect("Failed to write spark checksums");
+
+    if checksums.is_empty() {
+        return;
+    }
+
+    let release = env::var("PROFILE").is_ok_and(|profile| profile == "release");
+    let lock_content = fs::read_to_string(checksum::SPARK_LOCK_FILE).unwrap_or_default();
+    let borrowed = checksums.iter().map(|(name, checksum)| (*name, checksum.as_str())).collect::<Vec<_>>();
+
+    let mismatches = checksum::checksum_mismatches(&lock_content, &borrowed).unwrap_or_else(|e| vec![e]);
+
+    for mismatch in &mismatches {
+        println!("cargo:warning={}", mismatch);
+    }
+
+    if release && !mismatches.is_empty() {
+        panic!("Spark directories do not match {}. Review the changes and run `cargo run --example lock_sparks`.", checksum::SPARK_LOCK_FILE);
+    }
+}
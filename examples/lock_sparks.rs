@@ -0,0 +1,18 @@
+use std::{path::Path, process};
+
+use catalyst::services::sparks::{update_spark_lock, SPARK_LOCK_FILE};
+
+fn main() {
+    match update_spark_lock(Path::new(".")) {
+        Ok(lock) => {
+            println!("Wrote {} with {} spark(s):", SPARK_LOCK_FILE, lock.sparks.len());
+            for entry in lock.sparks {
+                println!("  {} {} ({}) {}", entry.name, entry.version, entry.source, entry.checksum);
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to update {}: {}", SPARK_LOCK_FILE, e);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use std::{env, path::Path, process};
+
+use catalyst::services::sparks::scaffold_spark;
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    let Some(name) = args.first() else {
+        eprintln!("Usage: cargo run --example new_spark -- <name> [description] [author]");
+        process::exit(1);
+    };
+
+    let description = args.get(1).cloned().unwrap_or_else(|| format!("The {} spark", name));
+    let author = args.get(2).cloned().unwrap_or_else(|| env::var("USER").unwrap_or_else(|_| "Unknown".to_string()));
+
+    match scaffold_spark(Path::new("."), name, &description, &author) {
+        Ok(files) => {
+            println!("Created spark '{}':", name);
+            for file in files {
+                println!("  {}", file.display());
+            }
+            println!("Registered it in src/services/sparks/mod.rs and Catalyst.toml");
+        }
+        Err(e) => {
+            eprintln!("Failed to create spark '{}': {}", name, e);
+            process::exit(1);
+        }
+    }
+}
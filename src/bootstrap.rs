@@ -1,4 +1,4 @@
-use std::{collections::HashMap, fs, path::Path, sync::OnceLock};
+use std::{collections::HashMap, fmt, fs, path::Path, sync::OnceLock};
 
 use serde::{Deserialize, Serialize};
 use toml::Value as TomlValue;
@@ -14,6 +14,138 @@ pub struct AppConfig {
     pub sparks: HashMap<String, TomlValue>,
     #[serde(default)]
     pub required_env: RequiredEnv,
+    #[serde(default)]
+    pub security: SecuritySettings,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct SecuritySettings {
+    #[serde(default)]
+    pub password: PasswordSettings,
+
+    #[serde(default)]
+    pub lockout: LockoutSettings,
+
+    #[serde(default)]
+    pub oidc: OidcSettings,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OidcSettings {
+    #[serde(default = "default_oidc_http_timeout_secs")]
+    pub http_timeout_secs: u64,
+
+    #[serde(default = "default_oidc_discovery_cache_secs")]
+    pub discovery_cache_secs: u64,
+
+    #[serde(default = "default_oidc_flow_ttl_secs")]
+    pub flow_ttl_secs: i64,
+
+    #[serde(default)]
+    pub allow_insecure_issuers: bool,
+}
+
+impl Default for OidcSettings {
+    fn default() -> Self {
+        OidcSettings {
+            http_timeout_secs: default_oidc_http_timeout_secs(),
+            discovery_cache_secs: default_oidc_discovery_cache_secs(),
+            flow_ttl_secs: default_oidc_flow_ttl_secs(),
+            allow_insecure_issuers: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LockoutSettings {
+    #[serde(default = "default_lockout_max_failures")]
+    pub max_failures: u32,
+
+    #[serde(default = "default_lockout_window_secs")]
+    pub window_secs: i64,
+
+    #[serde(default = "default_lockout_base_secs")]
+    pub base_lockout_secs: i64,
+
+    #[serde(default = "default_lockout_max_secs")]
+    pub max_lockout_secs: i64,
+}
+
+impl Default for LockoutSettings {
+    fn default() -> Self {
+        LockoutSettings {
+            max_failures: default_lockout_max_failures(),
+            window_secs: default_lockout_window_secs(),
+            base_lockout_secs: default_lockout_base_secs(),
+            max_lockout_secs: default_lockout_max_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PasswordAlgorithm {
+    #[default]
+    Bcrypt,
+    Argon2id,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PasswordSettings {
+    #[serde(default)]
+    pub algorithm: PasswordAlgorithm,
+
+    #[serde(default = "default_bcrypt_cost")]
+    pub bcrypt_cost: u32,
+
+    #[serde(default = "default_argon2_memory_kib")]
+    pub argon2_memory_kib: u32,
+
+    #[serde(default = "default_argon2_iterations")]
+    pub argon2_iterations: u32,
+
+    #[serde(default = "default_argon2_parallelism")]
+    pub argon2_parallelism: u32,
+
+    #[serde(default = "default_password_min_length")]
+    pub min_length: usize,
+
+    #[serde(default = "default_password_max_length")]
+    pub max_length: usize,
+
+    #[serde(default)]
+    pub require_uppercase: bool,
+
+    #[serde(default)]
+    pub require_lowercase: bool,
+
+    #[serde(default)]
+    pub require_digit: bool,
+
+    #[serde(default)]
+    pub require_symbol: bool,
+
+    #[serde(default)]
+    pub breached_list: Option<String>,
+}
+
+impl Default for PasswordSettings {
+    fn default() -> Self {
+        PasswordSettings {
+            algorithm: PasswordAlgorithm::default(),
+            bcrypt_cost: default_bcrypt_cost(),
+            argon2_memory_kib: default_argon2_memory_kib(),
+            argon2_iterations: default_argon2_iterations(),
+            argon2_parallelism: default_argon2_parallelism(),
+            min_length: default_password_min_length(),
+            max_length: default_password_max_length(),
+            require_uppercase: false,
+            require_lowercase: false,
+            require_digit: false,
+            require_symbol: false,
+            breached_list: None,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
@@ -29,6 +161,18 @@ pub struct Settings {
 
     #[serde(default)]
     pub jwt: JwtSettings,
+
+    #[serde(default)]
+    pub mail: MailSettings,
+
+    #[serde(default)]
+    pub logging: LoggingSettings,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct LoggingSettings {
+    #[serde(default)]
+    pub redact_patterns: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -53,6 +197,59 @@ pub struct JwtSettings {
 
     #[serde(default = "default_token_leeway_secs")]
     pub token_leeway_secs: u64,
+
+    #[serde(default = "default_impersonation_token_expiry_mins")]
+    pub impersonation_token_expiry_mins: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MailBackend {
+    #[default]
+    File,
+    Smtp,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MailSettings {
+    #[serde(default)]
+    pub backend: MailBackend,
+
+    #[serde(default = "default_mail_from")]
+    pub from: String,
+
+    #[serde(default = "default_mail_base_url")]
+    pub base_url: String,
+
+    #[serde(default = "default_mail_drop_dir")]
+    pub drop_dir: String,
+
+    #[serde(default)]
+    pub smtp_host: String,
+
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+
+    #[serde(default)]
+    pub smtp_username: Option<String>,
+
+    #[serde(default = "default_smtp_starttls")]
+    pub smtp_starttls: bool,
+}
+
+impl Default for MailSettings {
+    fn default() -> Self {
+        MailSettings {
+            backend: MailBackend::default(),
+            from: default_mail_from(),
+            base_url: default_mail_base_url(),
+            drop_dir: default_mail_drop_dir(),
+            smtp_host: String::new(),
+            smtp_port: default_smtp_port(),
+            smtp_username: None,
+            smtp_starttls: default_smtp_starttls(),
+        }
+    }
 }
 
 impl Default for JwtSettings {
@@ -68,6 +265,8 @@ impl Default for JwtSettings {
             token_refresh_threshold_mins: default_token_refresh_threshold_mins(),
 
             token_leeway_secs: default_token_leeway_secs(),
+
+            impersonation_token_expiry_mins: default_impersonation_token_expiry_mins(),
         }
     }
 }
@@ -104,6 +303,193 @@ fn default_token_leeway_secs() -> u64 {
     5
 }
 
+fn default_impersonation_token_expiry_mins() -> u64 {
+    15
+}
+
+fn default_mail_from() -> String {
+    "Catalyst <no-reply@localhost>".to_string()
+}
+
+fn default_mail_base_url() -> String {
+    "http://localhost:8000".to_string()
+}
+
+fn default_mail_drop_dir() -> String {
+    "storage/mail".to_string()
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_smtp_starttls() -> bool {
+    true
+}
+
+fn default_bcrypt_cost() -> u32 {
+    bcrypt::DEFAULT_COST
+}
+
+fn default_argon2_memory_kib() -> u32 {
+    19456
+}
+
+fn default_argon2_iterations() -> u32 {
+    2
+}
+
+fn default_argon2_parallelism() -> u32 {
+    1
+}
+
+fn default_password_min_length() -> usize {
+    8
+}
+
+fn default_password_max_length() -> usize {
+    128
+}
+
+fn default_lockout_max_failures() -> u32 {
+    5
+}
+
+fn default_lockout_window_secs() -> i64 {
+    900
+}
+
+fn default_lockout_base_secs() -> i64 {
+    60
+}
+
+fn default_lockout_max_secs() -> i64 {
+    3600
+}
+
+fn default_oidc_http_timeout_secs() -> u64 {
+    10
+}
+
+fn default_oidc_discovery_cache_secs() -> u64 {
+    3600
+}
+
+fn default_oidc_flow_ttl_secs() -> i64 {
+    600
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootStage {
+    Hooks,
+    Config,
+    Environment,
+    Sparks,
+    Database,
+}
+
+impl BootStage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BootStage::Hooks => "hooks",
+            BootStage::Config => "config",
+            BootStage::Environment => "env",
+            BootStage::Sparks => "sparks",
+            BootStage::Database => "database",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootProblem {
+    pub stage: BootStage,
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+impl BootProblem {
+    pub fn new(stage: BootStage, message: impl Into<String>) -> Self {
+        BootProblem {
+            stage,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+impl fmt::Display for BootProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.stage.name(), self.message)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BootReport {
+    pub environment: String,
+    pub sparks: Vec<String>,
+    pub warnings: Vec<BootProblem>,
+}
+
+impl BootReport {
+    fn warn(&mut self, problem: BootProblem) {
+        cata_log!(Warning, problem.to_string());
+        if let Some(hint) = &problem.hint {
+            cata_log!(Warning, format!("  {}", hint));
+        }
+        self.warnings.push(problem);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BootError {
+    pub problems: Vec<BootProblem>,
+}
+
+impl BootError {
+    pub fn hints(&self) -> Vec<&str> {
+        let mut hints = Vec::new();
+        for hint in self.problems.iter().filter_map(|problem| problem.hint.as_deref()) {
+            if !hints.contains(&hint) {
+                hints.push(hint);
+            }
+        }
+        hints
+    }
+}
+
+impl From<BootProblem> for BootError {
+    fn from(problem: BootProblem) -> Self {
+        BootError { problems: vec![problem] }
+    }
+}
+
+impl fmt::Display for BootError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Bootstrap failed with {} problem(s):", self.problems.len())?;
+        for problem in &self.problems {
+            write!(f, "\n  - {}", problem)?;
+        }
+
+        let hints = self.hints();
+        if !hints.is_empty() {
+            write!(f, "\nTo fix:")?;
+            for hint in hints {
+                write!(f, "\n  - {}", hint)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for BootError {
+}
+
 impl AppConfig {
     pub fn load_from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
         let contents = fs::read_to_string(path)?;
@@ -116,67 +502,47 @@ impl AppConfig {
     }
 }
 
-fn validate_configured_sparks(config: &AppConfig) {
+fn validate_configured_sparks(config: &AppConfig) -> Vec<BootProblem> {
     if config.sparks.is_empty() {
         cata_log!(Debug, "No sparks configured in Catalyst.toml");
-        return;
+        return Vec::new();
     }
 
     cata_log!(Info, format!("Validating {} configured spark(s)", config.sparks.len()));
 
-    let mut missing_sparks = Vec::new();
-    let mut invalid_sparks = Vec::new();
-
-    for (spark_name, spark_url) in &config.sparks {
-        let spark_dir = Path::new("src/services/sparks").join(spark_name);
-        let manifest_path = spark_dir.join("manifest.toml");
-
-        if !spark_dir.exists() {
-            missing_sparks.push((spark_name.clone(), spark_url.as_str().unwrap_or("unknown").to_string()));
+    let mut problems = Vec::new();
+    let mut spark_names = config.sparks.keys().collect::<Vec<_>>();
+    spark_names.sort();
+
+    for spark_name in spark_names {
+        let Some(registration) = registry::compiled_spark(spark_name) else {
+            let spark_url = config.sparks[spark_name].as_str().unwrap_or("unknown");
+            problems.push(
+                BootProblem::new(BootStage::Sparks, format!("Spark '{}' ({}) is configured but not compiled into this binary", spark_name, spark_url))
+                    .with_hint("Install missing sparks using: blast spark add <repository_url>"),
+            );
             continue;
-        }
+        };
 
-        if !manifest_path.exists() {
-            invalid_sparks.push(spark_name.clone());
-            continue;
-        }
-
-        match validate_spark_manifest(&manifest_path, spark_name) {
+        match validate_spark_manifest(registration.manifest, spark_name) {
             Ok(_) => {
                 cata_log!(Debug, format!("Spark '{}' validated successfully", spark_name));
             }
             Err(e) => {
-                cata_log!(Warning, format!("Spark '{}' validation failed: {}", spark_name, e));
-                invalid_sparks.push(spark_name.clone());
+                problems.push(BootProblem::new(BootStage::Sparks, format!("Spark '{}' has an invalid manifest: {}", spark_name, e)).with_hint("Fix spark manifests or reinstall using: blast spark add <repository_url>"))
             }
         }
     }
 
-    if !missing_sparks.is_empty() {
-        cata_log!(Error, "The following sparks are configured but not installed:");
-        for (name, url) in &missing_sparks {
-            cata_log!(Error, format!("  - {} ({})", name, url));
-        }
-        cata_log!(Error, "Install missing sparks using: blast spark add <repository_url>");
-        std::process::exit(1);
-    }
-
-    if !invalid_sparks.is_empty() {
-        cata_log!(Error, "The following sparks have invalid manifests:");
-        for name in &invalid_sparks {
-            cata_log!(Error, format!("  - {}", name));
-        }
-        cata_log!(Error, "Fix spark manifests or reinstall using: blast spark add <repository_url>");
-        std::process::exit(1);
+    if problems.is_empty() {
+        cata_log!(Info, "All configured sparks validated successfully");
     }
 
-    cata_log!(Info, "All configured sparks validated successfully");
+    problems
 }
 
-fn validate_spark_manifest(manifest_path: &Path, spark_name: &str) -> Result<(), String> {
-    let manifest_content = fs::read_to_string(manifest_path).map_err(|e| format!("Failed to read manifest.toml: {}", e))?;
-
-    let manifest: TomlValue = toml::from_str(&manifest_content).map_err(|e| format!("Failed to parse manifest.toml: {}", e))?;
+fn validate_spark_manifest(manifest_content: &str, spark_name: &str) -> Result<(), String> {
+    let manifest: TomlValue = toml::from_str(manifest_content).map_err(|e| format!("Failed to parse manifest.toml: {}", e))?;
 
     let spark_section = manifest.get("spark").ok_or("Missing [spark] section in manifest.toml")?.as_table().ok_or("[spark] section must be a table")?;
 
@@ -193,93 +559,141 @@ fn validate_spark_manifest(manifest_path: &Path, spark_name: &str) -> Result<(),
     let manifest_name = spark_section.get("name").and_then(|v| v.as_str()).unwrap();
 
     if manifest_name != spark_name {
-        return Err(format!("Spark name mismatch: directory name '{}' does not match manifest name '{}'", spark_name, manifest_name));
+        return Err(format!("Spark name mismatch: registered name '{}' does not match manifest name '{}'", spark_name, manifest_name));
     }
 
     Ok(())
 }
 
-pub async fn bootstrap() {
-    cata_log!(Info, "Starting bootstrap process");
+fn verify_spark_lock_file(config: &AppConfig) -> Vec<BootProblem> {
+    let Err(errors) = lock::verify_spark_lock(Path::new(checksum::SPARK_LOCK_FILE), &config.sparks) else {
+        cata_log!(Debug, format!("{} matches the compiled sparks", checksum::SPARK_LOCK_FILE));
+        return Vec::new();
+    };
+
+    errors
+        .into_iter()
+        .map(|error| {
+            BootProblem::new(BootStage::Sparks, format!("{} does not match the compiled sparks: {}", checksum::SPARK_LOCK_FILE, error)).with_hint("Review the spark changes and run: cargo run --example lock_sparks")
+        })
+        .collect()
+}
+
+pub fn validate_config(config: &AppConfig, report: &mut BootReport) -> Result<(), BootError> {
+    let mut problems = Vec::new();
+
+    problems.extend(validate_required_env_vars(config));
+    let spark_problems = validate_configured_sparks(config);
+    if spark_problems.is_empty() {
+        if let Err(resolve_problems) = resolve_configured_sparks(config) {
+            problems.extend(resolve_problems);
+        }
+    }
+    problems.extend(spark_problems);
+    problems.extend(validate_spark_state(config));
+
+    let lock_problems = verify_spark_lock_file(config);
+    if config.is_development() {
+        lock_problems.into_iter().for_each(|problem| report.warn(problem));
+    } else {
+        problems.extend(lock_problems);
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(BootError { problems })
+    }
+}
+
+async fn run_hook(phase: BootstrapPhase, problems: &mut Vec<BootProblem>) -> Result<(), BootError> {
+    let phase_name = phase.name();
 
-    if let Err(e) = run_custom_bootstrap(BootstrapPhase::PreConfig).await {
-        cata_log!(Error, format!("Custom bootstrap PreConfig phase failed: {}", e));
-        std::process::exit(1);
+    match run_custom_bootstrap(phase).await {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            problems.push(BootProblem::new(BootStage::Hooks, format!("Custom bootstrap {} phase failed: {}", phase_name, e)));
+            Err(BootError { problems: std::mem::take(problems) })
+        }
     }
+}
+
+pub async fn bootstrap() -> Result<BootReport, BootError> {
+    cata_log!(Info, "Starting bootstrap process");
+
+    let mut problems = Vec::new();
+
+    run_hook(BootstrapPhase::PreConfig, &mut problems).await?;
 
     dotenv::dotenv().ok();
 
     logger::setup_panic_hook();
 
     cata_log!(Debug, "Loading configuration from Catalyst.toml");
-    let config = AppConfig::load_from_file("Catalyst.toml").unwrap_or_else(|e| {
-        cata_log!(Error, format!("Failed to load Catalyst.toml: {}", e));
-        std::process::exit(1);
-    });
+    let config = AppConfig::load_from_file("Catalyst.toml").map_err(|e| BootProblem::new(BootStage::Config, format!("Failed to load Catalyst.toml: {}", e)))?;
 
-    validate_required_env_vars(&config);
-    validate_configured_sparks(&config);
+    logger::configure_redaction(&config.settings.logging.redact_patterns);
 
-    let _ = APP_CONFIG.set(config);
+    let mut report = BootReport {
+        environment: config.settings.environment.clone(),
+        ..Default::default()
+    };
 
-    if let Err(e) = run_custom_bootstrap(BootstrapPhase::PostConfig).await {
-        cata_log!(Error, format!("Custom bootstrap PostConfig phase failed: {}", e));
-        std::process::exit(1);
+    if let Err(e) = validate_config(&config, &mut report) {
+        problems.extend(e.problems);
     }
 
-    if let Some(config) = APP_CONFIG.get() {
-        cata_log!(Info, format!("Environment: {}", config.settings.environment));
-    }
+    let _ = APP_CONFIG.set(config);
 
-    if let Err(e) = run_custom_bootstrap(BootstrapPhase::PreDatabase).await {
-        cata_log!(Error, format!("Custom bootstrap PreDatabase phase failed: {}", e));
-        std::process::exit(1);
-    }
+    run_hook(BootstrapPhase::PostConfig, &mut problems).await?;
+
+    cata_log!(Info, format!("Environment: {}", report.environment));
+
+    run_hook(BootstrapPhase::PreDatabase, &mut problems).await?;
 
     cata_log!(Debug, "Initializing database connection pool");
     if let Err(e) = crate::database::db::init_connection_pool().await {
-        cata_log!(Error, format!("Failed to initialize database connection pool: {}", e));
-        panic!("Database initialization failed");
+        problems.push(BootProblem::new(BootStage::Database, format!("Failed to initialize database connection pool: {}", e.log_message())));
     }
 
-    if let Err(e) = run_custom_bootstrap(BootstrapPhase::PostDatabase).await {
-        cata_log!(Error, format!("Custom bootstrap PostDatabase phase failed: {}", e));
-        std::process::exit(1);
+    if !problems.is_empty() {
+        return Err(BootError { problems });
     }
 
-    if let Err(e) = run_custom_bootstrap(BootstrapPhase::PreSparks).await {
-        cata_log!(Error, format!("Custom bootstrap PreSparks phase failed: {}", e));
-        std::process::exit(1);
-    }
+    run_hook(BootstrapPhase::PostDatabase, &mut problems).await?;
+    run_hook(BootstrapPhase::PreSparks, &mut problems).await?;
 
     registry::init_registry();
     makeuse::init_spark_configs();
     makeuse::init_template_registry();
 
     cata_log!(Info, "Starting spark discovery and registration");
-    validate_and_sync_spark_state();
-    load_spark_manifests();
+    if let Some(config) = APP_CONFIG.get() {
+        report.sparks = load_spark_manifests(config)?;
+    }
 
-    if let Err(e) = run_custom_bootstrap(BootstrapPhase::PostSparks).await {
-        cata_log!(Error, format!("Custom bootstrap PostSparks phase failed: {}", e));
-        std::process::exit(1);
+    problems.extend(lifecycle::migrate_all_tenants().await.into_iter().map(|failure| BootProblem::new(BootStage::Database, failure)));
+    if !problems.is_empty() {
+        return Err(BootError { problems });
     }
 
+    run_hook(BootstrapPhase::PostSparks, &mut problems).await?;
+
     cata_log!(Info, "Initializing token version registry");
     if let Err(e) = token_registry::initialize_token_registry().await {
         cata_log!(Error, format!("Failed to initialize token registry: {}", e));
+        report.warn(BootProblem::new(BootStage::Database, format!("Failed to initialize token registry: {}", e.log_message())));
     }
 
     let spark_count = registry::get_available_sparks().len();
     cata_log!(Info, format!("Bootstrap complete: {} sparks registered", spark_count));
 
-    if let Err(e) = run_custom_bootstrap(BootstrapPhase::PostBootstrap).await {
-        cata_log!(Error, format!("Custom bootstrap PostBootstrap phase failed: {}", e));
-        std::process::exit(1);
-    }
+    run_hook(BootstrapPhase::PostBootstrap, &mut problems).await?;
+
+    Ok(report)
 }
 
-fn validate_required_env_vars(config: &AppConfig) {
+fn validate_required_env_vars(config: &AppConfig) -> Vec<BootProblem> {
     let mut invalid_vars = Vec::new();
 
     for var in &config.required_env.variables {
@@ -291,312 +705,219 @@ fn validate_required_env_vars(config: &AppConfig) {
     }
 
     if !invalid_vars.is_empty() {
-        cata_log!(Error, format!("Environment vars {} are missing or empty", invalid_vars.join(", ")));
-        std::process::exit(1);
+        return invalid_vars
+            .into_iter()
+            .map(|var| BootProblem::new(BootStage::Environment, format!("Environment var {} is missing or empty", var)))
+            .collect();
     }
 
     if !config.required_env.variables.is_empty() {
         cata_log!(Info, format!("All {} required environment variables are set", config.required_env.variables.len()));
     }
+
+    Vec::new()
 }
 
-fn validate_and_sync_spark_state() {
+fn validate_spark_state(config: &AppConfig) -> Vec<BootProblem> {
     cata_log!(Debug, "Validating spark state consistency");
 
-    let configured_sparks = if let Some(config) = APP_CONFIG.get() {
-        config.sparks.keys().cloned().collect::<std::collections::HashSet<String>>()
-    } else {
-        cata_log!(Warning, "Could not access app config for spark state validation");
-        return;
-    };
+    let configured_sparks = config.sparks.keys().cloned().collect::<std::collections::HashSet<String>>();
+    let compiled_sparks = registry::compiled_sparks().map(|registration| registration.name.to_string()).collect::<std::collections::HashSet<String>>();
 
-    let sparks_dir = Path::new("src/services/sparks");
-    if !sparks_dir.exists() {
-        if !configured_sparks.is_empty() {
-            cata_log!(Error, format!("Sparks configured but sparks directory missing: {:?}", configured_sparks));
-            cata_log!(Error, "Run 'blast spark sync' to install missing sparks");
-            std::process::exit(1);
+    let mut unconfigured_registrations = compiled_sparks.difference(&configured_sparks).cloned().collect::<Vec<_>>();
+
+    if unconfigured_registrations.is_empty() {
+        if configured_sparks.is_empty() {
+            cata_log!(Debug, "No sparks configured - spark state is consistent");
         }
-        return;
+        return Vec::new();
     }
 
-    let mut issues_found = false;
-    let mut missing_directories = Vec::new();
-    let mut incomplete_sparks = Vec::new();
-    let mut unconfigured_directories = Vec::new();
-    let mut orphaned_registry_entries = Vec::new();
-    let mut orphaned_mod_entries = Vec::new();
-
-    for spark_name in &configured_sparks {
-        let spark_dir = sparks_dir.join(spark_name);
-        let manifest_path = spark_dir.join("manifest.toml");
-
-        if !spark_dir.exists() || !manifest_path.exists() {
-            missing_directories.push(spark_name.clone());
-            issues_found = true;
-            continue;
-        }
+    unconfigured_registrations.sort();
 
-        let registry_missing = !check_spark_in_registry(spark_name);
-        let mod_missing = !check_spark_in_mod_rs(spark_name);
+    unconfigured_registrations
+        .into_iter()
+        .map(|name| {
+            BootProblem::new(BootStage::Sparks, format!("Spark '{}' is compiled into this binary but missing from Catalyst.toml", name)).with_hint("Run 'blast spark sync' to fix these issues before starting the server")
+        })
+        .collect()
+}
 
-        if registry_missing || mod_missing {
-            incomplete_sparks.push((spark_name.clone(), registry_missing, mod_missing));
-            issues_found = true;
-        }
+fn resolve_configured_sparks(config: &AppConfig) -> Result<Vec<String>, Vec<BootProblem>> {
+    if config.sparks.is_empty() {
+        return Ok(Vec::new());
     }
 
-    if let Ok(entries) = fs::read_dir(sparks_dir) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
+    makeuse::init_spark_configs();
 
-                if !path.is_dir() || ["registry", "makeuse"].contains(&path.file_name().unwrap_or_default().to_string_lossy().as_ref()) {
-                    continue;
-                }
+    let configured_sparks = config.sparks.keys().cloned().collect::<std::collections::HashSet<String>>();
 
-                let spark_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let mut manifests = HashMap::new();
+    let mut problems = Vec::new();
+    cata_log!(Debug, format!("Loading {} configured spark(s): {:?}", configured_sparks.len(), configured_sparks));
 
-                if !configured_sparks.contains(&spark_name) {
-                    unconfigured_directories.push(spark_name);
-                    issues_found = true;
-                }
-            }
-        }
-    }
+    for spark_name in &configured_sparks {
+        let Some(registration) = registry::compiled_spark(spark_name) else {
+            cata_log!(Warning, format!("Configured spark '{}' is not compiled into this binary", spark_name));
+            continue;
+        };
 
-    if let Ok(registry_sparks) = get_sparks_from_registry() {
-        for registry_spark in registry_sparks {
-            if !configured_sparks.contains(&registry_spark) {
-                orphaned_registry_entries.push(registry_spark);
-                issues_found = true;
+        match load_manifest(registration.manifest, spark_name, config) {
+            Ok(_) if !(registration.create)().is_available() => {
+                cata_log!(Info, format!("Configured spark '{}' is disabled or incompatible with this environment - skipping", spark_name));
             }
-        }
-    }
-
-    if let Ok(mod_sparks) = get_sparks_from_mod_rs() {
-        for mod_spark in mod_sparks {
-            if !configured_sparks.contains(&mod_spark) {
-                orphaned_mod_entries.push(mod_spark);
-                issues_found = true;
+            Ok(manifest) => {
+                cata_log!(Debug, format!("Loaded manifest for configured spark '{}'", spark_name));
+                manifests.insert(spark_name.clone(), manifest);
+
+                if let Some(validate_config) = registration.validate_config {
+                    if let Err(e) = validate_config(spark_name) {
+                        problems.push(BootProblem::new(BootStage::Sparks, format!("Spark '{}' configuration is invalid: {}", spark_name, e)));
+                    }
+                }
             }
+            Err(e) => problems.push(BootProblem::new(BootStage::Sparks, format!("Failed to load manifest for configured spark '{}': {}", spark_name, e))),
         }
     }
 
-    if issues_found {
-        cata_log!(Warning, "Spark state inconsistencies detected:");
-
-        if !missing_directories.is_empty() {
-            cata_log!(Error, format!("Missing spark directories: {:?}", missing_directories));
-        }
-
-        if !incomplete_sparks.is_empty() {
-            for (spark_name, registry_missing, mod_missing) in &incomplete_sparks {
-                cata_log!(
-                    Warning,
-                    format!(
-                        "Incomplete spark '{}': registry={}, mod={}",
-                        spark_name,
-                        if *registry_missing { "MISSING" } else { "OK" },
-                        if *mod_missing { "MISSING" } else { "OK" }
-                    )
-                );
-            }
-        }
-
-        if !unconfigured_directories.is_empty() {
-            cata_log!(Warning, format!("Unconfigured spark directories: {:?}", unconfigured_directories));
-        }
-
-        if !orphaned_registry_entries.is_empty() {
-            cata_log!(Warning, format!("Orphaned registry entries: {:?}", orphaned_registry_entries));
-        }
-
-        if !orphaned_mod_entries.is_empty() {
-            cata_log!(Warning, format!("Orphaned mod.rs entries: {:?}", orphaned_mod_entries));
+    let ordered_sparks = match dependencies::resolve_spark_order(&manifests) {
+        Ok(order) => order,
+        Err(errors) => {
+            problems.extend(errors.into_iter().map(|error| {
+                BootProblem::new(BootStage::Sparks, format!("Spark dependencies could not be resolved: {}", error)).with_hint("Install or upgrade the required sparks using: blast spark add <repository_url>")
+            }));
+            Vec::new()
         }
+    };
 
-        cata_log!(Error, "Spark state is inconsistent with Catalyst.toml configuration");
-        cata_log!(Error, "Run 'blast spark sync' to fix these issues before starting the server");
-        std::process::exit(1);
-    } else {
-        if configured_sparks.is_empty() {
-            cata_log!(Debug, "No sparks configured - spark state is consistent");
-        } else {
-            cata_log!(Info, format!("All {} configured sparks are properly synchronized", configured_sparks.len()));
-        }
+    if !problems.is_empty() {
+        problems.sort_by(|a, b| a.message.cmp(&b.message));
+        return Err(problems);
     }
-}
-
-fn check_spark_in_registry(spark_name: &str) -> bool {
-    let registry_path = Path::new("src/services/sparks/registry.rs");
 
-    if !registry_path.exists() {
-        return false;
-    }
+    Ok(ordered_sparks)
+}
 
-    match fs::read_to_string(&registry_path) {
-        Ok(content) => {
-            let pattern = format!("\"{spark_name}\" =>");
-            content.contains(&pattern)
-        }
-        Err(_) => false,
+fn load_spark_manifests(config: &AppConfig) -> Result<Vec<String>, BootError> {
+    if config.sparks.is_empty() {
+        cata_log!(Info, "No sparks configured in Catalyst.toml - skipping spark loading");
+        return Ok(Vec::new());
     }
-}
 
-fn check_spark_in_mod_rs(spark_name: &str) -> bool {
-    let mod_rs_path = Path::new("src/services/sparks/mod.rs");
+    let ordered_sparks = resolve_configured_sparks(config).map_err(|problems| BootError { problems })?;
 
-    if !mod_rs_path.exists() {
-        return false;
+    cata_log!(Info, format!("Registering {} configured sparks in dependency order: {:?}", ordered_sparks.len(), ordered_sparks));
+    for spark_name in &ordered_sparks {
+        register_spark_dynamically(spark_name);
     }
 
-    match fs::read_to_string(&mod_rs_path) {
-        Ok(content) => {
-            let module_line = format!("pub mod {};", spark_name);
-            content.contains(&module_line)
-        }
-        Err(_) => false,
-    }
+    Ok(ordered_sparks)
 }
 
-fn get_sparks_from_registry() -> Result<Vec<String>, String> {
-    let registry_path = Path::new("src/services/sparks/registry.rs");
+fn register_spark_dynamically(spark_name: &str) {
+    cata_log!(Debug, format!("Registering spark '{}'", spark_name));
+    registry::register_by_name(spark_name);
+}
 
-    if !registry_path.exists() {
-        return Ok(Vec::new());
-    }
+fn load_manifest(manifest_str: &str, spark_name: &str, config: &AppConfig) -> Result<TomlValue, Box<dyn std::error::Error>> {
+    let manifest: TomlValue = toml::from_str(manifest_str)?;
 
-    let content = fs::read_to_string(&registry_path).map_err(|e| format!("Failed to read registry.rs: {}", e))?;
+    let defaults = manifest.get("config").and_then(|c| c.get("defaults")).cloned().unwrap_or_else(|| TomlValue::Table(toml::map::Map::new()));
 
-    let mut spark_names = Vec::new();
+    makeuse::register_spark_manifest(spark_name, manifest.clone(), defaults);
 
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.starts_with('"') && trimmed.contains("\" =>") {
-            if let Some(end_quote) = trimmed[1..].find('"') {
-                let spark_name = &trimmed[1..end_quote + 1];
-                if spark_name != "_" {
-                    spark_names.push(spark_name.to_string());
-                }
-            }
-        }
+    if let Some(spark_overrides) = config.sparks.get(spark_name) {
+        makeuse::register_spark_overrides(spark_name, spark_overrides.clone());
     }
 
-    Ok(spark_names)
+    Ok(manifest)
 }
 
-fn get_sparks_from_mod_rs() -> Result<Vec<String>, String> {
-    let mod_rs_path = Path::new("src/services/sparks/mod.rs");
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    if !mod_rs_path.exists() {
-        return Ok(Vec::new());
+    fn config(source: &str) -> AppConfig {
+        toml::from_str(source).unwrap()
     }
 
-    let content = fs::read_to_string(&mod_rs_path).map_err(|e| format!("Failed to read mod.rs: {}", e))?;
-
-    let mut spark_names = Vec::new();
+    #[test]
+    fn boot_error_lists_problems_and_unique_hints() {
+        let error = BootError {
+            problems: vec![
+                BootProblem::new(BootStage::Sparks, "Spark 'a' is missing").with_hint("Run 'blast spark sync'"),
+                BootProblem::new(BootStage::Sparks, "Spark 'b' is missing").with_hint("Run 'blast spark sync'"),
+                BootProblem::new(BootStage::Environment, "Environment var X is missing or empty"),
+            ],
+        };
 
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.starts_with("pub mod ") && trimmed.ends_with(';') {
-            let module_name = &trimmed[8..trimmed.len() - 1];
-
-            if !["makeuse", "registry"].contains(&module_name) {
-                spark_names.push(module_name.to_string());
-            }
-        }
+        assert_eq!(error.hints(), vec!["Run 'blast spark sync'"]);
+        assert_eq!(
+            error.to_string(),
+            "Bootstrap failed with 3 problem(s):\n  - [sparks] Spark 'a' is missing\n  - [sparks] Spark 'b' is missing\n  - [env] Environment var X is missing or empty\nTo fix:\n  - Run 'blast spark sync'"
+        );
     }
 
-    Ok(spark_names)
-}
-
-fn load_spark_manifests() {
-    let sparks_dir = Path::new("src/services/sparks");
+    #[test]
+    fn boot_error_from_single_problem() {
+        let error = BootError::from(BootProblem::new(BootStage::Config, "Failed to load Catalyst.toml"));
 
-    if !sparks_dir.exists() {
-        cata_log!(Warning, "Sparks directory not found");
-        return;
+        assert_eq!(error.problems.len(), 1);
+        assert!(error.hints().is_empty());
+        assert_eq!(error.to_string(), "Bootstrap failed with 1 problem(s):\n  - [config] Failed to load Catalyst.toml");
     }
 
-    let configured_sparks = if let Some(config) = APP_CONFIG.get() {
-        if config.sparks.is_empty() {
-            cata_log!(Info, "No sparks configured in Catalyst.toml - skipping spark loading");
-            return;
-        }
-        config.sparks.keys().cloned().collect::<std::collections::HashSet<String>>()
-    } else {
-        cata_log!(Warning, "Could not access app config for spark loading");
-        return;
-    };
-
-    let mut discovered_sparks = Vec::new();
-    cata_log!(Debug, format!("Loading {} configured spark(s): {:?}", configured_sparks.len(), configured_sparks));
-
-    for spark_name in &configured_sparks {
-        let spark_path = sparks_dir.join(spark_name);
-
-        if !spark_path.is_dir() {
-            cata_log!(Warning, format!("Configured spark '{}' directory not found at {}", spark_name, spark_path.display()));
-            continue;
-        }
+    #[test]
+    fn report_collects_warnings() {
+        let mut report = BootReport::default();
+        report.warn(BootProblem::new(BootStage::Database, "Failed to initialize token registry"));
 
-        let manifest_path = spark_path.join("manifest.toml");
-        if manifest_path.exists() {
-            match load_manifest(&manifest_path, spark_name) {
-                Ok(_) => {
-                    cata_log!(Debug, format!("Loaded manifest for configured spark '{}'", spark_name));
-                    discovered_sparks.push(spark_name.clone());
-                }
-                Err(e) => cata_log!(Error, format!("Failed to load manifest for configured spark '{}': {}", spark_name, e)),
-            }
-        } else {
-            cata_log!(Warning, format!("No manifest.toml found for configured spark '{}' at {}", spark_name, manifest_path.display()));
-        }
+        assert_eq!(report.warnings, vec![BootProblem::new(BootStage::Database, "Failed to initialize token registry")]);
     }
 
-    if let Ok(entries) = fs::read_dir(sparks_dir) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
+    #[test]
+    fn validate_config_collects_every_problem() {
+        let config = config(
+            r#"
+            [settings]
+            environment = "dev"
 
-                if !path.is_dir() || ["registry", "makeuse"].contains(&path.file_name().unwrap_or_default().to_string_lossy().as_ref()) {
-                    continue;
-                }
+            [required_env]
+            variables = ["CATALYST_TEST_MISSING_VAR_ONE", "CATALYST_TEST_MISSING_VAR_TWO"]
 
-                let spark_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            [sparks]
+            ghost = "https://example.com/ghost.git"
+            "#,
+        );
 
-                if !configured_sparks.contains(&spark_name) {
-                    cata_log!(Debug, format!("Spark '{}' is installed but not configured in Catalyst.toml - skipping", spark_name));
-                }
-            }
-        }
-    }
+        let error = validate_config(&config, &mut BootReport::default()).unwrap_err();
 
-    cata_log!(Info, format!("Registering {} configured sparks: {:?}", discovered_sparks.len(), discovered_sparks));
-    for spark_name in discovered_sparks {
-        register_spark_dynamically(&spark_name);
+        let environment = error.problems.iter().filter(|problem| problem.stage == BootStage::Environment).count();
+        assert_eq!(environment, 2);
+        assert!(error
+            .problems
+            .iter()
+            .any(|problem| problem.stage == BootStage::Sparks && problem.message == "Spark 'ghost' (https://example.com/ghost.git) is configured but not compiled into this binary"));
     }
-}
-
-fn register_spark_dynamically(spark_name: &str) {
-    cata_log!(Debug, format!("Registering spark '{}'", spark_name));
-    registry::register_by_name(spark_name);
-}
 
-fn load_manifest(path: &Path, spark_name: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let manifest_str = fs::read_to_string(path)?;
-    let manifest: TomlValue = toml::from_str(&manifest_str)?;
+    #[test]
+    fn validate_config_accepts_empty_config() {
+        let config = config("[settings]\nenvironment = \"dev\"\n");
+        let mut report = BootReport::default();
 
-    let defaults = manifest.get("config").and_then(|c| c.get("defaults")).cloned().unwrap_or_else(|| TomlValue::Table(toml::map::Map::new()));
+        assert!(validate_config(&config, &mut report).is_ok());
+    }
 
-    makeuse::register_spark_manifest(spark_name, manifest, defaults);
+    #[test]
+    fn validates_spark_manifests() {
+        let manifest = "[spark]\nname = \"audit\"\nversion = \"1.0.0\"\ndescription = \"Audit\"\nauthor = \"Someone\"\n";
 
-    if let Some(config) = APP_CONFIG.get() {
-        if let Some(spark_overrides) = config.sparks.get(spark_name) {
-            makeuse::register_spark_overrides(spark_name, spark_overrides.clone());
-        }
+        assert!(validate_spark_manifest(manifest, "audit").is_ok());
+        assert_eq!(
+            validate_spark_manifest(manifest, "vault").unwrap_err(),
+            "Spark name mismatch: registered name 'vault' does not match manifest name 'audit'"
+        );
+        assert_eq!(validate_spark_manifest("[spark]\nname = \"audit\"\n", "audit").unwrap_err(), "Missing required field 'version' in [spark] section");
+        assert_eq!(validate_spark_manifest("name = \"audit\"\n", "audit").unwrap_err(), "Missing [spark] section in manifest.toml");
     }
-
-    Ok(())
 }
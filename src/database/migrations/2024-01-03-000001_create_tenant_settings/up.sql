@@ -0,0 +1,7 @@
+CREATE TABLE tenant_settings (
+    id SERIAL PRIMARY KEY,
+    key TEXT UNIQUE NOT NULL,
+    value TEXT NOT NULL,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())),
+    updated_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()))
+);
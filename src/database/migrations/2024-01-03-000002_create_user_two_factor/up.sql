@@ -0,0 +1,12 @@
+CREATE TABLE user_two_factor (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    secret TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    recovery_codes JSONB NOT NULL DEFAULT '[]',
+    last_used_step BIGINT,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())),
+    updated_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()))
+);
+
+CREATE UNIQUE INDEX user_two_factor_user_id_idx ON user_two_factor(user_id);
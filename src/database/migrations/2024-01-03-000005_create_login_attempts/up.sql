@@ -0,0 +1,13 @@
+CREATE TABLE login_attempts (
+    id SERIAL PRIMARY KEY,
+    username TEXT NOT NULL,
+    user_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    ip_address TEXT,
+    user_agent TEXT,
+    success BOOLEAN NOT NULL,
+    failure_reason TEXT,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()))
+);
+
+CREATE INDEX login_attempts_username_created_at_idx ON login_attempts (username, created_at);
+CREATE INDEX login_attempts_created_at_idx ON login_attempts (created_at);
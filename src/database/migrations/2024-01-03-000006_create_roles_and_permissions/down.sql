@@ -0,0 +1,4 @@
+ALTER TABLE users DROP CONSTRAINT users_role_fkey;
+DROP TABLE role_permissions;
+DROP TABLE permissions;
+DROP TABLE roles;
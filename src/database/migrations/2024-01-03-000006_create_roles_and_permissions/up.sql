@@ -0,0 +1,50 @@
+CREATE TABLE roles (
+    id SERIAL PRIMARY KEY,
+    name TEXT UNIQUE NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    is_system BOOLEAN NOT NULL DEFAULT FALSE,
+    hidden BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())),
+    updated_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()))
+);
+
+CREATE TABLE permissions (
+    id SERIAL PRIMARY KEY,
+    name TEXT UNIQUE NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()))
+);
+
+CREATE TABLE role_permissions (
+    role_id INTEGER NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    permission_id INTEGER NOT NULL REFERENCES permissions(id) ON DELETE CASCADE,
+    PRIMARY KEY (role_id, permission_id)
+);
+
+INSERT INTO roles (name, description, is_system, hidden) VALUES
+('admin', 'Full access to the tenant administration area', TRUE, FALSE),
+('user', 'Regular tenant user', TRUE, FALSE),
+('dev', 'Developer account, hidden from user listings', TRUE, TRUE);
+
+INSERT INTO permissions (name, description) VALUES
+('admin.access', 'Open the admin area'),
+('users.manage', 'Create, edit and deactivate users'),
+('roles.manage', 'Create roles and edit their permissions'),
+('security.manage', 'Change the tenant security policy'),
+('audit.view', 'View authentication events'),
+('api_keys.manage', 'Manage API keys'),
+('posts.view', 'View posts'),
+('posts.create', 'Create posts'),
+('posts.edit', 'Edit posts'),
+('posts.delete', 'Delete posts');
+
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT roles.id, permissions.id FROM roles CROSS JOIN permissions WHERE roles.name = 'admin';
+
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT roles.id, permissions.id FROM roles CROSS JOIN permissions
+WHERE roles.name IN ('user', 'dev') AND permissions.name IN ('posts.view', 'posts.create');
+
+INSERT INTO roles (name) SELECT DISTINCT role FROM users ON CONFLICT (name) DO NOTHING;
+
+ALTER TABLE users ADD CONSTRAINT users_role_fkey FOREIGN KEY (role) REFERENCES roles(name) ON UPDATE CASCADE;
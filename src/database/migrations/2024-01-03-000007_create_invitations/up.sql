@@ -0,0 +1,14 @@
+CREATE TABLE invitations (
+    id SERIAL PRIMARY KEY,
+    email TEXT NOT NULL,
+    role TEXT NOT NULL REFERENCES roles(name) ON UPDATE CASCADE ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    invited_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    accepted_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    expires_at BIGINT NOT NULL,
+    accepted_at BIGINT,
+    revoked_at BIGINT,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()))
+);
+
+CREATE INDEX invitations_email_idx ON invitations (LOWER(email));
@@ -0,0 +1,29 @@
+CREATE TABLE oidc_providers (
+    id SERIAL PRIMARY KEY,
+    slug TEXT NOT NULL UNIQUE,
+    display_name TEXT NOT NULL,
+    issuer TEXT NOT NULL,
+    client_id TEXT NOT NULL,
+    client_secret TEXT NOT NULL,
+    scopes TEXT NOT NULL DEFAULT 'openid email profile',
+    role_claim TEXT,
+    role_mapping TEXT NOT NULL DEFAULT '',
+    default_role TEXT NOT NULL DEFAULT 'user' REFERENCES roles(name) ON UPDATE CASCADE,
+    auto_provision BOOLEAN NOT NULL DEFAULT TRUE,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())),
+    updated_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()))
+);
+
+CREATE TABLE user_identities (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider_id INTEGER NOT NULL REFERENCES oidc_providers(id) ON DELETE CASCADE,
+    subject TEXT NOT NULL,
+    email TEXT,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())),
+    last_login_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())),
+    UNIQUE (provider_id, subject)
+);
+
+CREATE INDEX user_identities_user_id_idx ON user_identities (user_id);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS spark_migrations;
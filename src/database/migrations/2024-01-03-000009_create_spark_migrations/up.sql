@@ -0,0 +1,6 @@
+CREATE TABLE spark_migrations (
+    spark TEXT NOT NULL,
+    version TEXT NOT NULL,
+    applied_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())),
+    PRIMARY KEY (spark, version)
+);
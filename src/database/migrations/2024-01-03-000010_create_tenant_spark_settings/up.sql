@@ -0,0 +1,9 @@
+CREATE TABLE tenant_spark_settings (
+    id SERIAL PRIMARY KEY,
+    spark TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())),
+    updated_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())),
+    UNIQUE (spark, key)
+);
@@ -0,0 +1 @@
+ALTER TABLE oidc_providers DROP COLUMN link_existing_accounts;
@@ -0,0 +1 @@
+ALTER TABLE oidc_providers ADD COLUMN link_existing_accounts BOOLEAN NOT NULL DEFAULT FALSE;
@@ -42,6 +42,63 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    invitations (id) {
+        id -> Int4,
+        email -> Text,
+        role -> Text,
+        token_hash -> Text,
+        invited_by -> Nullable<Int4>,
+        accepted_by -> Nullable<Int4>,
+        expires_at -> Int8,
+        accepted_at -> Nullable<Int8>,
+        revoked_at -> Nullable<Int8>,
+        created_at -> Int8,
+    }
+}
+
+diesel::table! {
+    login_attempts (id) {
+        id -> Int4,
+        username -> Text,
+        user_id -> Nullable<Int4>,
+        ip_address -> Nullable<Text>,
+        user_agent -> Nullable<Text>,
+        success -> Bool,
+        failure_reason -> Nullable<Text>,
+        created_at -> Int8,
+    }
+}
+
+diesel::table! {
+    oidc_providers (id) {
+        id -> Int4,
+        slug -> Text,
+        display_name -> Text,
+        issuer -> Text,
+        client_id -> Text,
+        client_secret -> Text,
+        scopes -> Text,
+        role_claim -> Nullable<Text>,
+        role_mapping -> Text,
+        default_role -> Text,
+        auto_provision -> Bool,
+        enabled -> Bool,
+        created_at -> Int8,
+        updated_at -> Int8,
+        link_existing_accounts -> Bool,
+    }
+}
+
+diesel::table! {
+    permissions (id) {
+        id -> Int4,
+        name -> Text,
+        description -> Text,
+        created_at -> Int8,
+    }
+}
+
 diesel::table! {
     posts (id) {
         id -> Int4,
@@ -55,6 +112,33 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    role_permissions (role_id, permission_id) {
+        role_id -> Int4,
+        permission_id -> Int4,
+    }
+}
+
+diesel::table! {
+    roles (id) {
+        id -> Int4,
+        name -> Text,
+        description -> Text,
+        is_system -> Bool,
+        hidden -> Bool,
+        created_at -> Int8,
+        updated_at -> Int8,
+    }
+}
+
+diesel::table! {
+    spark_migrations (spark, version) {
+        spark -> Text,
+        version -> Text,
+        applied_at -> Int8,
+    }
+}
+
 diesel::table! {
     spatial_ref_sys (srid) {
         srid -> Int4,
@@ -68,6 +152,64 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    tenant_settings (id) {
+        id -> Int4,
+        key -> Text,
+        value -> Text,
+        created_at -> Int8,
+        updated_at -> Int8,
+    }
+}
+
+diesel::table! {
+    tenant_spark_settings (id) {
+        id -> Int4,
+        spark -> Text,
+        key -> Text,
+        value -> Text,
+        created_at -> Int8,
+        updated_at -> Int8,
+    }
+}
+
+diesel::table! {
+    user_identities (id) {
+        id -> Int4,
+        user_id -> Int4,
+        provider_id -> Int4,
+        subject -> Text,
+        email -> Nullable<Text>,
+        created_at -> Int8,
+        last_login_at -> Int8,
+    }
+}
+
+diesel::table! {
+    user_tokens (id) {
+        id -> Int4,
+        user_id -> Int4,
+        purpose -> Text,
+        token_hash -> Text,
+        expires_at -> Int8,
+        used_at -> Nullable<Int8>,
+        created_at -> Int8,
+    }
+}
+
+diesel::table! {
+    user_two_factor (id) {
+        id -> Int4,
+        user_id -> Int4,
+        secret -> Text,
+        enabled -> Bool,
+        recovery_codes -> Jsonb,
+        last_used_step -> Nullable<Int8>,
+        created_at -> Int8,
+        updated_at -> Int8,
+    }
+}
+
 diesel::table! {
     users (id) {
         id -> Int4,
@@ -81,12 +223,39 @@ diesel::table! {
         should_change_password -> Bool,
         created_at -> Int8,
         updated_at -> Int8,
+        email_verified_at -> Nullable<Int8>,
     }
 }
 
 diesel::joinable!(api_keys -> users (user_id));
 diesel::joinable!(api_request_logs -> api_keys (api_key_id));
 diesel::joinable!(api_response_logs -> api_request_logs (request_log_id));
+diesel::joinable!(login_attempts -> users (user_id));
 diesel::joinable!(posts -> users (user_id));
+diesel::joinable!(role_permissions -> permissions (permission_id));
+diesel::joinable!(role_permissions -> roles (role_id));
+diesel::joinable!(user_identities -> oidc_providers (provider_id));
+diesel::joinable!(user_identities -> users (user_id));
+diesel::joinable!(user_tokens -> users (user_id));
+diesel::joinable!(user_two_factor -> users (user_id));
 
-diesel::allow_tables_to_appear_in_same_query!(api_keys, api_request_logs, api_response_logs, posts, spatial_ref_sys, users,);
+diesel::allow_tables_to_appear_in_same_query!(
+    api_keys,
+    api_request_logs,
+    api_response_logs,
+    invitations,
+    login_attempts,
+    oidc_providers,
+    permissions,
+    posts,
+    role_permissions,
+    roles,
+    spark_migrations,
+    spatial_ref_sys,
+    tenant_settings,
+    tenant_spark_settings,
+    user_identities,
+    user_tokens,
+    user_two_factor,
+    users,
+);
@@ -25,23 +25,42 @@ use services::*;
 
 #[launch]
 async fn rocket() -> _ {
-    bootstrap().await;
+    let report = bootstrap().await.unwrap_or_else(|e| {
+        cata_log!(Error, e.to_string());
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+
+    if !report.warnings.is_empty() {
+        cata_log!(Warning, format!("Bootstrap finished with {} warning(s)", report.warnings.len()));
+    }
+
     cata_log!(Info, "Starting server...");
     let mut rocket_app = rocket::build()
         .mount("/", home::routes())
         .mount("/", with_guard::<TenantAdminGuard>(admin_routes()))
         .mount("/", with_guard::<TenantAdminGuard>(admin_partial_routes()))
+        .mount("/", with_guard::<Permission<UsersManage>>(admin_user_routes()))
+        .mount("/", with_guard::<Permission<UsersManage>>(admin_user_partial_routes()))
+        .mount("/", with_guard::<Permission<RolesManage>>(admin_role_routes()))
+        .mount("/", with_guard::<Permission<SecurityManage>>(admin_security_routes()))
+        .mount("/", with_guard::<Permission<SecurityManage>>(admin_sso_routes()))
+        .mount("/", with_guard::<Permission<AuditView>>(admin_audit_routes()))
         .mount("/", with_guard::<TenantUserGuard>(user_routes()))
         .mount("/", with_guard::<TenantUserGuard>(user_partial_routes()))
         .mount("/", with_guard::<ApiKeyGuard>(api_v1_routes()))
+        .mount("/", api_auth_routes())
+        .mount("/", oidc_routes())
+        .mount("/", spark_asset_routes())
         .mount("/public", FileServer::from(relative!("public")))
         .mount("/", with_guard::<vessel::guards::VesselHomeGuard>(vessel::dashboard_routes()))
         .mount("/", vessel::auth_routes())
         .register("/", catchers![unauthorized, forbidden, not_found, internal_error, unprocessable_entity])
-        .attach(Template::fairing())
+        .attach(Template::custom(sparks::register_spark_templates))
         .attach(rocket_csrf_token::Fairing::default())
         .attach(api_logger::ApiLogFairing)
         .attach(sparks::SparkLoggingFairing)
+        .attach(sparks::SparkLifecycleFairing)
         .all_sparks();
 
     if let Some(config) = APP_CONFIG.get() {
@@ -26,6 +26,8 @@ pub enum MeltType {
     InvalidToken,
     MissingToken,
     InsufficientPermissions,
+    PasswordChangeRequired,
+    AccountLocked,
 
     ValidationFailed,
     InvalidInput,
@@ -107,6 +109,8 @@ impl MeltDown {
             MeltType::InvalidToken => "Invalid authentication token.".to_string(),
             MeltType::MissingToken => "Authentication required.".to_string(),
             MeltType::InsufficientPermissions => "You don't have permission to perform this action.".to_string(),
+            MeltType::PasswordChangeRequired => "You must change your password before continuing.".to_string(),
+            MeltType::AccountLocked => "Too many failed login attempts. Please try again later.".to_string(),
 
             MeltType::ValidationFailed => {
                 if self.details.is_empty() {
@@ -152,7 +156,7 @@ impl MeltDown {
         message
     }
 
-    fn melt_type_str(&self) -> &'static str {
+    pub fn melt_type_str(&self) -> &'static str {
         match self.melt_type {
             MeltType::DatabaseConnection => "DatabaseConnection",
             MeltType::DatabaseError => "DatabaseError",
@@ -167,6 +171,8 @@ impl MeltDown {
             MeltType::InvalidToken => "InvalidToken",
             MeltType::MissingToken => "MissingToken",
             MeltType::InsufficientPermissions => "InsufficientPermissions",
+            MeltType::PasswordChangeRequired => "PasswordChangeRequired",
+            MeltType::AccountLocked => "AccountLocked",
             MeltType::ValidationFailed => "ValidationFailed",
             MeltType::InvalidInput => "InvalidInput",
             MeltType::MissingField => "MissingField",
@@ -196,6 +202,8 @@ impl MeltDown {
             MeltType::InvalidToken => Status::Unauthorized,
             MeltType::MissingToken => Status::Unauthorized,
             MeltType::InsufficientPermissions => Status::Forbidden,
+            MeltType::PasswordChangeRequired => Status::Forbidden,
+            MeltType::AccountLocked => Status::TooManyRequests,
             MeltType::ValidationFailed => Status::BadRequest,
             MeltType::InvalidInput => Status::BadRequest,
             MeltType::MissingField => Status::BadRequest,
@@ -6,7 +6,7 @@ use rocket::{
 };
 use serde_json::Value as JsonValue;
 
-use crate::{cata_log, structs::*};
+use crate::{cata_log, middleware::jwt::looks_like_jwt, structs::*};
 
 fn extract_tenant_from_path(path: &str) -> Option<String> {
     let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
@@ -46,7 +46,7 @@ impl Fairing for ApiLogFairing {
 
     async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
         if let Some(auth) = request.headers().get_one("Authorization") {
-            if auth.starts_with("Bearer ") {
+            if auth.starts_with("Bearer ") && !looks_like_jwt(auth.trim_start_matches("Bearer ").trim()) {
                 let token = auth.trim_start_matches("Bearer ").trim();
 
                 let request_path = request.uri().path().to_string();
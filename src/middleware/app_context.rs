@@ -21,6 +21,7 @@ pub struct AppContext<'r> {
     requires_csrf: bool,
     tenant_name: Option<String>,
     request_uri: String,
+    impersonation: Option<(String, String)>,
 }
 
 #[derive(Serialize, Debug, Default)]
@@ -34,6 +35,8 @@ pub struct BaseContext {
     pub sparks: TemplateComponentsView,
     pub tenant_name: Option<String>,
     pub request_uri: String,
+    pub impersonated_by: Option<String>,
+    pub impersonated_user: Option<String>,
 }
 
 #[derive(Serialize, Debug)]
@@ -59,6 +62,8 @@ pub static TRANSLATIONS: Lazy<HashMap<String, Value>> = Lazy::new(|| {
         }
     }
 
+    merge_spark_translations(&mut map);
+
     map
 });
 
@@ -98,6 +103,15 @@ impl<'r> FromRequest<'r> for AppContext<'r> {
             }
         });
 
+        let impersonation = match req.cookies().get(IMPERSONATOR_ACCESS_COOKIE) {
+            Some(_) => req
+                .cookies()
+                .get("access_token")
+                .and_then(|cookie| validate_token(cookie.value()).ok())
+                .and_then(|claims| claims.impersonated_by.map(|impersonator| (impersonator, claims.username))),
+            None => None,
+        };
+
         Success(AppContext {
             cookies: req.cookies(),
             csrf_token,
@@ -105,6 +119,7 @@ impl<'r> FromRequest<'r> for AppContext<'r> {
             requires_csrf,
             tenant_name,
             request_uri: req.uri().path().to_string(),
+            impersonation,
         })
     }
 }
@@ -183,6 +198,8 @@ impl<'r> AppContext<'r> {
             sparks,
             tenant_name: self.tenant_name.clone(),
             request_uri: self.request_uri.clone(),
+            impersonated_by: self.impersonation.as_ref().map(|(impersonator, _)| impersonator.clone()),
+            impersonated_user: self.impersonation.as_ref().map(|(_, username)| username.clone()),
         }
     }
 
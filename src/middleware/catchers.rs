@@ -8,7 +8,7 @@ use rocket::{
 use rocket_dyn_templates::Template;
 use serde_json::json;
 
-use super::app_context;
+use super::{app_context, guards::PendingPasswordChange};
 use crate::{cata_log, meltdown::*, routes::*};
 
 fn extract_tenant_name(req: &Request) -> String {
@@ -51,6 +51,22 @@ pub fn unauthorized(req: &Request) -> Result<Redirect, Json<serde_json::Value>>
 pub fn forbidden(req: &Request) -> Result<Redirect, Json<serde_json::Value>> {
     cata_log!(Warning, format!("Forbidden access attempt to {}", req.uri()));
 
+    if let PendingPasswordChange(Some(tenant)) = req.local_cache(PendingPasswordChange::default) {
+        if is_api_path(req) || accepts_json(req) {
+            let error = MeltDown::new(MeltType::PasswordChangeRequired, "Password change required");
+            return Err(Json(json!({
+                "error": {
+                    "code": 403,
+                    "type": error.melt_type_str(),
+                    "message": error.user_message(),
+                    "change_password_url": format!("/{}/api/v1/auth/change_password", tenant)
+                }
+            })));
+        }
+
+        return Ok(Redirect::to(format!("/{}/auth/change_password", tenant)));
+    }
+
     if req.uri().path().starts_with("/api") || accepts_json(req) {
         let error = MeltDown::new(MeltType::Forbidden, "Insufficient permissions");
         return Err(Json(json!({
@@ -98,6 +114,8 @@ pub fn not_found(req: &Request) -> Result<Template, Json<serde_json::Value>> {
         sparks: crate::services::makeuse::get_template_components(true),
         tenant_name: Some(extract_tenant_name(req)),
         request_uri: req.uri().path().to_string(),
+        impersonated_by: None,
+        impersonated_user: None,
     };
 
     let mut map = serde_json::Map::new();
@@ -167,6 +185,11 @@ pub fn internal_error(req: &Request) -> Result<Redirect, Json<serde_json::Value>
     Ok(Redirect::to(format!("/{}/not-found", tenant)))
 }
 
+fn is_api_path(request: &Request) -> bool {
+    let path = request.uri().path();
+    path.starts_with("/api") || path.segments().nth(1) == Some("api")
+}
+
 fn accepts_json(request: &Request) -> bool {
     request.headers().get("Accept").any(|v| v.contains("application/json"))
 }
@@ -203,6 +226,8 @@ impl<'r> Responder<'r, 'static> for MeltDown {
                         sparks: crate::services::makeuse::get_template_components(true),
                         tenant_name: Some(tenant.clone()),
                         request_uri: req.uri().path().to_string(),
+                        impersonated_by: None,
+                        impersonated_user: None,
                     };
                     let mut map = serde_json::Map::new();
                     map.insert(
@@ -233,6 +258,8 @@ impl<'r> Responder<'r, 'static> for MeltDown {
                         sparks: crate::services::makeuse::get_template_components(true),
                         tenant_name: Some(tenant.clone()),
                         request_uri: req.uri().path().to_string(),
+                        impersonated_by: None,
+                        impersonated_user: None,
                     };
                     let mut map = serde_json::Map::new();
                     map.insert(
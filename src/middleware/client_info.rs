@@ -0,0 +1,32 @@
+use std::convert::Infallible;
+
+use rocket::{
+    async_trait,
+    request::{FromRequest, Outcome, Request},
+};
+
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl ClientInfo {
+    pub fn from_request(req: &Request<'_>) -> Self {
+        ClientInfo {
+            ip_address: req.client_ip().map(|ip| ip.to_string()),
+            user_agent: req.headers().get_one("User-Agent").map(|agent| agent.chars().take(MAX_USER_AGENT_LENGTH).collect()),
+        }
+    }
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for ClientInfo {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(ClientInfo::from_request(req))
+    }
+}
@@ -23,6 +23,18 @@ fn extract_tenant_from_path(path: &str) -> Option<String> {
     None
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct PendingPasswordChange(pub Option<String>);
+
+pub fn password_change_required<T>(req: &Request<'_>, jwt: &JWT) -> Outcome<T, MeltDown> {
+    let tenant_name = jwt.get_tenant_name().cloned();
+    req.local_cache(|| PendingPasswordChange(tenant_name.clone()));
+
+    cata_log!(Info, format!("User {} must change their password before accessing {}", jwt.get_username(), req.uri().path()));
+    let error = MeltDown::new(MeltType::PasswordChangeRequired, format!("User {} has a pending password change", jwt.get_username()));
+    Error((Status::Forbidden, error))
+}
+
 pub struct AdminGuard;
 
 #[async_trait]
@@ -32,7 +44,7 @@ impl<'r> FromRequest<'r> for AdminGuard {
     async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
         match req.guard::<JWT>().await {
             Success(jwt) => {
-                if jwt.is_admin() {
+                if jwt.is_admin().await {
                     Success(AdminGuard)
                 } else {
                     let error = MeltDown::new(MeltType::Forbidden, "Insufficient permissions to access admin area");
@@ -77,6 +89,11 @@ impl<'r> FromRequest<'r> for ApiKeyGuard {
                     return Error((Status::Unauthorized, error));
                 }
 
+                if looks_like_jwt(token) {
+                    let error = MeltDown::new(MeltType::Unauthorized, "JWT bearer tokens are not accepted as API keys");
+                    return Error((Status::Unauthorized, error));
+                }
+
                 let request_path = req.uri().path().to_string();
                 let tenant_name = extract_tenant_from_path(&request_path).unwrap_or_else(|| "main".to_string());
 
@@ -17,6 +17,8 @@ pub enum TokenType {
     Access,
     #[serde(rename = "refresh")]
     Refresh,
+    #[serde(rename = "two_factor")]
+    TwoFactor,
 }
 
 impl Default for TokenType {
@@ -58,12 +60,16 @@ pub struct Claims {
     pub token_type: TokenType,
     #[serde(default)]
     pub auth_system: AuthSystem,
+    #[serde(default)]
+    pub password_change_required: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub refresh_jti: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub device_info: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tenant_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonated_by: Option<String>,
 }
 
 fn default_version() -> u32 {
@@ -78,8 +84,30 @@ impl JWT {
         self.0.sub.parse::<i32>().unwrap()
     }
 
-    pub fn is_admin(&self) -> bool {
-        self.0.role == "admin"
+    pub async fn has_permission(&self, permission: &str) -> bool {
+        let tenant_name = match self.get_tenant_name() {
+            Some(tenant_name) if self.is_tenant_auth() => tenant_name,
+            _ => return false,
+        };
+
+        match permission_registry::role_has_permission(tenant_name, &self.0.role, permission).await {
+            Ok(granted) => granted,
+            Err(error) => {
+                cata_log!(
+                    Error,
+                    format!("Failed to resolve permission {} for role {} (tenant: {}): {}", permission, self.0.role, tenant_name, error.log_message())
+                );
+                false
+            }
+        }
+    }
+
+    pub async fn is_admin(&self) -> bool {
+        self.has_permission(ADMIN_ACCESS).await
+    }
+
+    pub fn is_vessel_owner(&self) -> bool {
+        self.is_vessel_auth() && self.0.role == VESSEL_ROLE
     }
 
     pub fn get_role(&self) -> &str {
@@ -134,6 +162,10 @@ impl JWT {
         &self.0.auth_system
     }
 
+    pub fn password_change_required(&self) -> bool {
+        self.0.password_change_required
+    }
+
     pub fn is_vessel_auth(&self) -> bool {
         self.0.auth_system == AuthSystem::Vessel
     }
@@ -141,6 +173,57 @@ impl JWT {
     pub fn is_tenant_auth(&self) -> bool {
         self.0.auth_system == AuthSystem::Tenant
     }
+
+    pub fn impersonated_by(&self) -> Option<&String> {
+        self.0.impersonated_by.as_ref()
+    }
+
+    pub fn is_impersonated(&self) -> bool {
+        self.0.impersonated_by.is_some()
+    }
+
+    pub fn refuse_impersonation(&self, action: &str) -> Result<(), MeltDown> {
+        let Some(impersonator) = self.impersonated_by() else {
+            return Ok(());
+        };
+
+        cata_log!(
+            Warning,
+            format!(
+                "Blocked {} for {} during impersonation by vessel owner {} (tenant: {})",
+                action,
+                self.get_username(),
+                impersonator,
+                self.get_tenant_name().map(|name| name.as_str()).unwrap_or("unknown")
+            )
+        );
+
+        Err(MeltDown::new(MeltType::Forbidden, format!("{} refused during impersonation", action)).with_user_message("Account credentials cannot be changed while impersonating."))
+    }
+}
+
+struct ImpersonationAudited;
+
+fn audit_impersonated_request(request: &Request<'_>, claims: &Claims) {
+    let Some(impersonator) = claims.impersonated_by.as_ref() else {
+        return;
+    };
+
+    request.local_cache(|| {
+        cata_log!(
+            Info,
+            format!(
+                "Impersonated request: {} {} as {} by vessel owner {} (tenant: {}, jti: {})",
+                request.method(),
+                request.uri(),
+                claims.username,
+                impersonator,
+                claims.tenant_name.as_deref().unwrap_or("unknown"),
+                claims.jti
+            )
+        );
+        ImpersonationAudited
+    });
 }
 
 #[async_trait]
@@ -150,10 +233,26 @@ impl<'r> FromRequest<'r> for JWT {
     async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
         let cookies = request.cookies();
 
+        if let Some(token) = bearer_jwt(request) {
+            return match validate_token(&token) {
+                Ok(claims) => match check_access_claims(&claims) {
+                    Ok(()) => {
+                        audit_impersonated_request(request, &claims);
+                        Outcome::Success(JWT(claims))
+                    }
+                    Err(error) => Outcome::Error((error.status_code(), error)),
+                },
+                Err(error) => {
+                    cata_log!(Warning, error.log_message());
+                    Outcome::Error((error.status_code(), error))
+                }
+            };
+        }
+
         let token_cookie = match cookies.get("access_token") {
             Some(cookie) => cookie,
             None => {
-                let error = MeltDown::new(MeltType::MissingToken, "No access token in cookies");
+                let error = MeltDown::new(MeltType::MissingToken, "No access token in cookies or Authorization header");
                 cookies.remove(Cookie::new("access_token", ""));
                 cookies.remove(Cookie::new("user_id", ""));
                 return Outcome::Error((error.status_code(), error));
@@ -164,15 +263,7 @@ impl<'r> FromRequest<'r> for JWT {
 
         match validate_token(&token) {
             Ok(claims) => {
-                if claims.token_type == TokenType::Refresh {
-                    let error = MeltDown::new(MeltType::InvalidToken, "Refresh token cannot be used for authentication");
-                    cata_log!(Warning, error.log_message());
-                    return Outcome::Error((error.status_code(), error));
-                }
-
-                if claims.sub.parse::<i32>().is_err() {
-                    let error = MeltDown::new(MeltType::InvalidToken, "Invalid user ID format in JWT");
-                    cata_log!(Warning, error.log_message());
+                if let Err(error) = check_access_claims(&claims) {
                     return Outcome::Error((error.status_code(), error));
                 }
 
@@ -188,6 +279,7 @@ impl<'r> FromRequest<'r> for JWT {
                     }
                 }
 
+                audit_impersonated_request(request, &claims);
                 Outcome::Success(JWT(claims))
             }
             Err(e) => {
@@ -202,6 +294,42 @@ impl<'r> FromRequest<'r> for JWT {
     }
 }
 
+fn check_access_claims(claims: &Claims) -> Result<(), MeltDown> {
+    if claims.token_type != TokenType::Access {
+        let message = match claims.token_type {
+            TokenType::TwoFactor => "Two-factor verification is still pending for this token",
+            _ => "Refresh token cannot be used for authentication",
+        };
+        let error = MeltDown::new(MeltType::InvalidToken, message);
+        cata_log!(Warning, error.log_message());
+        return Err(error);
+    }
+
+    if claims.sub.parse::<i32>().is_err() {
+        let error = MeltDown::new(MeltType::InvalidToken, "Invalid user ID format in JWT");
+        cata_log!(Warning, error.log_message());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+pub fn looks_like_jwt(token: &str) -> bool {
+    let segments: Vec<&str> = token.split('.').collect();
+    segments.len() == 3 && segments.iter().all(|segment| !segment.is_empty() && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+}
+
+pub fn bearer_jwt(request: &Request<'_>) -> Option<String> {
+    let value = request.headers().get_one("Authorization")?;
+    let token = value.strip_prefix("Bearer ")?.trim();
+
+    if looks_like_jwt(token) {
+        Some(token.to_string())
+    } else {
+        None
+    }
+}
+
 pub async fn jwt_to_user(jwt_token: &str, tenant_name: &str) -> Result<Users, MeltDown> {
     let claims = validate_token(jwt_token)?;
 
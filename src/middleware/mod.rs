@@ -2,11 +2,14 @@ pub mod api_logger;
 pub mod app_context;
 pub mod cache;
 pub mod catchers;
+pub mod client_info;
 pub mod compress;
 pub mod enforcer;
 pub mod guards;
 pub mod htmx;
 pub mod jwt;
+pub mod permissions;
+pub mod spark_guard;
 pub mod tenant;
 pub mod tenant_admin_guard;
 pub mod tenant_user_guard;
@@ -15,11 +18,14 @@ pub use api_logger::*;
 pub use app_context::*;
 pub use cache::*;
 pub use catchers::*;
+pub use client_info::*;
 pub use compress::*;
 pub use enforcer::*;
 pub use guards::*;
 pub use htmx::*;
 pub use jwt::*;
+pub use permissions::*;
+pub use spark_guard::*;
 pub use tenant::*;
 pub use tenant_admin_guard::*;
 pub use tenant_user_guard::*;
@@ -0,0 +1,71 @@
+use std::marker::PhantomData;
+
+use rocket::{
+    async_trait,
+    http::Status,
+    outcome::Outcome::{Error, Forward, Success},
+    request::{FromRequest, Outcome, Request},
+};
+
+use crate::{cata_log, meltdown::*, middleware::*, structs::*};
+
+pub trait PermissionName: Send + Sync + 'static {
+    const NAME: &'static str;
+}
+
+#[macro_export]
+macro_rules! permission {
+    ($marker:ident, $name:expr) => {
+        pub struct $marker;
+
+        impl $crate::middleware::PermissionName for $marker {
+            const NAME: &'static str = $name;
+        }
+    };
+}
+
+permission!(AdminAccess, ADMIN_ACCESS);
+permission!(UsersManage, USERS_MANAGE);
+permission!(RolesManage, ROLES_MANAGE);
+permission!(SecurityManage, SECURITY_MANAGE);
+permission!(AuditView, AUDIT_VIEW);
+permission!(ApiKeysManage, API_KEYS_MANAGE);
+permission!(PostsView, POSTS_VIEW);
+permission!(PostsCreate, POSTS_CREATE);
+permission!(PostsEdit, POSTS_EDIT);
+permission!(PostsDelete, POSTS_DELETE);
+
+pub struct Permission<P: PermissionName> {
+    pub tenant_name: String,
+    _permission: PhantomData<P>,
+}
+
+#[async_trait]
+impl<'r, P: PermissionName> FromRequest<'r> for Permission<P> {
+    type Error = MeltDown;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let tenant_name = match req.guard::<TenantUserGuard>().await {
+            Success(guard) => guard.tenant_name,
+            Error((status, error)) => return Error((status, error)),
+            Forward(status) => return Forward(status),
+        };
+
+        let jwt = match req.guard::<JWT>().await {
+            Success(jwt) => jwt,
+            Error((status, error)) => return Error((status, error)),
+            Forward(status) => return Forward(status),
+        };
+
+        if !jwt.has_permission(P::NAME).await {
+            let error = MeltDown::new(MeltType::Forbidden, format!("Missing permission: {}", P::NAME)).with_user_message("You do not have permission to access this page.");
+            cata_log!(
+                Warning,
+                format!("User {} with role {} lacks permission {} for path: {}", jwt.get_username(), jwt.get_role(), P::NAME, req.uri().path())
+            );
+            return Error((Status::Forbidden, error));
+        }
+
+        Success(Permission { tenant_name, _permission: PhantomData })
+    }
+}
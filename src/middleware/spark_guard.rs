@@ -0,0 +1,91 @@
+use std::marker::PhantomData;
+
+use rocket::{
+    async_trait,
+    http::Status,
+    outcome::Outcome::{Error, Success},
+    request::{FromRequest, Outcome, Request},
+};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    cata_log,
+    meltdown::*,
+    middleware::*,
+    services::sparks::{is_active_spark, spark_config, spark_config_for_tenant},
+    vessel::structs::{Tenant, TenantSpark},
+};
+
+pub trait SparkName: Send + Sync + 'static {
+    const NAME: &'static str;
+}
+
+#[macro_export]
+macro_rules! spark_name {
+    ($marker:ident, $name:expr) => {
+        pub struct $marker;
+
+        impl $crate::middleware::SparkName for $marker {
+            const NAME: &'static str = $name;
+        }
+    };
+}
+
+pub struct SparkEnabled<S: SparkName> {
+    pub tenant_name: Option<String>,
+    _spark: PhantomData<S>,
+}
+
+impl<S: SparkName> SparkEnabled<S> {
+    pub async fn config<T: DeserializeOwned>(&self) -> Result<T, String> {
+        match &self.tenant_name {
+            Some(tenant_name) => spark_config_for_tenant(S::NAME, tenant_name).await,
+            None => spark_config(S::NAME),
+        }
+    }
+}
+
+fn spark_not_found<T>(spark: &str) -> Outcome<T, MeltDown> {
+    Error((Status::NotFound, MeltDown::new(MeltType::NotFound, format!("Spark '{}' is not enabled", spark))))
+}
+
+#[async_trait]
+impl<'r, S: SparkName> FromRequest<'r> for SparkEnabled<S> {
+    type Error = MeltDown;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        if !is_active_spark(S::NAME) {
+            cata_log!(Warning, format!("Request to inactive spark '{}' at path: {}", S::NAME, req.uri().path()));
+            return spark_not_found(S::NAME);
+        }
+
+        let jwt_tenant_name = match req.guard::<JWT>().await {
+            Success(jwt) if jwt.is_tenant_auth() => jwt.get_tenant_name().cloned(),
+            _ => None,
+        };
+
+        let tenant_name = match jwt_tenant_name {
+            Some(tenant_name) => Some(tenant_name),
+            None => match req.uri().path().segments().get(0).filter(|segment| !segment.is_empty() && *segment != "vessel") {
+                Some(segment) => match Tenant::find_by_name(segment).await {
+                    Ok(tenant) => tenant.map(|tenant| tenant.name),
+                    Err(error) => return Error((Status::InternalServerError, error)),
+                },
+                None => None,
+            },
+        };
+
+        if let Some(tenant_name) = &tenant_name {
+            match TenantSpark::is_enabled_for_tenant(tenant_name, S::NAME).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    cata_log!(Info, format!("Spark '{}' is disabled for tenant {}, path: {}", S::NAME, tenant_name, req.uri().path()));
+                    return spark_not_found(S::NAME);
+                }
+                Err(error) => return Error((Status::InternalServerError, error)),
+            }
+        }
+
+        Success(SparkEnabled { tenant_name, _spark: PhantomData })
+    }
+}
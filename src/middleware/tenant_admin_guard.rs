@@ -45,7 +45,11 @@ impl<'r> FromRequest<'r> for TenantAdminGuard {
                     return Error((Status::Forbidden, error));
                 }
 
-                if !jwt.is_admin() {
+                if jwt.password_change_required() {
+                    return password_change_required(req, &jwt);
+                }
+
+                if !jwt.is_admin().await {
                     let error = MeltDown::new(MeltType::Forbidden, "Insufficient permissions to access admin area");
                     cata_log!(Warning, format!("Non-admin user tried to access admin path: {}", path));
                     return Error((Status::Forbidden, error));
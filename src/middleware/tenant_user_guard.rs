@@ -51,7 +51,13 @@ impl<'r> FromRequest<'r> for TenantUserGuard {
             return Error((Status::Forbidden, error));
         }
 
-        if jwt.is_admin() {
+        if jwt.password_change_required() {
+            return password_change_required(req, &jwt);
+        }
+
+        let is_admin = jwt.is_admin().await;
+
+        if is_admin {
             if let Some(jwt_tenant) = jwt.get_tenant_name() {
                 if let Some(uri_tenant) = &uri_tenant_name {
                     if jwt_tenant != uri_tenant {
@@ -72,7 +78,7 @@ impl<'r> FromRequest<'r> for TenantUserGuard {
         let jwt_tenant_name = jwt.get_tenant_name().cloned();
 
         if let Some(uri_tenant) = uri_tenant_name {
-            if !jwt.is_admin() {
+            if !is_admin {
                 if let Some(jwt_tenant) = &jwt_tenant_name {
                     if *jwt_tenant != uri_tenant {
                         let error = MeltDown::new(MeltType::Forbidden, format!("User has no access to tenant: {}", uri_tenant));
@@ -0,0 +1,211 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use rand::{distributions::Alphanumeric, Rng};
+
+use crate::{
+    cata_log,
+    database::{db::establish_connection_with_tenant, schema::invitations::dsl as invitation_dsl},
+    meltdown::*,
+    services::default::{
+        mailer::{self, OutgoingMail},
+        signed_token,
+    },
+    structs::*,
+};
+
+const INVITATION_TOKEN_LENGTH: usize = 40;
+const INVITATIONS_PAGE_LIMIT: i64 = 100;
+
+fn invalid_invitation() -> MeltDown {
+    MeltDown::new(MeltType::InvalidToken, "Invitation is unknown, used, revoked or expired").with_user_message("This invitation link is invalid or has expired.")
+}
+
+fn email_domain(email: &str) -> Option<String> {
+    email.trim().rsplit_once('@').map(|(_, domain)| domain.to_lowercase()).filter(|domain| !domain.is_empty())
+}
+
+impl RegistrationPolicy {
+    pub fn parse_domains(input: &str) -> Vec<String> {
+        let mut domains: Vec<String> = input
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .map(|domain| domain.trim().trim_start_matches('@').to_lowercase())
+            .filter(|domain| !domain.is_empty())
+            .collect();
+
+        domains.sort();
+        domains.dedup();
+        domains
+    }
+
+    pub async fn load(tenant_name: &str) -> Result<Self, MeltDown> {
+        let mode = match TenantSettings::get_value(REGISTRATION_MODE, tenant_name).await? {
+            Some(value) => RegistrationMode::parse(&value).unwrap_or_else(|| {
+                cata_log!(Warning, format!("Unknown registration mode '{}' (tenant: {}), treating registration as disabled", value, tenant_name));
+                RegistrationMode::Disabled
+            }),
+            None => RegistrationMode::default(),
+        };
+
+        let allowed_domains = Self::parse_domains(&TenantSettings::get_value(REGISTRATION_ALLOWED_DOMAINS, tenant_name).await?.unwrap_or_default());
+
+        Ok(RegistrationPolicy { mode, allowed_domains })
+    }
+
+    pub async fn save(&self, tenant_name: &str) -> Result<(), MeltDown> {
+        if self.mode == RegistrationMode::AllowedDomains && self.allowed_domains.is_empty() {
+            return Err(MeltDown::new(MeltType::ValidationFailed, "Domain-restricted registration without domains").with_user_message("Enter at least one allowed email domain."));
+        }
+
+        TenantSettings::set_value(REGISTRATION_MODE, self.mode.as_str(), tenant_name).await?;
+        TenantSettings::set_value(REGISTRATION_ALLOWED_DOMAINS, &self.allowed_domains.join(","), tenant_name).await
+    }
+
+    pub fn email_domain_allowed(&self, email: &str) -> bool {
+        email_domain(email).is_some_and(|domain| self.allowed_domains.iter().any(|allowed| *allowed == domain))
+    }
+
+    pub fn check_open(&self) -> Result<(), MeltDown> {
+        match self.mode {
+            RegistrationMode::Disabled => Err(MeltDown::new(MeltType::Forbidden, "Registration is disabled").with_user_message("Registration is closed for this organisation.")),
+            RegistrationMode::InviteOnly => Err(MeltDown::new(MeltType::Forbidden, "Registration requires an invitation").with_user_message("Registration is by invitation only.")),
+            RegistrationMode::Open | RegistrationMode::AllowedDomains => Ok(()),
+        }
+    }
+
+    pub fn check(&self, email: &str, invitation: Option<&Invitation>) -> Result<(), MeltDown> {
+        if self.mode == RegistrationMode::Disabled {
+            return self.check_open();
+        }
+
+        if let Some(invitation) = invitation {
+            if !invitation.email.eq_ignore_ascii_case(email.trim()) {
+                return Err(MeltDown::new(MeltType::ValidationFailed, "Registration email does not match invitation").with_user_message("This invitation was issued for a different email address."));
+            }
+            return Ok(());
+        }
+
+        self.check_open()?;
+
+        if self.mode == RegistrationMode::AllowedDomains && !self.email_domain_allowed(email) {
+            return Err(MeltDown::new(MeltType::Forbidden, "Email domain not allowed for registration")
+                .with_context("email_domain", email_domain(email).unwrap_or_default())
+                .with_user_message("Registration is restricted to approved email domains."));
+        }
+
+        Ok(())
+    }
+}
+
+impl Invitation {
+    pub async fn create(email: &str, role: &str, actor_role: &str, lifetime_days: i64, invited_by: Option<i32>, tenant_name: &str) -> Result<(Invitation, String), MeltDown> {
+        let email = email.trim().to_string();
+        if email_domain(&email).is_none() {
+            return Err(MeltDown::invalid_input("Invitation email").with_user_message("Please enter a valid email address."));
+        }
+
+        let role = Users::grantable_role(role, actor_role, tenant_name).await?;
+
+        let token: String = rand::thread_rng().sample_iter(&Alphanumeric).take(INVITATION_TOKEN_LENGTH).map(char::from).collect();
+        let lifetime_days = lifetime_days.clamp(1, MAX_INVITATION_LIFETIME_DAYS);
+
+        let new_invitation = NewInvitation {
+            email,
+            role,
+            token_hash: signed_token::hash_token(&token),
+            invited_by,
+            expires_at: Utc::now().timestamp() + lifetime_days * 24 * 60 * 60,
+        };
+
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        let invitation = diesel::insert_into(invitation_dsl::invitations)
+            .values(&new_invitation)
+            .get_result::<Invitation>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "create_invitation"))?;
+
+        Ok((invitation, token))
+    }
+
+    pub fn link(token: &str, tenant_name: &str) -> String {
+        format!("{}/{}/auth/register?invite={}", mailer::mail_settings().base_url.trim_end_matches('/'), tenant_name, token)
+    }
+
+    pub async fn send(&self, token: &str, tenant_name: &str) -> Result<(), MeltDown> {
+        mailer::send_mail(OutgoingMail {
+            to: self.email.clone(),
+            subject: "You have been invited".to_string(),
+            body: format!(
+                "Hello,\n\nYou have been invited to create an account. Use the link below to register with this email address:\n\n{}\n\nThe invitation expires on {} (UTC).\n",
+                Self::link(token, tenant_name),
+                chrono::DateTime::from_timestamp(self.expires_at, 0).map(|expiry| expiry.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default()
+            ),
+        })
+        .await?;
+
+        cata_log!(Info, format!("Invitation {} sent (tenant: {})", self.id, tenant_name));
+        Ok(())
+    }
+
+    pub async fn find_valid(token: &str, tenant_name: &str) -> Result<Invitation, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        invitation_dsl::invitations
+            .filter(invitation_dsl::token_hash.eq(signed_token::hash_token(token)))
+            .filter(invitation_dsl::accepted_at.is_null())
+            .filter(invitation_dsl::revoked_at.is_null())
+            .filter(invitation_dsl::expires_at.gt(Utc::now().timestamp()))
+            .first::<Invitation>(&mut conn)
+            .await
+            .optional()
+            .map_err(|e| MeltDown::from(e).with_context("operation", "find_invitation"))?
+            .ok_or_else(invalid_invitation)
+    }
+
+    pub async fn recent(tenant_name: &str) -> Result<Vec<Invitation>, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        invitation_dsl::invitations
+            .order(invitation_dsl::created_at.desc())
+            .limit(INVITATIONS_PAGE_LIMIT)
+            .load::<Invitation>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "list_invitations"))
+    }
+
+    pub async fn revoke(id: i32, tenant_name: &str) -> Result<Invitation, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        diesel::update(
+            invitation_dsl::invitations
+                .filter(invitation_dsl::id.eq(id))
+                .filter(invitation_dsl::accepted_at.is_null())
+                .filter(invitation_dsl::revoked_at.is_null()),
+        )
+        .set(invitation_dsl::revoked_at.eq(Some(Utc::now().timestamp())))
+        .get_result::<Invitation>(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| MeltDown::from(e).with_context("operation", "revoke_invitation").with_context("invitation_id", id.to_string()))?
+        .ok_or_else(|| MeltDown::new(MeltType::NotFound, "Invitation not pending").with_user_message("This invitation has already been used or revoked."))
+    }
+}
+
+impl Users {
+    pub async fn register_with_policy(register: RegisterForm, tenant_name: &str) -> Result<Users, MeltDown> {
+        let policy = RegistrationPolicy::load(tenant_name).await?;
+
+        let invitation = match register.invitation.as_deref().filter(|token| !token.is_empty()) {
+            Some(token) => Some(Invitation::find_valid(token, tenant_name).await?),
+            None => None,
+        };
+
+        policy.check(&register.email, invitation.as_ref())?;
+
+        let role = invitation.as_ref().map(|invitation| invitation.role.clone()).unwrap_or_else(|| DEFAULT_USER_ROLE.to_string());
+        let pending_verification = invitation.is_none() && policy.mode == RegistrationMode::AllowedDomains;
+
+        Self::register_user_with(register, &role, invitation.as_ref(), pending_verification, tenant_name).await
+    }
+}
@@ -0,0 +1,183 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use crate::{
+    cata_log,
+    database::{db::establish_connection_with_tenant, schema::login_attempts::dsl as attempt_dsl},
+    meltdown::*,
+    middleware::ClientInfo,
+    services::default::lockout_service,
+    structs::*,
+};
+
+impl LoginAttempt {
+    pub async fn record(attempt: NewLoginAttempt, tenant_name: &str) -> Result<(), MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        diesel::insert_into(attempt_dsl::login_attempts)
+            .values(&attempt)
+            .execute(&mut conn)
+            .await
+            .map(|_| ())
+            .map_err(|e| MeltDown::from(e).with_context("operation", "record_login_attempt").with_context("username", attempt.username.clone()))
+    }
+
+    pub async fn log_attempt(username: &str, user_id: Option<i32>, failure: Option<LoginFailureReason>, client: &ClientInfo, tenant_name: &str) {
+        let attempt = NewLoginAttempt {
+            username: username.to_string(),
+            user_id,
+            ip_address: client.ip_address.clone(),
+            user_agent: client.user_agent.clone(),
+            success: failure.is_none(),
+            failure_reason: failure.map(|reason| reason.as_str().to_string()),
+        };
+
+        if let Err(e) = Self::record(attempt, tenant_name).await {
+            cata_log!(Error, format!("Failed to record login attempt for {} (tenant: {}): {}", username, tenant_name, e.log_message()));
+        }
+    }
+
+    pub async fn lockout_remaining(username: &str, tenant_name: &str) -> Result<Option<i64>, MeltDown> {
+        let settings = lockout_service::lockout_settings();
+        let now = Utc::now().timestamp();
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        let last_success = attempt_dsl::login_attempts
+            .filter(attempt_dsl::username.eq(username))
+            .filter(attempt_dsl::success.eq(true))
+            .select(diesel::dsl::max(attempt_dsl::created_at))
+            .first::<Option<i64>>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "last_successful_login").with_context("username", username))?;
+
+        let failures = attempt_dsl::login_attempts
+            .filter(attempt_dsl::username.eq(username))
+            .filter(attempt_dsl::success.eq(false))
+            .filter(attempt_dsl::failure_reason.ne(LoginFailureReason::LockedOut.as_str()))
+            .filter(
+                attempt_dsl::created_at.gt(lockout_service::failure_window_start(&settings, last_success, now)).or(attempt_dsl::failure_reason
+                    .eq(LoginFailureReason::InvalidTwoFactor.as_str())
+                    .and(attempt_dsl::created_at.gt(now - settings.window_secs))),
+            )
+            .select(attempt_dsl::created_at)
+            .load::<i64>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "recent_login_failures").with_context("username", username))?;
+
+        Ok(lockout_service::lockout_remaining_with(&settings, &failures, now))
+    }
+
+    pub async fn two_factor_failures_since(user_id: i32, since: i64, tenant_name: &str) -> Result<i64, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        attempt_dsl::login_attempts
+            .filter(attempt_dsl::user_id.eq(user_id))
+            .filter(attempt_dsl::failure_reason.eq(LoginFailureReason::InvalidTwoFactor.as_str()))
+            .filter(attempt_dsl::created_at.ge(since))
+            .count()
+            .get_result::<i64>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "two_factor_failures_since").with_context("user_id", user_id.to_string()))
+    }
+
+    pub async fn recent(filter: &LoginAttemptFilter, tenant_name: &str) -> Result<Vec<LoginAttempt>, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        let mut query = attempt_dsl::login_attempts.into_boxed();
+
+        if let Some(username) = filter.username.as_deref().map(str::trim).filter(|username| !username.is_empty()) {
+            query = query.filter(attempt_dsl::username.ilike(format!("%{}%", username)));
+        }
+        if let Some(ip_address) = filter.ip_address.as_deref().map(str::trim).filter(|ip_address| !ip_address.is_empty()) {
+            query = query.filter(attempt_dsl::ip_address.eq(ip_address.to_string()));
+        }
+        match filter.outcome.as_deref() {
+            Some("success") => query = query.filter(attempt_dsl::success.eq(true)),
+            Some("failure") => query = query.filter(attempt_dsl::success.eq(false)),
+            Some("locked_out") => query = query.filter(attempt_dsl::failure_reason.eq(LoginFailureReason::LockedOut.as_str())),
+            _ => {}
+        }
+        if let Some(hours) = filter.since_hours.filter(|hours| *hours > 0) {
+            query = query.filter(attempt_dsl::created_at.ge(Utc::now().timestamp() - hours * 3600));
+        }
+
+        query
+            .order(attempt_dsl::created_at.desc())
+            .limit(LOGIN_ATTEMPTS_PAGE_LIMIT)
+            .load::<LoginAttempt>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "recent_login_attempts"))
+    }
+}
+
+impl Users {
+    pub async fn authenticate(username: &str, password: &str, client: &ClientInfo, tenant_name: &str) -> Result<Users, MeltDown> {
+        if let Some(remaining) = LoginAttempt::lockout_remaining(username, tenant_name).await? {
+            cata_log!(Warning, format!("Login for {} rejected, account locked for another {}s (tenant: {})", username, remaining, tenant_name));
+            LoginAttempt::log_attempt(username, None, Some(LoginFailureReason::LockedOut), client, tenant_name).await;
+            return Err(lockout_service::account_locked(remaining));
+        }
+
+        let mut user = match Self::get_user_by_username(username.to_string(), tenant_name).await {
+            Ok(user) => user,
+            Err(e) if matches!(e.melt_type, MeltType::RecordNotFound) => {
+                cata_log!(Warning, format!("Login attempt with invalid username: {} for tenant: {}", username, tenant_name));
+                LoginAttempt::log_attempt(username, None, Some(LoginFailureReason::UnknownUser), client, tenant_name).await;
+                return Err(MeltDown::invalid_credentials());
+            }
+            Err(e) => return Err(e),
+        };
+
+        match user.verify_password(password.to_string()).await {
+            Ok(true) => {}
+            Ok(false) | Err(_) => {
+                cata_log!(Warning, format!("Failed login attempt for user: {} (tenant: {})", username, tenant_name));
+                LoginAttempt::log_attempt(username, Some(user.id), Some(LoginFailureReason::InvalidPassword), client, tenant_name).await;
+                return Err(MeltDown::invalid_credentials());
+            }
+        }
+
+        LoginAttempt::log_attempt(username, Some(user.id), None, client, tenant_name).await;
+
+        if let Err(error) = user.rehash_password_if_needed(password, tenant_name).await {
+            cata_log!(Warning, format!("Failed to upgrade password hash for user {} (tenant: {}): {}", user.id, tenant_name, error.log_message()));
+        }
+
+        Ok(user)
+    }
+
+    pub async fn verify_two_factor_login(&self, code: &str, issued_at: usize, client: &ClientInfo, tenant_name: &str) -> Result<(), MeltDown> {
+        if let Some(remaining) = LoginAttempt::lockout_remaining(&self.username, tenant_name).await? {
+            cata_log!(
+                Warning,
+                format!("Two-factor login for {} rejected, account locked for another {}s (tenant: {})", self.username, remaining, tenant_name)
+            );
+            LoginAttempt::log_attempt(&self.username, Some(self.id), Some(LoginFailureReason::LockedOut), client, tenant_name).await;
+            return Err(lockout_service::account_locked(remaining));
+        }
+
+        let too_many_attempts = || {
+            MeltDown::new(MeltType::InvalidToken, format!("Two-factor token for user {} exhausted its {} attempts", self.id, MAX_TWO_FACTOR_ATTEMPTS))
+                .with_user_message("Too many invalid verification codes. Please log in again.")
+        };
+
+        let failures = LoginAttempt::two_factor_failures_since(self.id, issued_at as i64, tenant_name).await?;
+        if failures >= MAX_TWO_FACTOR_ATTEMPTS {
+            return Err(too_many_attempts());
+        }
+
+        if UserTwoFactor::verify(self.id, code, tenant_name).await? {
+            return Ok(());
+        }
+
+        cata_log!(Warning, format!("Invalid two-factor code for user: {} (tenant: {})", self.username, tenant_name));
+        LoginAttempt::log_attempt(&self.username, Some(self.id), Some(LoginFailureReason::InvalidTwoFactor), client, tenant_name).await;
+
+        if failures + 1 >= MAX_TWO_FACTOR_ATTEMPTS {
+            return Err(too_many_attempts());
+        }
+
+        Err(MeltDown::new(MeltType::InvalidCredentials, format!("Invalid two-factor code for user {}", self.id)).with_user_message("Invalid verification code."))
+    }
+}
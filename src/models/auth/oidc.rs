@@ -0,0 +1,334 @@
+use chrono::Utc;
+use diesel::{
+    prelude::*,
+    sql_types::{Nullable, Text},
+};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use rand::{distributions::Alphanumeric, Rng};
+
+use crate::{
+    cata_log,
+    database::{
+        db::establish_connection_with_tenant,
+        schema::{oidc_providers::dsl as provider_dsl, user_identities::dsl as identity_dsl, users::dsl as user_dsl},
+    },
+    meltdown::*,
+    services::default::{oidc_service, password_service, token_registry},
+    structs::*,
+};
+
+const MAX_USERNAME_ATTEMPTS: u32 = 20;
+
+define_sql_function!(fn lower(value: Nullable<Text>) -> Nullable<Text>);
+
+fn provider_not_found(id: i32) -> MeltDown {
+    MeltDown::new(MeltType::NotFound, "OIDC provider not found")
+        .with_context("provider_id", id.to_string())
+        .with_user_message("This sign-in provider does not exist.")
+}
+
+impl OidcProvider {
+    async fn from_form(form: &OidcProviderForm, existing_secret: Option<&str>, tenant_name: &str) -> Result<NewOidcProvider, MeltDown> {
+        let slug = form.slug.trim().to_lowercase();
+        if slug.is_empty() || !slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+            return Err(MeltDown::invalid_input("slug").with_user_message("The slug may only contain lowercase letters, digits and dashes."));
+        }
+
+        if form.display_name.trim().is_empty() || form.client_id.trim().is_empty() {
+            return Err(MeltDown::missing_field("display_name, client_id").with_user_message("Display name and client ID are required."));
+        }
+
+        let issuer = oidc_service::validate_issuer_url(&form.issuer)?.to_string().trim_end_matches('/').to_string();
+
+        let client_secret = match (form.client_secret.trim(), existing_secret) {
+            ("", Some(existing)) => existing.to_string(),
+            ("", None) => return Err(MeltDown::missing_field("client_secret").with_user_message("A client secret is required.")),
+            (secret, _) => secret.to_string(),
+        };
+
+        let scopes = Some(form.scopes.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|scopes| !scopes.is_empty())
+            .unwrap_or_else(|| DEFAULT_OIDC_SCOPES.to_string());
+        if !scopes.split(' ').any(|scope| scope == "openid") {
+            return Err(MeltDown::invalid_input("scopes").with_user_message("The scopes must include 'openid'."));
+        }
+
+        Ok(NewOidcProvider {
+            slug,
+            display_name: form.display_name.trim().to_string(),
+            issuer,
+            client_id: form.client_id.trim().to_string(),
+            client_secret,
+            scopes,
+            role_claim: Some(form.role_claim.trim().to_string()).filter(|claim| !claim.is_empty()),
+            role_mapping: form.role_mapping.lines().map(str::trim).filter(|line| !line.is_empty()).collect::<Vec<_>>().join("\n"),
+            default_role: Users::assignable_role(&form.default_role, tenant_name).await?,
+            auto_provision: form.auto_provision.unwrap_or(false),
+            enabled: form.enabled.unwrap_or(false),
+            link_existing_accounts: form.link_existing_accounts.unwrap_or(false),
+        })
+    }
+
+    pub async fn get_all(tenant_name: &str) -> Result<Vec<OidcProvider>, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        provider_dsl::oidc_providers
+            .order(provider_dsl::display_name.asc())
+            .load::<OidcProvider>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "get_oidc_providers"))
+    }
+
+    pub async fn get_enabled(tenant_name: &str) -> Result<Vec<OidcProvider>, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        provider_dsl::oidc_providers
+            .filter(provider_dsl::enabled.eq(true))
+            .order(provider_dsl::display_name.asc())
+            .load::<OidcProvider>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "get_enabled_oidc_providers"))
+    }
+
+    pub async fn get_by_id(id: i32, tenant_name: &str) -> Result<OidcProvider, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        provider_dsl::oidc_providers
+            .filter(provider_dsl::id.eq(id))
+            .first::<OidcProvider>(&mut conn)
+            .await
+            .optional()
+            .map_err(|e| MeltDown::from(e).with_context("operation", "get_oidc_provider").with_context("provider_id", id.to_string()))?
+            .ok_or_else(|| provider_not_found(id))
+    }
+
+    pub async fn get_enabled_by_slug(slug: &str, tenant_name: &str) -> Result<OidcProvider, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        provider_dsl::oidc_providers
+            .filter(provider_dsl::slug.eq(slug))
+            .filter(provider_dsl::enabled.eq(true))
+            .first::<OidcProvider>(&mut conn)
+            .await
+            .optional()
+            .map_err(|e| MeltDown::from(e).with_context("operation", "get_oidc_provider_by_slug").with_context("slug", slug.to_string()))?
+            .ok_or_else(|| MeltDown::new(MeltType::NotFound, format!("OIDC provider {} not found or disabled", slug)).with_user_message("This sign-in provider is not available."))
+    }
+
+    pub async fn create(form: &OidcProviderForm, tenant_name: &str) -> Result<OidcProvider, MeltDown> {
+        let new_provider = Self::from_form(form, None, tenant_name).await?;
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        let provider = diesel::insert_into(provider_dsl::oidc_providers)
+            .values(&new_provider)
+            .get_result::<OidcProvider>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "create_oidc_provider").with_context("slug", new_provider.slug.clone()))?;
+
+        cata_log!(Info, format!("Created OIDC provider {} (tenant: {})", provider.slug, tenant_name));
+        Ok(provider)
+    }
+
+    pub async fn update(id: i32, form: &OidcProviderForm, tenant_name: &str) -> Result<OidcProvider, MeltDown> {
+        let existing = Self::get_by_id(id, tenant_name).await?;
+        let changes = Self::from_form(form, Some(&existing.client_secret), tenant_name).await?;
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        let provider = diesel::update(provider_dsl::oidc_providers.filter(provider_dsl::id.eq(id)))
+            .set((&changes, provider_dsl::updated_at.eq(Utc::now().timestamp())))
+            .get_result::<OidcProvider>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "update_oidc_provider").with_context("provider_id", id.to_string()))?;
+
+        oidc_service::invalidate_provider(&existing.issuer);
+        cata_log!(Info, format!("Updated OIDC provider {} (tenant: {})", provider.slug, tenant_name));
+        Ok(provider)
+    }
+
+    pub async fn delete(id: i32, tenant_name: &str) -> Result<(), MeltDown> {
+        let existing = Self::get_by_id(id, tenant_name).await?;
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        diesel::delete(provider_dsl::oidc_providers.filter(provider_dsl::id.eq(id)))
+            .execute(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "delete_oidc_provider").with_context("provider_id", id.to_string()))?;
+
+        oidc_service::invalidate_provider(&existing.issuer);
+        cata_log!(Info, format!("Deleted OIDC provider {} (tenant: {})", existing.slug, tenant_name));
+        Ok(())
+    }
+}
+
+impl Users {
+    async fn oidc_username(claims: &OidcClaims, tenant_name: &str) -> Result<String, MeltDown> {
+        let candidate = claims
+            .preferred_username
+            .clone()
+            .or_else(|| claims.email.as_deref().and_then(|email| email.split('@').next()).map(str::to_string))
+            .unwrap_or_default();
+
+        let base: String = candidate.chars().filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')).collect::<String>().to_lowercase();
+        let base = if base.is_empty() { "sso-user".to_string() } else { base };
+
+        for attempt in 1..=MAX_USERNAME_ATTEMPTS {
+            let username = if attempt == 1 { base.clone() } else { format!("{}{}", base, attempt) };
+            if !Self::username_exists(username.clone(), tenant_name).await? {
+                return Ok(username);
+            }
+        }
+
+        let suffix: String = rand::thread_rng().sample_iter(&Alphanumeric).take(6).map(char::from).collect();
+        Ok(format!("{}-{}", base, suffix.to_lowercase()))
+    }
+
+    async fn oidc_role(provider: &OidcProvider, claims: &OidcClaims, tenant_name: &str) -> Result<Option<String>, MeltDown> {
+        let Some(role) = oidc_service::map_role(provider, claims) else {
+            return Ok(None);
+        };
+
+        match Self::assignable_role(&role, tenant_name).await {
+            Ok(role) => Ok(Some(role)),
+            Err(_) => {
+                cata_log!(Warning, format!("OIDC provider {} mapped to unassignable role '{}' (tenant: {})", provider.slug, role, tenant_name));
+                Ok(None)
+            }
+        }
+    }
+
+    pub async fn from_oidc_login(provider: &OidcProvider, claims: &OidcClaims, tenant_name: &str) -> Result<Users, MeltDown> {
+        let mapped_role = Self::oidc_role(provider, claims, tenant_name).await?;
+        let email = claims.email.as_deref().map(str::trim).filter(|email| !email.is_empty()).map(str::to_string);
+        let username = Self::oidc_username(claims, tenant_name).await?;
+        let password_hash = password_service::hash_password(rand::thread_rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect()).await?;
+
+        let provider = provider.clone();
+        let claims = claims.clone();
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+        let now = Utc::now().timestamp();
+
+        let (user, previous_role) = conn
+            .transaction::<_, MeltDown, _>(|conn| {
+                async move {
+                    let identity = identity_dsl::user_identities
+                        .filter(identity_dsl::provider_id.eq(provider.id))
+                        .filter(identity_dsl::subject.eq(&claims.sub))
+                        .first::<UserIdentity>(conn)
+                        .await
+                        .optional()
+                        .map_err(|e| MeltDown::from(e).with_context("operation", "find_user_identity"))?;
+
+                    let user = match identity {
+                        Some(identity) => {
+                            diesel::update(identity_dsl::user_identities.filter(identity_dsl::id.eq(identity.id)))
+                                .set((identity_dsl::last_login_at.eq(now), identity_dsl::email.eq(&email)))
+                                .execute(conn)
+                                .await
+                                .map_err(|e| MeltDown::from(e).with_context("operation", "touch_user_identity"))?;
+
+                            user_dsl::users
+                                .filter(user_dsl::id.eq(identity.user_id))
+                                .first::<Users>(conn)
+                                .await
+                                .map_err(|e| MeltDown::from(e).with_context("operation", "get_identity_user").with_context("user_id", identity.user_id.to_string()))?
+                        }
+                        None => {
+                            let existing = match email.as_deref() {
+                                Some(email) => user_dsl::users
+                                    .filter(lower(user_dsl::email).eq(email.to_lowercase()))
+                                    .first::<Users>(conn)
+                                    .await
+                                    .optional()
+                                    .map_err(|e| MeltDown::from(e).with_context("operation", "find_user_by_email"))?,
+                                None => None,
+                            };
+
+                            let user = match existing {
+                                Some(user) if provider.link_existing_accounts && claims.email_verified && user.email_verified_at.is_some() => user,
+                                Some(user) => {
+                                    return Err(MeltDown::new(MeltType::Forbidden, "OIDC identity matches an existing account but linking is not allowed")
+                                        .with_context("provider", provider.slug.clone())
+                                        .with_context("user_id", user.id.to_string())
+                                        .with_user_message("An account with this email already exists. Please contact your administrator to link it."));
+                                }
+                                None if provider.auto_provision => {
+                                    let new_user = NewUser {
+                                        username,
+                                        first_name: claims.given_name.clone().or_else(|| claims.name.clone()).unwrap_or_default(),
+                                        last_name: claims.family_name.clone().unwrap_or_default(),
+                                        email: email.clone(),
+                                        password_hash,
+                                        role: mapped_role.clone().unwrap_or_else(|| provider.default_role.clone()),
+                                        should_change_password: false,
+                                    };
+
+                                    let user = diesel::insert_into(user_dsl::users)
+                                        .values(&new_user)
+                                        .get_result::<Users>(conn)
+                                        .await
+                                        .map_err(|e| MeltDown::from(e).with_context("operation", "oidc_provision_user").with_context("username", new_user.username.clone()))?;
+
+                                    if claims.email_verified && email.is_some() {
+                                        diesel::update(user_dsl::users.filter(user_dsl::id.eq(user.id)))
+                                            .set(user_dsl::email_verified_at.eq(Some(now)))
+                                            .get_result::<Users>(conn)
+                                            .await
+                                            .map_err(|e| MeltDown::from(e).with_context("operation", "verify_oidc_email").with_context("user_id", user.id.to_string()))?
+                                    } else {
+                                        user
+                                    }
+                                }
+                                None => {
+                                    return Err(MeltDown::new(MeltType::Forbidden, "No local account for OIDC identity and provisioning is disabled")
+                                        .with_context("provider", provider.slug.clone())
+                                        .with_user_message("No account is linked to this identity. Please contact your administrator."));
+                                }
+                            };
+
+                            diesel::insert_into(identity_dsl::user_identities)
+                                .values(&NewUserIdentity {
+                                    user_id: user.id,
+                                    provider_id: provider.id,
+                                    subject: claims.sub.clone(),
+                                    email: email.clone(),
+                                })
+                                .execute(conn)
+                                .await
+                                .map_err(|e| MeltDown::from(e).with_context("operation", "link_user_identity").with_context("user_id", user.id.to_string()))?;
+
+                            user
+                        }
+                    };
+
+                    if !user.active {
+                        return Err(MeltDown::new(MeltType::Forbidden, "OIDC login for inactive user")
+                            .with_context("user_id", user.id.to_string())
+                            .with_user_message("Your account has been deactivated."));
+                    }
+
+                    match mapped_role.filter(|role| *role != user.role) {
+                        Some(role) => {
+                            let previous_role = user.role.clone();
+                            let user = diesel::update(user_dsl::users.filter(user_dsl::id.eq(user.id)))
+                                .set((user_dsl::role.eq(&role), user_dsl::updated_at.eq(now)))
+                                .get_result::<Users>(conn)
+                                .await
+                                .map_err(|e| MeltDown::from(e).with_context("operation", "sync_oidc_role").with_context("user_id", user.id.to_string()))?;
+
+                            Ok((user, Some(previous_role)))
+                        }
+                        None => Ok((user, None)),
+                    }
+                }
+                .scope_boxed()
+            })
+            .await?;
+
+        if let Some(previous_role) = previous_role {
+            token_registry::invalidate_user_tokens(tenant_name, user.id);
+            cata_log!(Info, format!("OIDC role sync changed user {} from '{}' to '{}' (tenant: {})", user.id, previous_role, user.role, tenant_name));
+        }
+
+        Ok(user)
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    database::{
+        db::establish_connection_with_tenant,
+        schema::{permissions::dsl as permission_dsl, role_permissions::dsl as role_permission_dsl, roles::dsl as role_dsl, users::dsl as user_dsl},
+    },
+    meltdown::*,
+    services::default::permission_registry,
+    structs::*,
+};
+
+const MAX_ROLE_NAME_LENGTH: usize = 50;
+
+fn validate_role_name(name: &str) -> Result<(), MeltDown> {
+    let valid = !name.is_empty() && name.len() <= MAX_ROLE_NAME_LENGTH && name.chars().next().is_some_and(|c| c.is_ascii_lowercase()) && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+    if !valid {
+        return Err(MeltDown::new(MeltType::ValidationFailed, format!("Invalid role name: {}", name)).with_user_message(format!(
+            "Role names must start with a lowercase letter and contain only lowercase letters, digits and underscores (max {} characters).",
+            MAX_ROLE_NAME_LENGTH
+        )));
+    }
+
+    Ok(())
+}
+
+impl Roles {
+    pub async fn get_all(tenant_name: &str) -> Result<Vec<Roles>, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        role_dsl::roles
+            .order(role_dsl::name.asc())
+            .load::<Roles>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "get_all_roles"))
+    }
+
+    pub async fn get_assignable(tenant_name: &str) -> Result<Vec<Roles>, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        role_dsl::roles
+            .filter(role_dsl::hidden.eq(false))
+            .order(role_dsl::name.asc())
+            .load::<Roles>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "get_assignable_roles"))
+    }
+
+    pub async fn get_grantable(actor_role: &str, tenant_name: &str) -> Result<Vec<Roles>, MeltDown> {
+        let mut grantable = Vec::new();
+
+        for role in Self::get_assignable(tenant_name).await? {
+            if permission_registry::role_within(tenant_name, &role.name, actor_role).await? {
+                grantable.push(role);
+            }
+        }
+
+        Ok(grantable)
+    }
+
+    pub async fn get_by_id(id: i32, tenant_name: &str) -> Result<Roles, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        role_dsl::roles
+            .filter(role_dsl::id.eq(id))
+            .first::<Roles>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "get_role_by_id").with_context("role_id", id.to_string()))
+    }
+
+    pub async fn permission_names(id: i32, tenant_name: &str) -> Result<Vec<String>, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        role_permission_dsl::role_permissions
+            .inner_join(permission_dsl::permissions)
+            .filter(role_permission_dsl::role_id.eq(id))
+            .select(permission_dsl::name)
+            .order(permission_dsl::name.asc())
+            .load::<String>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "get_role_permissions").with_context("role_id", id.to_string()))
+    }
+
+    pub async fn permission_map(tenant_name: &str) -> Result<HashMap<String, HashSet<String>>, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        let pairs = role_permission_dsl::role_permissions
+            .inner_join(role_dsl::roles)
+            .inner_join(permission_dsl::permissions)
+            .select((role_dsl::name, permission_dsl::name))
+            .load::<(String, String)>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "load_permission_map"))?;
+
+        let mut map: HashMap<String, HashSet<String>> = HashMap::new();
+        for (role, permission) in pairs {
+            map.entry(role).or_default().insert(permission);
+        }
+
+        Ok(map)
+    }
+
+    pub async fn summaries(tenant_name: &str) -> Result<Vec<RoleSummary>, MeltDown> {
+        let roles = Self::get_all(tenant_name).await?;
+        let mut permission_map = Self::permission_map(tenant_name).await?;
+
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+        let user_counts: HashMap<String, i64> = user_dsl::users
+            .group_by(user_dsl::role)
+            .select((user_dsl::role, diesel::dsl::count_star()))
+            .load::<(String, i64)>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "count_users_per_role"))?
+            .into_iter()
+            .collect();
+
+        Ok(roles
+            .into_iter()
+            .map(|role| {
+                let mut permissions: Vec<String> = permission_map.remove(&role.name).unwrap_or_default().into_iter().collect();
+                permissions.sort();
+
+                RoleSummary {
+                    user_count: user_counts.get(&role.name).copied().unwrap_or(0),
+                    permissions,
+                    role,
+                }
+            })
+            .collect())
+    }
+
+    async fn ensure_permissions_within(permissions: &[String], actor_role: &str, tenant_name: &str) -> Result<(), MeltDown> {
+        let held = permission_registry::permissions_for_role(tenant_name, actor_role).await?;
+
+        match permissions.iter().find(|permission| !held.contains(*permission)) {
+            Some(permission) => Err(MeltDown::new(MeltType::Forbidden, format!("Role {} cannot grant permission {}", actor_role, permission)).with_user_message("You cannot grant permissions you do not have.")),
+            None => Ok(()),
+        }
+    }
+
+    async fn replace_permissions(conn: &mut AsyncPgConnection, id: i32, permissions: &[String]) -> Result<(), MeltDown> {
+        let permission_ids = permission_dsl::permissions
+            .filter(permission_dsl::name.eq_any(permissions))
+            .select(permission_dsl::id)
+            .load::<i32>(conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "resolve_permissions"))?;
+
+        if permission_ids.len() != permissions.len() {
+            return Err(MeltDown::new(MeltType::ValidationFailed, "Unknown permission in role update").with_user_message("One or more selected permissions do not exist."));
+        }
+
+        diesel::delete(role_permission_dsl::role_permissions.filter(role_permission_dsl::role_id.eq(id)))
+            .execute(conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "clear_role_permissions").with_context("role_id", id.to_string()))?;
+
+        let rows: Vec<RolePermissions> = permission_ids.into_iter().map(|permission_id| RolePermissions { role_id: id, permission_id }).collect();
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        diesel::insert_into(role_permission_dsl::role_permissions)
+            .values(&rows)
+            .execute(conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "set_role_permissions").with_context("role_id", id.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn create(name: &str, description: &str, permissions: &[String], actor_role: &str, tenant_name: &str) -> Result<Roles, MeltDown> {
+        let name = name.trim();
+        validate_role_name(name)?;
+        Self::ensure_permissions_within(permissions, actor_role, tenant_name).await?;
+
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        let new_role = NewRole {
+            name: name.to_string(),
+            description: description.trim().to_string(),
+        };
+        let permissions = permissions.to_vec();
+
+        let role = conn
+            .transaction::<_, MeltDown, _>(|conn| {
+                async move {
+                    let role = diesel::insert_into(role_dsl::roles).values(&new_role).get_result::<Roles>(conn).await.map_err(|e| {
+                        let error = MeltDown::from(e).with_context("operation", "create_role").with_context("name", new_role.name.clone());
+                        if matches!(error.melt_type, MeltType::UniqueViolation) {
+                            error.with_user_message(format!("A role named '{}' already exists.", new_role.name))
+                        } else {
+                            error
+                        }
+                    })?;
+
+                    Self::replace_permissions(conn, role.id, &permissions).await?;
+
+                    Ok(role)
+                }
+                .scope_boxed()
+            })
+            .await?;
+
+        permission_registry::invalidate_tenant(tenant_name);
+        Ok(role)
+    }
+
+    pub async fn update(id: i32, description: &str, permissions: &[String], actor_role: &str, tenant_name: &str) -> Result<(), MeltDown> {
+        let role = Self::get_by_id(id, tenant_name).await?;
+        if !permission_registry::role_within(tenant_name, &role.name, actor_role).await? {
+            return Err(MeltDown::new(MeltType::Forbidden, format!("Role {} cannot edit role {}", actor_role, role.name)).with_user_message("You cannot edit a role with permissions you do not have."));
+        }
+        Self::ensure_permissions_within(permissions, actor_role, tenant_name).await?;
+
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+        let description = description.trim().to_string();
+        let permissions = permissions.to_vec();
+
+        conn.transaction::<_, MeltDown, _>(|conn| {
+            async move {
+                diesel::update(role_dsl::roles.filter(role_dsl::id.eq(id)))
+                    .set((role_dsl::description.eq(&description), role_dsl::updated_at.eq(Utc::now().timestamp())))
+                    .execute(conn)
+                    .await
+                    .map_err(|e| MeltDown::from(e).with_context("operation", "update_role").with_context("role_id", id.to_string()))?;
+
+                Self::replace_permissions(conn, id, &permissions).await
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+        permission_registry::invalidate_tenant(tenant_name);
+        Ok(())
+    }
+
+    pub async fn delete(id: i32, tenant_name: &str) -> Result<Roles, MeltDown> {
+        let role = Self::get_by_id(id, tenant_name).await?;
+
+        if role.is_system {
+            return Err(MeltDown::new(MeltType::ValidationFailed, format!("Role {} is a system role", role.name)).with_user_message("System roles cannot be deleted."));
+        }
+
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        let assigned = user_dsl::users
+            .filter(user_dsl::role.eq(&role.name))
+            .count()
+            .get_result::<i64>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "count_role_users").with_context("role_id", id.to_string()))?;
+
+        if assigned > 0 {
+            return Err(
+                MeltDown::new(MeltType::ValidationFailed, format!("Role {} is assigned to {} users", role.name, assigned)).with_user_message(format!(
+                    "This role is still assigned to {} user{}.",
+                    assigned,
+                    if assigned == 1 { "" } else { "s" }
+                )),
+            );
+        }
+
+        diesel::delete(role_dsl::roles.filter(role_dsl::id.eq(id)))
+            .execute(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "delete_role").with_context("role_id", id.to_string()))?;
+
+        permission_registry::invalidate_tenant(tenant_name);
+        Ok(role)
+    }
+}
+
+impl Permissions {
+    pub async fn get_all(tenant_name: &str) -> Result<Vec<Permissions>, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        permission_dsl::permissions
+            .order(permission_dsl::name.asc())
+            .load::<Permissions>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "get_all_permissions"))
+    }
+}
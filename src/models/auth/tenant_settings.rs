@@ -0,0 +1,56 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use crate::{
+    database::{db::establish_connection_with_tenant, schema::tenant_settings::dsl as setting_dsl},
+    meltdown::*,
+    structs::*,
+};
+
+impl TenantSettings {
+    pub async fn get_value(key: &str, tenant_name: &str) -> Result<Option<String>, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        setting_dsl::tenant_settings
+            .filter(setting_dsl::key.eq(key))
+            .select(setting_dsl::value)
+            .first::<String>(&mut conn)
+            .await
+            .optional()
+            .map_err(|e| MeltDown::from(e).with_context("operation", "get_tenant_setting").with_context("key", key))
+    }
+
+    pub async fn get_bool(key: &str, tenant_name: &str) -> Result<bool, MeltDown> {
+        Ok(Self::get_value(key, tenant_name).await?.is_some_and(|value| value == "true"))
+    }
+
+    pub async fn set_value(key: &str, value: &str, tenant_name: &str) -> Result<(), MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        let setting = NewTenantSetting {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+
+        diesel::insert_into(setting_dsl::tenant_settings)
+            .values(&setting)
+            .on_conflict(setting_dsl::key)
+            .do_update()
+            .set((setting_dsl::value.eq(value), setting_dsl::updated_at.eq(Utc::now().timestamp())))
+            .execute(&mut conn)
+            .await
+            .map(|_| ())
+            .map_err(|e| MeltDown::from(e).with_context("operation", "set_tenant_setting").with_context("key", key))
+    }
+
+    pub async fn get_all(tenant_name: &str) -> Result<Vec<TenantSettings>, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        setting_dsl::tenant_settings
+            .order(setting_dsl::key.asc())
+            .load::<TenantSettings>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "get_all_tenant_settings"))
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use crate::{
+    database::{db::establish_connection_with_tenant, schema::tenant_spark_settings::dsl as spark_setting_dsl},
+    meltdown::*,
+    services::sparks::makeuse,
+    structs::*,
+};
+
+impl TenantSparkSettings {
+    pub async fn for_spark(spark: &str, tenant_name: &str) -> Result<Vec<TenantSparkSettings>, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        spark_setting_dsl::tenant_spark_settings
+            .filter(spark_setting_dsl::spark.eq(spark))
+            .order(spark_setting_dsl::key.asc())
+            .load::<TenantSparkSettings>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "get_tenant_spark_settings").with_context("spark", spark))
+    }
+
+    pub async fn get_all(tenant_name: &str) -> Result<Vec<TenantSparkSettings>, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        spark_setting_dsl::tenant_spark_settings
+            .order((spark_setting_dsl::spark.asc(), spark_setting_dsl::key.asc()))
+            .load::<TenantSparkSettings>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "get_all_tenant_spark_settings"))
+    }
+
+    pub async fn update(spark: &str, key: &str, value: &str, tenant_name: &str) -> Result<(), MeltDown> {
+        let current = Self::for_spark(spark, tenant_name).await?;
+        let candidate = current
+            .iter()
+            .filter(|setting| setting.key != key)
+            .map(|setting| (setting.key.as_str(), setting.value.as_str()))
+            .chain((!value.is_empty()).then_some((key, value)));
+
+        if let Err(error) = makeuse::validate_tenant_settings(spark, candidate) {
+            return Err(MeltDown::new(MeltType::ValidationFailed, error.clone()).with_context("spark", spark).with_user_message(error));
+        }
+
+        match value {
+            "" => Self::remove(spark, key, tenant_name).await,
+            value => Self::set_value(spark, key, value, tenant_name).await,
+        }
+    }
+
+    pub async fn set_value(spark: &str, key: &str, value: &str, tenant_name: &str) -> Result<(), MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        let setting = NewTenantSparkSetting {
+            spark: spark.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+
+        diesel::insert_into(spark_setting_dsl::tenant_spark_settings)
+            .values(&setting)
+            .on_conflict((spark_setting_dsl::spark, spark_setting_dsl::key))
+            .do_update()
+            .set((spark_setting_dsl::value.eq(value), spark_setting_dsl::updated_at.eq(Utc::now().timestamp())))
+            .execute(&mut conn)
+            .await
+            .map(|_| ())
+            .map_err(|e| MeltDown::from(e).with_context("operation", "set_tenant_spark_setting").with_context("key", key))
+    }
+
+    pub async fn remove(spark: &str, key: &str, tenant_name: &str) -> Result<(), MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        diesel::delete(spark_setting_dsl::tenant_spark_settings.filter(spark_setting_dsl::spark.eq(spark)).filter(spark_setting_dsl::key.eq(key)))
+            .execute(&mut conn)
+            .await
+            .map(|_| ())
+            .map_err(|e| MeltDown::from(e).with_context("operation", "remove_tenant_spark_setting").with_context("key", key))
+    }
+}
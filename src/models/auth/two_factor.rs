@@ -0,0 +1,159 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use crate::{
+    database::{db::establish_connection_with_tenant, schema::user_two_factor::dsl as two_factor_dsl},
+    meltdown::*,
+    services::default::{
+        jwt_service::{self, LoginStep},
+        permission_registry,
+        totp_service::{self, SecondFactorCheck},
+    },
+    structs::*,
+};
+
+impl UserTwoFactor {
+    pub async fn find_by_user_id(user_id: i32, tenant_name: &str) -> Result<Option<UserTwoFactor>, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        two_factor_dsl::user_two_factor
+            .filter(two_factor_dsl::user_id.eq(user_id))
+            .first::<UserTwoFactor>(&mut conn)
+            .await
+            .optional()
+            .map_err(|e| MeltDown::from(e).with_context("operation", "find_two_factor").with_context("user_id", user_id.to_string()))
+    }
+
+    pub async fn is_enabled(user_id: i32, tenant_name: &str) -> Result<bool, MeltDown> {
+        Ok(Self::find_by_user_id(user_id, tenant_name).await?.is_some_and(|two_factor| two_factor.enabled))
+    }
+
+    pub async fn begin_enrolment(user_id: i32, tenant_name: &str) -> Result<UserTwoFactor, MeltDown> {
+        if let Some(existing) = Self::find_by_user_id(user_id, tenant_name).await? {
+            if existing.enabled {
+                return Err(MeltDown::new(MeltType::ValidationFailed, "Two-factor authentication is already enabled"));
+            }
+            return Ok(existing);
+        }
+
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        let new_two_factor = NewUserTwoFactor {
+            user_id,
+            secret: totp_service::generate_totp_secret(),
+        };
+
+        diesel::insert_into(two_factor_dsl::user_two_factor)
+            .values(&new_two_factor)
+            .get_result::<UserTwoFactor>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "begin_two_factor_enrolment").with_context("user_id", user_id.to_string()))
+    }
+
+    pub fn provisioning_uri(&self, account_name: &str, tenant_name: &str) -> Result<String, MeltDown> {
+        totp_service::totp_provisioning_uri(&self.secret, &format!("Catalyst ({})", tenant_name), account_name)
+    }
+
+    pub async fn confirm_enrolment(user_id: i32, code: &str, tenant_name: &str) -> Result<Vec<String>, MeltDown> {
+        let pending = Self::find_by_user_id(user_id, tenant_name)
+            .await?
+            .ok_or_else(|| MeltDown::new(MeltType::ValidationFailed, "Two-factor enrolment has not been started"))?;
+
+        if pending.enabled {
+            return Err(MeltDown::new(MeltType::ValidationFailed, "Two-factor authentication is already enabled"));
+        }
+
+        let step = totp_service::verify_totp_code(&pending.secret, code, None)?.ok_or_else(|| MeltDown::new(MeltType::ValidationFailed, "Invalid verification code"))?;
+
+        let recovery_codes = totp_service::generate_recovery_codes();
+
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        diesel::update(two_factor_dsl::user_two_factor.find(pending.id))
+            .set((
+                two_factor_dsl::enabled.eq(true),
+                two_factor_dsl::recovery_codes.eq(totp_service::recovery_code_hashes(&recovery_codes)),
+                two_factor_dsl::last_used_step.eq(Some(step)),
+                two_factor_dsl::updated_at.eq(Utc::now().timestamp()),
+            ))
+            .execute(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "confirm_two_factor_enrolment").with_context("user_id", user_id.to_string()))?;
+
+        Ok(recovery_codes)
+    }
+
+    pub async fn verify(user_id: i32, code: &str, tenant_name: &str) -> Result<bool, MeltDown> {
+        let two_factor = match Self::find_by_user_id(user_id, tenant_name).await? {
+            Some(two_factor) if two_factor.enabled => two_factor,
+            _ => return Ok(false),
+        };
+
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+        let now = Utc::now().timestamp();
+
+        let updated = match totp_service::check_second_factor(&two_factor.secret, &two_factor.recovery_codes, two_factor.last_used_step, code)? {
+            SecondFactorCheck::Totp(step) => {
+                diesel::update(
+                    two_factor_dsl::user_two_factor
+                        .find(two_factor.id)
+                        .filter(two_factor_dsl::last_used_step.is_null().or(two_factor_dsl::last_used_step.lt(step))),
+                )
+                .set((two_factor_dsl::last_used_step.eq(Some(step)), two_factor_dsl::updated_at.eq(now)))
+                .execute(&mut conn)
+                .await
+            }
+            SecondFactorCheck::RecoveryCode(remaining) => {
+                crate::cata_log!(Warning, format!("Recovery code used for user {} (tenant: {}), {} remaining", user_id, tenant_name, remaining.len()));
+                diesel::update(two_factor_dsl::user_two_factor.find(two_factor.id).filter(two_factor_dsl::recovery_codes.eq(&two_factor.recovery_codes)))
+                    .set((two_factor_dsl::recovery_codes.eq(serde_json::json!(remaining)), two_factor_dsl::updated_at.eq(now)))
+                    .execute(&mut conn)
+                    .await
+            }
+            SecondFactorCheck::Invalid => return Ok(false),
+        };
+
+        updated
+            .map(|rows| rows == 1)
+            .map_err(|e| MeltDown::from(e).with_context("operation", "verify_two_factor").with_context("user_id", user_id.to_string()))
+    }
+
+    pub async fn disable(user_id: i32, tenant_name: &str) -> Result<(), MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        diesel::delete(two_factor_dsl::user_two_factor.filter(two_factor_dsl::user_id.eq(user_id)))
+            .execute(&mut conn)
+            .await
+            .map(|_| ())
+            .map_err(|e| MeltDown::from(e).with_context("operation", "disable_two_factor").with_context("user_id", user_id.to_string()))
+    }
+
+    pub async fn requirement_for(user: &Users, tenant_name: &str) -> Result<TwoFactorRequirement, MeltDown> {
+        if Self::is_enabled(user.id, tenant_name).await? {
+            return Ok(TwoFactorRequirement::Verify);
+        }
+
+        if permission_registry::role_has_permission(tenant_name, &user.role, ADMIN_ACCESS).await? && TenantSettings::get_bool(REQUIRE_ADMIN_TWO_FACTOR, tenant_name).await? {
+            return Ok(TwoFactorRequirement::Enrol);
+        }
+
+        Ok(TwoFactorRequirement::NotRequired)
+    }
+}
+
+impl Users {
+    pub async fn start_session(&self, tenant_name: &str, remember: bool, device_info: Option<String>) -> Result<LoginStep, MeltDown> {
+        match UserTwoFactor::requirement_for(self, tenant_name).await? {
+            TwoFactorRequirement::NotRequired => Ok(LoginStep::Complete(jwt_service::generate_token_pair(self, tenant_name, remember, device_info)?)),
+            requirement => {
+                let (token, claims) = jwt_service::generate_two_factor_token(self, tenant_name, remember, device_info)?;
+                Ok(LoginStep::TwoFactorPending {
+                    token,
+                    claims,
+                    enrolment_required: requirement == TwoFactorRequirement::Enrol,
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use crate::{
+    cata_log,
+    database::{
+        db::establish_connection_with_tenant,
+        schema::{user_tokens::dsl as token_dsl, users::dsl as user_dsl},
+    },
+    meltdown::*,
+    services::default::{
+        mailer::{self, OutgoingMail},
+        password_service, signed_token, token_registry,
+    },
+    structs::*,
+};
+
+fn invalid_link() -> MeltDown {
+    MeltDown::new(MeltType::InvalidToken, "Token is unknown, used or expired").with_user_message("This link is invalid or has expired.")
+}
+
+impl UserToken {
+    pub async fn issue(user_id: i32, purpose: TokenPurpose, tenant_name: &str) -> Result<String, MeltDown> {
+        let signed = signed_token::sign_token(purpose, user_id)?;
+        let now = Utc::now().timestamp();
+
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        diesel::update(
+            token_dsl::user_tokens
+                .filter(token_dsl::user_id.eq(user_id))
+                .filter(token_dsl::purpose.eq(purpose.as_str()))
+                .filter(token_dsl::used_at.is_null()),
+        )
+        .set(token_dsl::used_at.eq(Some(now)))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| MeltDown::from(e).with_context("operation", "revoke_user_tokens").with_context("user_id", user_id.to_string()))?;
+
+        let new_token = NewUserToken {
+            user_id,
+            purpose: purpose.as_str().to_string(),
+            token_hash: signed_token::hash_token(&signed.token),
+            expires_at: signed.expires_at,
+        };
+
+        diesel::insert_into(token_dsl::user_tokens)
+            .values(&new_token)
+            .execute(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "issue_user_token").with_context("user_id", user_id.to_string()))?;
+
+        Ok(signed.token)
+    }
+
+    pub async fn peek(token: &str, purpose: TokenPurpose, tenant_name: &str) -> Result<i32, MeltDown> {
+        let signed = signed_token::verify_token(purpose, token)?;
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        token_dsl::user_tokens
+            .filter(token_dsl::token_hash.eq(signed_token::hash_token(token)))
+            .filter(token_dsl::purpose.eq(purpose.as_str()))
+            .filter(token_dsl::used_at.is_null())
+            .filter(token_dsl::expires_at.gt(Utc::now().timestamp()))
+            .select(token_dsl::user_id)
+            .first::<i32>(&mut conn)
+            .await
+            .optional()
+            .map_err(|e| MeltDown::from(e).with_context("operation", "peek_user_token"))?
+            .filter(|user_id| *user_id == signed.user_id)
+            .ok_or_else(invalid_link)
+    }
+
+    pub async fn revoke_for_user(user_id: i32, tenant_name: &str) -> Result<(), MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        diesel::update(token_dsl::user_tokens.filter(token_dsl::user_id.eq(user_id)).filter(token_dsl::used_at.is_null()))
+            .set(token_dsl::used_at.eq(Some(Utc::now().timestamp())))
+            .execute(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "revoke_all_user_tokens").with_context("user_id", user_id.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn consume(token: &str, purpose: TokenPurpose, tenant_name: &str) -> Result<i32, MeltDown> {
+        let signed = signed_token::verify_token(purpose, token)?;
+        let now = Utc::now().timestamp();
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        diesel::update(
+            token_dsl::user_tokens
+                .filter(token_dsl::token_hash.eq(signed_token::hash_token(token)))
+                .filter(token_dsl::purpose.eq(purpose.as_str()))
+                .filter(token_dsl::user_id.eq(signed.user_id))
+                .filter(token_dsl::used_at.is_null())
+                .filter(token_dsl::expires_at.gt(now)),
+        )
+        .set(token_dsl::used_at.eq(Some(now)))
+        .returning(token_dsl::user_id)
+        .get_result::<i32>(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| MeltDown::from(e).with_context("operation", "consume_user_token"))?
+        .ok_or_else(invalid_link)
+    }
+}
+
+impl Users {
+    pub async fn find_active_by_email(email: &str, tenant_name: &str) -> Result<Option<Users>, MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        user_dsl::users
+            .filter(user_dsl::email.eq(email.trim()))
+            .filter(user_dsl::active.eq(true))
+            .first::<Users>(&mut conn)
+            .await
+            .optional()
+            .map_err(|e| MeltDown::from(e).with_context("operation", "find_active_by_email"))
+    }
+
+    pub fn is_email_verified(&self) -> bool {
+        self.email_verified_at.is_some()
+    }
+
+    pub async fn request_password_reset(email: &str, tenant_name: &str) -> Result<(), MeltDown> {
+        let user = match Self::find_active_by_email(email, tenant_name).await? {
+            Some(user) => user,
+            None => {
+                cata_log!(Info, format!("Password reset requested for unknown email (tenant: {})", tenant_name));
+                return Ok(());
+            }
+        };
+
+        let token = UserToken::issue(user.id, TokenPurpose::PasswordReset, tenant_name).await?;
+        let link = format!("{}/{}/auth/reset/{}", mailer::mail_settings().base_url.trim_end_matches('/'), tenant_name, token);
+
+        mailer::send_mail(OutgoingMail {
+            to: email.trim().to_string(),
+            subject: "Reset your password".to_string(),
+            body: format!(
+                "Hello {},\n\nWe received a request to reset your password. Use the link below to choose a new one:\n\n{}\n\nThe link expires in {} minutes and can only be used once. If you did not request a reset, \
+                 you can ignore this email.\n",
+                user.first_name,
+                link,
+                TokenPurpose::PasswordReset.lifetime_secs() / 60
+            ),
+        })
+        .await?;
+
+        cata_log!(Info, format!("Password reset email sent to user {} (tenant: {})", user.id, tenant_name));
+        Ok(())
+    }
+
+    pub async fn reset_password_with_token(token: &str, new_password: &str, tenant_name: &str) -> Result<Users, MeltDown> {
+        password_service::validate_password(new_password)?;
+
+        let user_id = UserToken::consume(token, TokenPurpose::PasswordReset, tenant_name).await?;
+
+        let user = Self::get_user_by_id(user_id, tenant_name).await?;
+        if !user.active {
+            return Err(invalid_link().with_context("user_id", user_id.to_string()));
+        }
+
+        Self::change_password_by_id(user_id, new_password, tenant_name).await?;
+        token_registry::invalidate_user_tokens(tenant_name, user_id);
+
+        Ok(user)
+    }
+
+    pub async fn send_invitation(&self, tenant_name: &str) -> Result<(), MeltDown> {
+        let email = match &self.email {
+            Some(email) if !email.trim().is_empty() => email.trim().to_string(),
+            _ => return Err(MeltDown::new(MeltType::MissingField, "User has no email address").with_user_message("An email address is required to send an invitation.")),
+        };
+
+        let token = UserToken::issue(self.id, TokenPurpose::PasswordReset, tenant_name).await?;
+        let link = format!("{}/{}/auth/reset/{}", mailer::mail_settings().base_url.trim_end_matches('/'), tenant_name, token);
+
+        mailer::send_mail(OutgoingMail {
+            to: email,
+            subject: "You have been invited".to_string(),
+            body: format!(
+                "Hello {},\n\nAn account with the username '{}' has been created for you. Use the link below to choose your password:\n\n{}\n\nThe link expires in {} minutes. Afterwards you can request a new one from \
+                 the \"Forgot password\" page.\n",
+                self.first_name,
+                self.username,
+                link,
+                TokenPurpose::PasswordReset.lifetime_secs() / 60
+            ),
+        })
+        .await?;
+
+        cata_log!(Info, format!("Invitation email sent to user {} (tenant: {})", self.id, tenant_name));
+        Ok(())
+    }
+
+    pub async fn send_email_verification(&self, tenant_name: &str) -> Result<(), MeltDown> {
+        let email = match &self.email {
+            Some(email) if !email.trim().is_empty() => email.trim().to_string(),
+            _ => return Err(MeltDown::new(MeltType::MissingField, "User has no email address").with_user_message("Add an email address to your account first.")),
+        };
+
+        let token = UserToken::issue(self.id, TokenPurpose::EmailVerification, tenant_name).await?;
+        let link = format!("{}/{}/auth/verify/{}", mailer::mail_settings().base_url.trim_end_matches('/'), tenant_name, token);
+
+        mailer::send_mail(OutgoingMail {
+            to: email,
+            subject: "Verify your email address".to_string(),
+            body: format!(
+                "Hello {},\n\nPlease confirm your email address by opening the link below:\n\n{}\n\nThe link expires in {} hours.\n",
+                self.first_name,
+                link,
+                TokenPurpose::EmailVerification.lifetime_secs() / 3600
+            ),
+        })
+        .await?;
+
+        cata_log!(Info, format!("Verification email sent to user {} (tenant: {})", self.id, tenant_name));
+        Ok(())
+    }
+
+    pub async fn verify_email_with_token(token: &str, tenant_name: &str) -> Result<Users, MeltDown> {
+        let user_id = UserToken::consume(token, TokenPurpose::EmailVerification, tenant_name).await?;
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        diesel::update(user_dsl::users.filter(user_dsl::id.eq(user_id)))
+            .set((
+                user_dsl::active.eq(user_dsl::active.or(user_dsl::email_verified_at.is_null())),
+                user_dsl::email_verified_at.eq(Some(Utc::now().timestamp())),
+                user_dsl::updated_at.eq(Utc::now().timestamp()),
+            ))
+            .get_result::<Users>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "verify_email").with_context("user_id", user_id.to_string()))
+    }
+}
@@ -1,13 +1,18 @@
-use bcrypt::{hash, verify};
 use diesel::prelude::*;
 use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use rand::{distributions::Alphanumeric, Rng};
 
 use crate::{
     database::{
         db::{establish_connection, establish_connection_with_tenant},
-        schema::users::dsl::{self as user_dsl},
+        schema::{
+            invitations::dsl as invitation_dsl,
+            roles::dsl as role_dsl,
+            users::dsl::{self as user_dsl},
+        },
     },
     meltdown::*,
+    services::default::{password_service, permission_registry, token_registry},
     structs::*,
 };
 
@@ -24,35 +29,43 @@ impl Users {
     }
 
     pub async fn verify_password(&self, password: String) -> Result<bool, MeltDown> {
-        let password_hash = self.password_hash.clone();
-
-        tokio::task::spawn_blocking(move || match verify(&password, &password_hash) {
-            Ok(result) => {
-                if !result {
-                    return Err(MeltDown::invalid_credentials().with_context("operation", "password_verification"));
-                }
-                Ok(result)
-            }
-            Err(e) => Err(MeltDown::from(e).with_context("operation", "password_verification")),
-        })
-        .await
-        .map_err(|e| MeltDown::new(MeltType::Unknown, format!("Task join error: {}", e)))?
+        match password_service::verify_password_hash(password, self.password_hash.clone()).await {
+            Ok(true) => Ok(true),
+            Ok(false) => Err(MeltDown::invalid_credentials().with_context("operation", "password_verification")),
+            Err(e) => Err(e.with_context("operation", "password_verification")),
+        }
     }
 
     pub async fn set_password(&mut self, password: String) -> Result<(), MeltDown> {
-        let hashed = tokio::task::spawn_blocking(move || hash(&password, bcrypt::DEFAULT_COST).map_err(|e| MeltDown::from(e).with_context("operation", "password_hashing")))
-            .await
-            .map_err(|e| MeltDown::new(MeltType::Unknown, format!("Task join error: {}", e)))??;
+        password_service::validate_password(&password)?;
 
-        self.password_hash = hashed;
+        self.password_hash = password_service::hash_password(password).await?;
         Ok(())
     }
 
+    pub async fn rehash_password_if_needed(&mut self, password: &str, tenant_name: &str) -> Result<bool, MeltDown> {
+        if !password_service::needs_rehash(&self.password_hash) {
+            return Ok(false);
+        }
+
+        let password_hash = password_service::hash_password(password.to_string()).await?;
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        diesel::update(user_dsl::users.filter(user_dsl::id.eq(self.id)))
+            .set(user_dsl::password_hash.eq(&password_hash))
+            .execute(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "rehash_password").with_context("id", self.id.to_string()))?;
+
+        self.password_hash = password_hash;
+        Ok(true)
+    }
+
     pub async fn get_all_users(tenant_name: &str) -> Result<Vec<Users>, MeltDown> {
         let mut conn = establish_connection_with_tenant(tenant_name).await?;
 
         user_dsl::users
-            .filter(user_dsl::role.ne("dev"))
+            .filter(user_dsl::role.ne_all(role_dsl::roles.filter(role_dsl::hidden.eq(true)).select(role_dsl::name)))
             .load::<Users>(&mut conn)
             .await
             .map_err(|e| MeltDown::from(e).with_context("operation", "get_all_users"))
@@ -60,14 +73,14 @@ impl Users {
 
     pub async fn is_admin(id: i32, tenant_name: &str) -> Result<bool, MeltDown> {
         let user = Self::get_user_by_id(id, tenant_name).await?;
-        Ok(user.role == "admin")
+        permission_registry::role_has_permission(tenant_name, &user.role, ADMIN_ACCESS).await
     }
 
     pub async fn get_all_users_active(tenant_name: &str) -> Result<Vec<Users>, MeltDown> {
         let mut conn = establish_connection_with_tenant(tenant_name).await?;
 
         user_dsl::users
-            .filter(user_dsl::role.ne("dev"))
+            .filter(user_dsl::role.ne_all(role_dsl::roles.filter(role_dsl::hidden.eq(true)).select(role_dsl::name)))
             .filter(user_dsl::active.eq(true))
             .order(user_dsl::id.asc())
             .load::<Users>(&mut conn)
@@ -75,6 +88,18 @@ impl Users {
             .map_err(|e| MeltDown::from(e).with_context("operation", "get_all_users_active"))
     }
 
+    pub async fn get_active_admins(tenant_name: &str) -> Result<Vec<Users>, MeltDown> {
+        let mut admins = Vec::new();
+
+        for user in Self::get_all_users_active(tenant_name).await? {
+            if permission_registry::role_has_permission(tenant_name, &user.role, ADMIN_ACCESS).await? {
+                admins.push(user);
+            }
+        }
+
+        Ok(admins)
+    }
+
     pub async fn search_users(query: &str, tenant_name: &str) -> Result<Vec<Users>, MeltDown> {
         let query_string = query.to_string();
         let mut conn = establish_connection_with_tenant(tenant_name).await?;
@@ -110,15 +135,21 @@ impl Users {
 
     pub async fn is_admin_by_id(id: i32, tenant_name: &str) -> Result<bool, MeltDown> {
         let user = Self::get_user_by_id(id, tenant_name).await?;
-        Ok(user.role == "admin")
+        permission_registry::role_has_permission(tenant_name, &user.role, ADMIN_ACCESS).await
     }
 
     pub async fn register_user(register: RegisterForm, tenant_name: &str) -> Result<(), MeltDown> {
+        Self::register_user_with(register, DEFAULT_USER_ROLE, None, false, tenant_name).await.map(|_| ())
+    }
+
+    pub async fn register_user_with(register: RegisterForm, role: &str, invitation: Option<&Invitation>, pending_verification: bool, tenant_name: &str) -> Result<Users, MeltDown> {
+        password_service::validate_password(&register.password)?;
+
         let mut conn = establish_connection_with_tenant(tenant_name).await?;
 
-        let password_hash = tokio::task::spawn_blocking(move || hash(&register.password, bcrypt::DEFAULT_COST).map_err(|e| MeltDown::from(e).with_context("operation", "password_hashing")))
-            .await
-            .map_err(|e| MeltDown::new(MeltType::Unknown, format!("Task join error: {}", e)))??;
+        let password_hash = password_service::hash_password(register.password.clone()).await?;
+        let role = role.to_string();
+        let invitation_id = invitation.map(|invitation| invitation.id);
 
         conn.transaction::<_, MeltDown, _>(|conn| {
             async move {
@@ -128,16 +159,51 @@ impl Users {
                     last_name: register.last_name.to_string(),
                     email: Some(register.email.to_string()),
                     password_hash,
-                    role: "user".to_string(),
+                    role,
+                    should_change_password: false,
                 };
 
-                diesel::insert_into(user_dsl::users)
+                let user = diesel::insert_into(user_dsl::users)
                     .values(&new_user)
-                    .execute(conn)
+                    .get_result::<Users>(conn)
                     .await
                     .map_err(|e| MeltDown::from(e).with_context("operation", "user_registration"))?;
 
-                Ok(())
+                if pending_verification {
+                    return diesel::update(user_dsl::users.filter(user_dsl::id.eq(user.id)))
+                        .set(user_dsl::active.eq(false))
+                        .get_result::<Users>(conn)
+                        .await
+                        .map_err(|e| MeltDown::from(e).with_context("operation", "hold_unverified_registration").with_context("user_id", user.id.to_string()));
+                }
+
+                let Some(invitation_id) = invitation_id else {
+                    return Ok(user);
+                };
+
+                let now = chrono::Utc::now().timestamp();
+
+                let accepted = diesel::update(
+                    invitation_dsl::invitations
+                        .filter(invitation_dsl::id.eq(invitation_id))
+                        .filter(invitation_dsl::accepted_at.is_null())
+                        .filter(invitation_dsl::revoked_at.is_null())
+                        .filter(invitation_dsl::expires_at.gt(now)),
+                )
+                .set((invitation_dsl::accepted_at.eq(Some(now)), invitation_dsl::accepted_by.eq(Some(user.id))))
+                .execute(conn)
+                .await
+                .map_err(|e| MeltDown::from(e).with_context("operation", "accept_invitation").with_context("invitation_id", invitation_id.to_string()))?;
+
+                if accepted == 0 {
+                    return Err(MeltDown::new(MeltType::InvalidToken, "Invitation was used, revoked or expired during registration").with_user_message("This invitation link is invalid or has expired."));
+                }
+
+                diesel::update(user_dsl::users.filter(user_dsl::id.eq(user.id)))
+                    .set(user_dsl::email_verified_at.eq(Some(now)))
+                    .get_result::<Users>(conn)
+                    .await
+                    .map_err(|e| MeltDown::from(e).with_context("operation", "verify_invited_email").with_context("user_id", user.id.to_string()))
             }
             .scope_boxed()
         })
@@ -145,8 +211,6 @@ impl Users {
     }
 
     pub async fn get_user_by_id(id: i32, tenant_name: &str) -> Result<Users, MeltDown> {
-        crate::services::default::jwt_service::set_current_tenant(tenant_name);
-
         let mut conn = establish_connection_with_tenant(tenant_name).await?;
 
         user_dsl::users.filter(user_dsl::id.eq(id)).first::<Users>(&mut conn).await.map_err(|e| {
@@ -158,8 +222,6 @@ impl Users {
     }
 
     pub async fn get_user_by_username(username: String, tenant_name: &str) -> Result<Users, MeltDown> {
-        crate::services::default::jwt_service::set_current_tenant(tenant_name);
-
         let mut conn = establish_connection_with_tenant(tenant_name).await?;
 
         user_dsl::users
@@ -225,16 +287,16 @@ impl Users {
             .await
             .map_err(|e| MeltDown::from(e).with_context("operation", "deactivate_user").with_context("id", id.to_string()))?;
 
-        Ok(())
+        UserToken::revoke_for_user(id, tenant_name).await
     }
 
     pub async fn change_password_by_id(id: i32, new_password: &str, tenant_name: &str) -> Result<(), MeltDown> {
         let password_string = new_password.to_string();
+        password_service::validate_password(&password_string)?;
+
         let mut conn = establish_connection_with_tenant(tenant_name).await?;
 
-        let password_hash = tokio::task::spawn_blocking(move || hash(&password_string, bcrypt::DEFAULT_COST).map_err(|e| MeltDown::from(e).with_context("operation", "password_hashing")))
-            .await
-            .map_err(|e| MeltDown::new(MeltType::Unknown, format!("Task join error: {}", e)))??;
+        let password_hash = password_service::hash_password(password_string).await?;
 
         let mut user = user_dsl::users
             .filter(user_dsl::id.eq(id))
@@ -256,11 +318,11 @@ impl Users {
 
     pub async fn reset_password_by_id(id: i32, new_password: &str, tenant_name: &str) -> Result<(), MeltDown> {
         let password_string = new_password.to_string();
+        password_service::validate_password(&password_string)?;
+
         let mut conn = establish_connection_with_tenant(tenant_name).await?;
 
-        let password_hash = tokio::task::spawn_blocking(move || hash(&password_string, bcrypt::DEFAULT_COST).map_err(|e| MeltDown::from(e).with_context("operation", "password_hashing")))
-            .await
-            .map_err(|e| MeltDown::new(MeltType::Unknown, format!("Task join error: {}", e)))??;
+        let password_hash = password_service::hash_password(password_string).await?;
 
         let mut user = user_dsl::users
             .filter(user_dsl::id.eq(id))
@@ -299,11 +361,14 @@ impl Users {
             return Err(MeltDown::validation_failed("User account is not active"));
         }
 
+        let email_verified_at = if user.email == email_option { user.email_verified_at } else { None };
+
         let result = diesel::update(user_dsl::users.filter(user_dsl::id.eq(user_id)))
             .set((
                 user_dsl::first_name.eq(&first_name_string),
                 user_dsl::last_name.eq(&last_name_string),
                 user_dsl::email.eq(&email_option),
+                user_dsl::email_verified_at.eq(email_verified_at),
                 user_dsl::updated_at.eq(updated_at),
             ))
             .execute(&mut conn)
@@ -314,9 +379,206 @@ impl Users {
             self.first_name = first_name_string;
             self.last_name = last_name_string;
             self.email = email_option;
+            self.email_verified_at = email_verified_at;
             self.updated_at = updated_at;
         }
 
         result.map(|_| ())
     }
+
+    pub async fn assignable_role(role: &str, tenant_name: &str) -> Result<String, MeltDown> {
+        let role = role.trim();
+
+        if Roles::get_assignable(tenant_name).await?.iter().any(|assignable| assignable.name == role) {
+            Ok(role.to_string())
+        } else {
+            Err(MeltDown::new(MeltType::ValidationFailed, format!("Role {} is not assignable", role)).with_user_message("Please choose a valid role."))
+        }
+    }
+
+    pub async fn grantable_role(role: &str, actor_role: &str, tenant_name: &str) -> Result<String, MeltDown> {
+        let role = Self::assignable_role(role, tenant_name).await?;
+
+        if permission_registry::role_within(tenant_name, &role, actor_role).await? {
+            Ok(role)
+        } else {
+            Err(MeltDown::new(MeltType::Forbidden, format!("Role {} is not within the permissions of role {}", role, actor_role)).with_user_message("You cannot grant a role with permissions you do not have."))
+        }
+    }
+
+    pub async fn page(query: &str, page: i64, tenant_name: &str) -> Result<UserPage, MeltDown> {
+        let query = query.trim().to_string();
+        let pattern = format!("%{}%", query.to_lowercase());
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        let filtered = || {
+            let mut statement = user_dsl::users.filter(user_dsl::role.ne_all(role_dsl::roles.filter(role_dsl::hidden.eq(true)).select(role_dsl::name))).into_boxed();
+
+            if !query.is_empty() {
+                statement = statement.filter(
+                    user_dsl::username
+                        .ilike(pattern.clone())
+                        .or(user_dsl::first_name.ilike(pattern.clone()))
+                        .or(user_dsl::last_name.ilike(pattern.clone()))
+                        .or(user_dsl::email.ilike(pattern.clone())),
+                );
+            }
+
+            statement
+        };
+
+        let total = filtered()
+            .count()
+            .get_result::<i64>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "count_users_page").with_context("query", query.clone()))?;
+
+        let total_pages = ((total + ADMIN_USERS_PAGE_SIZE - 1) / ADMIN_USERS_PAGE_SIZE).max(1);
+        let page = page.clamp(1, total_pages);
+
+        let users = filtered()
+            .order(user_dsl::username.asc())
+            .limit(ADMIN_USERS_PAGE_SIZE)
+            .offset((page - 1) * ADMIN_USERS_PAGE_SIZE)
+            .load::<Users>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "load_users_page").with_context("query", query.clone()))?;
+
+        Ok(UserPage {
+            users: users.into_iter().map(UserListItem::from).collect(),
+            query,
+            page,
+            total,
+            total_pages,
+        })
+    }
+
+    pub async fn create_by_admin(form: AdminCreateUserForm, actor_role: &str, tenant_name: &str) -> Result<Users, MeltDown> {
+        let role = Self::grantable_role(&form.role, actor_role, tenant_name).await?;
+        let username = form.username.trim().to_string();
+        let email = Some(form.email.trim().to_string()).filter(|email| !email.is_empty());
+
+        if username.is_empty() || form.first_name.trim().is_empty() || form.last_name.trim().is_empty() {
+            return Err(MeltDown::missing_field("username, first_name, last_name").with_user_message("Username, first name and last name are required."));
+        }
+
+        if Self::username_exists(username.clone(), tenant_name).await? {
+            return Err(MeltDown::unique_violation("username").with_user_message(format!("The username '{}' is already taken.", username)));
+        }
+
+        let invite = form.password.is_empty();
+        if invite && email.is_none() {
+            return Err(MeltDown::missing_field("email").with_user_message("An email address is required to send an invitation."));
+        }
+
+        let password = if invite {
+            rand::thread_rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect()
+        } else {
+            password_service::validate_password(&form.password)?;
+            form.password.clone()
+        };
+
+        let new_user = NewUser {
+            username,
+            first_name: form.first_name.trim().to_string(),
+            last_name: form.last_name.trim().to_string(),
+            email,
+            password_hash: password_service::hash_password(password).await?,
+            role,
+            should_change_password: !invite,
+        };
+
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        let user = diesel::insert_into(user_dsl::users)
+            .values(&new_user)
+            .get_result::<Users>(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "admin_create_user").with_context("username", new_user.username.clone()))?;
+
+        if invite {
+            user.send_invitation(tenant_name).await?;
+        }
+
+        Ok(user)
+    }
+
+    pub async fn get_manageable(id: i32, actor_role: &str, tenant_name: &str) -> Result<Users, MeltDown> {
+        let user = Self::get_user_by_id(id, tenant_name).await?;
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        let hidden = role_dsl::roles
+            .filter(role_dsl::name.eq(&user.role))
+            .select(role_dsl::hidden)
+            .first::<bool>(&mut conn)
+            .await
+            .optional()
+            .map_err(|e| MeltDown::from(e).with_context("operation", "get_manageable_user").with_context("id", id.to_string()))?
+            .unwrap_or(false);
+
+        if hidden || !permission_registry::role_within(tenant_name, &user.role, actor_role).await? {
+            return Err(MeltDown::new(MeltType::Forbidden, format!("User {} has role {} beyond the permissions of role {}", id, user.role, actor_role))
+                .with_user_message("You cannot edit a user with permissions you do not have."));
+        }
+
+        Ok(user)
+    }
+
+    pub async fn update_by_admin(id: i32, form: &AdminUpdateUserForm, actor_role: &str, tenant_name: &str) -> Result<Users, MeltDown> {
+        let mut user = Self::get_manageable(id, actor_role, tenant_name).await?;
+
+        let role = if user.role == form.role.trim() {
+            user.role.clone()
+        } else {
+            Self::grantable_role(&form.role, actor_role, tenant_name).await?
+        };
+        let email = Some(form.email.trim()).filter(|email| !email.is_empty());
+
+        user.update_profile(form.first_name.trim(), form.last_name.trim(), email, tenant_name).await?;
+
+        if user.role != role {
+            let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+            diesel::update(user_dsl::users.filter(user_dsl::id.eq(id)))
+                .set((user_dsl::role.eq(&role), user_dsl::updated_at.eq(chrono::Utc::now().timestamp())))
+                .execute(&mut conn)
+                .await
+                .map_err(|e| MeltDown::from(e).with_context("operation", "set_user_role").with_context("id", id.to_string()))?;
+
+            token_registry::invalidate_user_tokens(tenant_name, id);
+            user.role = role;
+        }
+
+        Ok(user)
+    }
+
+    pub async fn require_password_change(id: i32, tenant_name: &str) -> Result<(), MeltDown> {
+        let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+        diesel::update(user_dsl::users.filter(user_dsl::id.eq(id)))
+            .set((user_dsl::should_change_password.eq(true), user_dsl::updated_at.eq(chrono::Utc::now().timestamp())))
+            .execute(&mut conn)
+            .await
+            .map_err(|e| MeltDown::from(e).with_context("operation", "require_password_change").with_context("id", id.to_string()))?;
+
+        token_registry::invalidate_user_tokens(tenant_name, id);
+        Ok(())
+    }
+}
+
+impl From<Users> for UserListItem {
+    fn from(user: Users) -> Self {
+        UserListItem {
+            email_verified: user.email_verified_at.is_some(),
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            role: user.role,
+            active: user.active,
+            should_change_password: user.should_change_password,
+            created_at: user.created_at,
+        }
+    }
 }
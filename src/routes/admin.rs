@@ -1,7 +1,19 @@
-use rocket::{get, routes, Route};
+use rocket::{
+    form::Form,
+    get, post,
+    response::{Flash, Redirect},
+    routes, uri, Route,
+};
 use rocket_dyn_templates::Template;
 
-use crate::{meltdown::*, middleware::*, vessel::structs::Vessel};
+use crate::{
+    cata_log,
+    meltdown::*,
+    middleware::*,
+    services::default::{oidc_service, permission_registry},
+    structs::*,
+    vessel::structs::Vessel,
+};
 
 #[get("/<tenant>/admin/dashboard")]
 pub async fn get_admin_dashboard(tenant: &str, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
@@ -22,6 +34,452 @@ pub async fn get_admin_dashboard(tenant: &str, app_context: AppContext<'_>) -> R
     Ok(app_context.render_with("admin/index", tenant_data))
 }
 
+#[get("/<tenant>/admin/security")]
+pub async fn get_admin_security(tenant: &str, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
+    let require_admin_two_factor = TenantSettings::get_bool(REQUIRE_ADMIN_TWO_FACTOR, tenant).await?;
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String("Security policy".to_string()));
+    context_data.insert("require_admin_two_factor".to_string(), serde_json::Value::Bool(require_admin_two_factor));
+
+    Ok(app_context.render_with("admin/security", TenantData::new(tenant, context_data)))
+}
+
+#[post("/<tenant>/admin/security", data = "<policy_form>")]
+pub async fn post_admin_security(tenant: &str, jwt: JWT, policy_form: Form<TwoFactorPolicyForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = policy_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to(uri!(get_admin_security(tenant))), csrf_error.user_message());
+    }
+
+    let require_admin_two_factor = form.require_admin_two_factor.unwrap_or(false);
+
+    match TenantSettings::set_value(REQUIRE_ADMIN_TWO_FACTOR, &require_admin_two_factor.to_string(), tenant).await {
+        Ok(()) => {
+            cata_log!(Info, format!("Admin {} set require_admin_two_factor={} (tenant: {})", jwt.get_username(), require_admin_two_factor, tenant));
+            Flash::success(Redirect::to(uri!(get_admin_security(tenant))), "Security policy updated.")
+        }
+        Err(error) => Flash::error(Redirect::to(uri!(get_admin_security(tenant))), error.user_message()),
+    }
+}
+
+#[get("/<tenant>/admin/security/logins?<filter..>")]
+pub async fn get_admin_login_attempts(tenant: &str, filter: LoginAttemptFilter, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
+    let attempts = LoginAttempt::recent(&filter, tenant).await?;
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String("Authentication events".to_string()));
+    context_data.insert("attempts".to_string(), serde_json::to_value(&attempts).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?);
+    context_data.insert("filter".to_string(), serde_json::to_value(&filter).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?);
+    context_data.insert("limit".to_string(), serde_json::Value::from(LOGIN_ATTEMPTS_PAGE_LIMIT));
+
+    Ok(app_context.render_with("admin/login_attempts", TenantData::new(tenant, context_data)))
+}
+
+#[get("/<tenant>/admin/roles")]
+pub async fn get_admin_roles(tenant: &str, jwt: JWT, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
+    let roles = Roles::summaries(tenant).await?;
+    let held = permission_registry::permissions_for_role(tenant, jwt.get_role()).await?;
+    let grantable = Permissions::get_all(tenant).await?.into_iter().filter(|permission| held.contains(&permission.name)).collect::<Vec<_>>();
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String("Roles".to_string()));
+    context_data.insert("roles".to_string(), serde_json::to_value(&roles).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?);
+    context_data.insert(
+        "permissions".to_string(),
+        serde_json::to_value(&grantable).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?,
+    );
+
+    Ok(app_context.render_with("admin/roles", TenantData::new(tenant, context_data)))
+}
+
+#[post("/<tenant>/admin/roles", data = "<role_form>")]
+pub async fn post_admin_roles(tenant: &str, jwt: JWT, role_form: Form<RoleForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = role_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to(uri!(get_admin_roles(tenant))), csrf_error.user_message());
+    }
+
+    match Roles::create(&form.name, &form.description, &form.permissions, jwt.get_role(), tenant).await {
+        Ok(role) => {
+            cata_log!(Info, format!("Admin {} created role {} (tenant: {})", jwt.get_username(), role.name, tenant));
+            Flash::success(Redirect::to(uri!(get_admin_role(tenant, role.id))), "Role created.")
+        }
+        Err(error) => Flash::error(Redirect::to(uri!(get_admin_roles(tenant))), error.user_message()),
+    }
+}
+
+#[get("/<tenant>/admin/roles/<id>")]
+pub async fn get_admin_role(tenant: &str, id: i32, jwt: JWT, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
+    let role = Roles::get_by_id(id, tenant).await?;
+    let granted = Roles::permission_names(id, tenant).await?;
+    let permissions = Permissions::get_all(tenant).await?;
+    let held = permission_registry::permissions_for_role(tenant, jwt.get_role()).await?;
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String(format!("Role: {}", role.name)));
+    context_data.insert("role".to_string(), serde_json::to_value(&role).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?);
+    context_data.insert(
+        "permissions".to_string(),
+        serde_json::to_value(&permissions).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?,
+    );
+    context_data.insert("granted".to_string(), serde_json::json!(granted));
+    context_data.insert("editable".to_string(), serde_json::Value::Bool(granted.iter().all(|permission| held.contains(permission))));
+    context_data.insert("held".to_string(), serde_json::json!(held));
+
+    Ok(app_context.render_with("admin/role", TenantData::new(tenant, context_data)))
+}
+
+#[post("/<tenant>/admin/roles/<id>", data = "<role_form>")]
+pub async fn post_admin_role(tenant: &str, id: i32, jwt: JWT, role_form: Form<RoleUpdateForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = role_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to(uri!(get_admin_role(tenant, id))), csrf_error.user_message());
+    }
+
+    let role = match Roles::get_by_id(id, tenant).await {
+        Ok(role) => role,
+        Err(error) => return Flash::error(Redirect::to(uri!(get_admin_roles(tenant))), error.user_message()),
+    };
+
+    if role.name == jwt.get_role() && !form.permissions.iter().any(|permission| permission == ROLES_MANAGE) {
+        cata_log!(
+            Warning,
+            format!("Admin {} tried to remove {} from their own role {} (tenant: {})", jwt.get_username(), ROLES_MANAGE, role.name, tenant)
+        );
+        return Flash::error(Redirect::to(uri!(get_admin_role(tenant, id))), "You cannot remove role management from your own role.");
+    }
+
+    match Roles::update(id, &form.description, &form.permissions, jwt.get_role(), tenant).await {
+        Ok(()) => {
+            cata_log!(
+                Info,
+                format!("Admin {} updated role {} with permissions [{}] (tenant: {})", jwt.get_username(), role.name, form.permissions.join(", "), tenant)
+            );
+            Flash::success(Redirect::to(uri!(get_admin_role(tenant, id))), "Role updated.")
+        }
+        Err(error) => Flash::error(Redirect::to(uri!(get_admin_role(tenant, id))), error.user_message()),
+    }
+}
+
+#[post("/<tenant>/admin/roles/<id>/delete", data = "<delete_form>")]
+pub async fn post_admin_role_delete(tenant: &str, id: i32, jwt: JWT, delete_form: Form<RoleDeleteForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = delete_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to(uri!(get_admin_role(tenant, id))), csrf_error.user_message());
+    }
+
+    match Roles::delete(id, tenant).await {
+        Ok(role) => {
+            cata_log!(Info, format!("Admin {} deleted role {} (tenant: {})", jwt.get_username(), role.name, tenant));
+            Flash::success(Redirect::to(uri!(get_admin_roles(tenant))), "Role deleted.")
+        }
+        Err(error) => Flash::error(Redirect::to(uri!(get_admin_role(tenant, id))), error.user_message()),
+    }
+}
+
+#[get("/<tenant>/admin/users?<q>&<page>")]
+pub async fn get_admin_users(tenant: &str, q: Option<String>, page: Option<i64>, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
+    let users = Users::page(q.as_deref().unwrap_or_default(), page.unwrap_or(1), tenant).await?;
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String("Users".to_string()));
+    context_data.insert("users_page".to_string(), serde_json::to_value(&users).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?);
+
+    Ok(app_context.render_with("admin/users", TenantData::new(tenant, context_data)))
+}
+
+#[get("/<tenant>/admin/users/new")]
+pub async fn get_admin_new_user(tenant: &str, jwt: JWT, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
+    let roles = Roles::get_grantable(jwt.get_role(), tenant).await?;
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String("New user".to_string()));
+    context_data.insert("roles".to_string(), serde_json::to_value(&roles).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?);
+    context_data.insert("default_role".to_string(), serde_json::Value::String(DEFAULT_USER_ROLE.to_string()));
+
+    Ok(app_context.render_with("admin/user_new", TenantData::new(tenant, context_data)))
+}
+
+#[post("/<tenant>/admin/users", data = "<user_form>")]
+pub async fn post_admin_users(tenant: &str, jwt: JWT, user_form: Form<AdminCreateUserForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = user_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to(uri!(get_admin_new_user(tenant))), csrf_error.user_message());
+    }
+
+    let invited = form.password.is_empty();
+
+    match Users::create_by_admin(form, jwt.get_role(), tenant).await {
+        Ok(user) => {
+            cata_log!(Info, format!("Admin {} created user {} with role {} (tenant: {})", jwt.get_username(), user.username, user.role, tenant));
+            let message = if invited {
+                "User created and invitation sent."
+            } else {
+                "User created. They will be asked to change their password at first login."
+            };
+            Flash::success(Redirect::to(uri!(get_admin_user(tenant, user.id))), message)
+        }
+        Err(error) => Flash::error(Redirect::to(uri!(get_admin_new_user(tenant))), error.user_message()),
+    }
+}
+
+#[get("/<tenant>/admin/users/<id>")]
+pub async fn get_admin_user(tenant: &str, id: i32, jwt: JWT, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
+    let user = UserListItem::from(Users::get_manageable(id, jwt.get_role(), tenant).await?);
+    let roles = Roles::get_grantable(jwt.get_role(), tenant).await?;
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String(format!("User: {}", user.username)));
+    context_data.insert("managed_user".to_string(), serde_json::to_value(&user).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?);
+    context_data.insert("roles".to_string(), serde_json::to_value(&roles).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?);
+
+    Ok(app_context.render_with("admin/user", TenantData::new(tenant, context_data)))
+}
+
+#[post("/<tenant>/admin/users/<id>", data = "<user_form>")]
+pub async fn post_admin_user(tenant: &str, id: i32, jwt: JWT, user_form: Form<AdminUpdateUserForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = user_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to(uri!(get_admin_user(tenant, id))), csrf_error.user_message());
+    }
+
+    if jwt.user_id() == id && form.role != jwt.get_role() {
+        return Flash::error(Redirect::to(uri!(get_admin_user(tenant, id))), "You cannot change your own role.");
+    }
+
+    match Users::update_by_admin(id, &form, jwt.get_role(), tenant).await {
+        Ok(user) => {
+            cata_log!(Info, format!("Admin {} updated user {} (role: {}, tenant: {})", jwt.get_username(), user.username, user.role, tenant));
+            Flash::success(Redirect::to(uri!(get_admin_user(tenant, id))), "User updated.")
+        }
+        Err(error) => Flash::error(Redirect::to(uri!(get_admin_user(tenant, id))), error.user_message()),
+    }
+}
+
+#[get("/<tenant>/admin/registration")]
+pub async fn get_admin_registration(tenant: &str, jwt: JWT, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
+    let policy = RegistrationPolicy::load(tenant).await?;
+    let invitations = Invitation::recent(tenant).await?;
+    let roles = Roles::get_grantable(jwt.get_role(), tenant).await?;
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String("Registration".to_string()));
+    context_data.insert("policy".to_string(), serde_json::to_value(&policy).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?);
+    context_data.insert(
+        "invitations".to_string(),
+        serde_json::to_value(&invitations).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?,
+    );
+    context_data.insert("roles".to_string(), serde_json::to_value(&roles).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?);
+    context_data.insert("default_role".to_string(), serde_json::Value::String(DEFAULT_USER_ROLE.to_string()));
+    context_data.insert("default_lifetime_days".to_string(), serde_json::Value::from(DEFAULT_INVITATION_LIFETIME_DAYS));
+    context_data.insert("max_lifetime_days".to_string(), serde_json::Value::from(MAX_INVITATION_LIFETIME_DAYS));
+    context_data.insert("now".to_string(), serde_json::Value::from(chrono::Utc::now().timestamp()));
+
+    Ok(app_context.render_with("admin/registration", TenantData::new(tenant, context_data)))
+}
+
+#[post("/<tenant>/admin/registration", data = "<policy_form>")]
+pub async fn post_admin_registration(tenant: &str, jwt: JWT, policy_form: Form<RegistrationPolicyForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = policy_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to(uri!(get_admin_registration(tenant))), csrf_error.user_message());
+    }
+
+    let Some(mode) = RegistrationMode::parse(&form.mode) else {
+        return Flash::error(Redirect::to(uri!(get_admin_registration(tenant))), "Please choose a valid registration mode.");
+    };
+
+    let policy = RegistrationPolicy {
+        mode,
+        allowed_domains: RegistrationPolicy::parse_domains(&form.allowed_domains),
+    };
+
+    match policy.save(tenant).await {
+        Ok(()) => {
+            cata_log!(
+                Info,
+                format!(
+                    "Admin {} set registration mode {} with domains [{}] (tenant: {})",
+                    jwt.get_username(),
+                    mode.as_str(),
+                    policy.allowed_domains.join(", "),
+                    tenant
+                )
+            );
+            Flash::success(Redirect::to(uri!(get_admin_registration(tenant))), "Registration policy updated.")
+        }
+        Err(error) => Flash::error(Redirect::to(uri!(get_admin_registration(tenant))), error.user_message()),
+    }
+}
+
+#[post("/<tenant>/admin/invitations", data = "<invitation_form>")]
+pub async fn post_admin_invitations(tenant: &str, jwt: JWT, invitation_form: Form<InvitationForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = invitation_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to(uri!(get_admin_registration(tenant))), csrf_error.user_message());
+    }
+
+    let lifetime_days = form.expires_in_days.unwrap_or(DEFAULT_INVITATION_LIFETIME_DAYS);
+
+    let (invitation, token) = match Invitation::create(&form.email, &form.role, jwt.get_role(), lifetime_days, Some(jwt.user_id()), tenant).await {
+        Ok(created) => created,
+        Err(error) => return Flash::error(Redirect::to(uri!(get_admin_registration(tenant))), error.user_message()),
+    };
+
+    cata_log!(
+        Info,
+        format!("Admin {} created invitation {} with role {} (tenant: {})", jwt.get_username(), invitation.id, invitation.role, tenant)
+    );
+
+    match invitation.send(&token, tenant).await {
+        Ok(()) => Flash::success(Redirect::to(uri!(get_admin_registration(tenant))), format!("Invitation sent to {}.", invitation.email)),
+        Err(error) => {
+            cata_log!(Warning, format!("Failed to send invitation {} (tenant: {}): {}", invitation.id, tenant, error.log_message()));
+            let _ = Invitation::revoke(invitation.id, tenant).await;
+            Flash::error(Redirect::to(uri!(get_admin_registration(tenant))), "The invitation email could not be sent. Please try again.")
+        }
+    }
+}
+
+#[post("/<tenant>/admin/invitations/<id>/revoke", data = "<revoke_form>")]
+pub async fn post_admin_invitation_revoke(tenant: &str, id: i32, jwt: JWT, revoke_form: Form<InvitationRevokeForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &revoke_form.authenticity_token) {
+        return Flash::error(Redirect::to(uri!(get_admin_registration(tenant))), csrf_error.user_message());
+    }
+
+    match Invitation::revoke(id, tenant).await {
+        Ok(invitation) => {
+            cata_log!(Info, format!("Admin {} revoked invitation {} (tenant: {})", jwt.get_username(), invitation.id, tenant));
+            Flash::success(Redirect::to(uri!(get_admin_registration(tenant))), format!("Invitation for {} revoked.", invitation.email))
+        }
+        Err(error) => Flash::error(Redirect::to(uri!(get_admin_registration(tenant))), error.user_message()),
+    }
+}
+
+#[get("/<tenant>/admin/sso")]
+pub async fn get_admin_sso(tenant: &str, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
+    let providers = OidcProvider::get_all(tenant).await?;
+    let roles = Roles::get_assignable(tenant).await?;
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String("Single sign-on".to_string()));
+    context_data.insert("providers".to_string(), serde_json::to_value(&providers).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?);
+    context_data.insert("roles".to_string(), serde_json::to_value(&roles).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?);
+    context_data.insert("default_role".to_string(), serde_json::Value::String(DEFAULT_USER_ROLE.to_string()));
+    context_data.insert("default_scopes".to_string(), serde_json::Value::String(DEFAULT_OIDC_SCOPES.to_string()));
+
+    Ok(app_context.render_with("admin/sso", TenantData::new(tenant, context_data)))
+}
+
+#[post("/<tenant>/admin/sso", data = "<provider_form>")]
+pub async fn post_admin_sso(tenant: &str, jwt: JWT, provider_form: Form<OidcProviderForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = provider_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to(uri!(get_admin_sso(tenant))), csrf_error.user_message());
+    }
+
+    match OidcProvider::create(&form, tenant).await {
+        Ok(provider) => {
+            cata_log!(Info, format!("Admin {} added OIDC provider {} (tenant: {})", jwt.get_username(), provider.slug, tenant));
+            Flash::success(
+                Redirect::to(uri!(get_admin_sso_provider(tenant, provider.id))),
+                "Provider added. Register the redirect URI below with your identity provider.",
+            )
+        }
+        Err(error) => Flash::error(Redirect::to(uri!(get_admin_sso(tenant))), error.user_message()),
+    }
+}
+
+#[get("/<tenant>/admin/sso/<id>")]
+pub async fn get_admin_sso_provider(tenant: &str, id: i32, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
+    let provider = OidcProvider::get_by_id(id, tenant).await?;
+    let roles = Roles::get_assignable(tenant).await?;
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String(provider.display_name.clone()));
+    context_data.insert("redirect_uri".to_string(), serde_json::Value::String(oidc_service::redirect_uri(&provider, tenant)));
+    context_data.insert("provider".to_string(), serde_json::to_value(&provider).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?);
+    context_data.insert("roles".to_string(), serde_json::to_value(&roles).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?);
+    context_data.insert("default_role".to_string(), serde_json::Value::String(DEFAULT_USER_ROLE.to_string()));
+    context_data.insert("default_scopes".to_string(), serde_json::Value::String(DEFAULT_OIDC_SCOPES.to_string()));
+
+    Ok(app_context.render_with("admin/sso_provider", TenantData::new(tenant, context_data)))
+}
+
+#[post("/<tenant>/admin/sso/<id>", data = "<provider_form>")]
+pub async fn post_admin_sso_provider(tenant: &str, id: i32, jwt: JWT, provider_form: Form<OidcProviderForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = provider_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to(uri!(get_admin_sso_provider(tenant, id))), csrf_error.user_message());
+    }
+
+    match OidcProvider::update(id, &form, tenant).await {
+        Ok(provider) => {
+            cata_log!(Info, format!("Admin {} updated OIDC provider {} (tenant: {})", jwt.get_username(), provider.slug, tenant));
+            Flash::success(Redirect::to(uri!(get_admin_sso_provider(tenant, id))), "Provider updated.")
+        }
+        Err(error) => Flash::error(Redirect::to(uri!(get_admin_sso_provider(tenant, id))), error.user_message()),
+    }
+}
+
+#[post("/<tenant>/admin/sso/<id>/delete", data = "<delete_form>")]
+pub async fn post_admin_sso_provider_delete(tenant: &str, id: i32, jwt: JWT, delete_form: Form<OidcProviderDeleteForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = delete_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to(uri!(get_admin_sso_provider(tenant, id))), csrf_error.user_message());
+    }
+
+    match OidcProvider::delete(id, tenant).await {
+        Ok(()) => {
+            cata_log!(Info, format!("Admin {} deleted OIDC provider {} (tenant: {})", jwt.get_username(), id, tenant));
+            Flash::success(Redirect::to(uri!(get_admin_sso(tenant))), "Provider deleted. Linked identities were removed.")
+        }
+        Err(error) => Flash::error(Redirect::to(uri!(get_admin_sso_provider(tenant, id))), error.user_message()),
+    }
+}
+
 pub fn admin_routes() -> Vec<Route> {
     routes![get_admin_dashboard]
 }
+
+pub fn admin_security_routes() -> Vec<Route> {
+    routes![get_admin_security, post_admin_security]
+}
+
+pub fn admin_audit_routes() -> Vec<Route> {
+    routes![get_admin_login_attempts]
+}
+
+pub fn admin_user_routes() -> Vec<Route> {
+    routes![
+        get_admin_users,
+        get_admin_new_user,
+        post_admin_users,
+        get_admin_user,
+        post_admin_user,
+        get_admin_registration,
+        post_admin_registration,
+        post_admin_invitations,
+        post_admin_invitation_revoke
+    ]
+}
+
+pub fn admin_role_routes() -> Vec<Route> {
+    routes![get_admin_roles, post_admin_roles, get_admin_role, post_admin_role, post_admin_role_delete]
+}
+
+pub fn admin_sso_routes() -> Vec<Route> {
+    routes![get_admin_sso, post_admin_sso, get_admin_sso_provider, post_admin_sso_provider, post_admin_sso_provider_delete]
+}
@@ -1,6 +1,13 @@
-use rocket::{get, http::Status, post, routes, Route};
+use rocket::{form::Form, get, http::Status, post, routes, Route};
+use rocket_dyn_templates::Template;
 
-use crate::{cata_log, middleware::*, services::*, structs::*};
+use crate::{cata_log, meltdown::*, middleware::*, services::*, structs::*};
+
+const USERS_CHANGED_EVENT: &str = "users-changed";
+
+fn htmx_error(error: MeltDown) -> Htmx {
+    HtmxError::with_notification(error.status_code(), error.user_message())
+}
 
 #[post("/<tenant>/admin/partials/create_duplicate_admin")]
 pub async fn create_duplicate_admin(tenant: &str) -> HtmxResult {
@@ -12,6 +19,7 @@ pub async fn create_duplicate_admin(tenant: &str) -> HtmxResult {
         password: "password123".to_string(),
         confirm_password: "password123".to_string(),
         authenticity_token: "valid-token".to_string(),
+        invitation: None,
     };
 
     match Users::register_user(register_form, tenant).await {
@@ -57,6 +65,61 @@ pub async fn test_info(tenant: &str) -> HtmxResult {
     Ok(HtmxInfo::with_notification(format!("This is an information message for tenant: {} that will be displayed as a blue toast", tenant)))
 }
 
+#[get("/<tenant>/admin/partials/users?<q>&<page>")]
+pub async fn users_table(tenant: &str, q: Option<String>, page: Option<i64>, app_context: AppContext<'_>) -> Result<Template, Htmx> {
+    let users = Users::page(q.as_deref().unwrap_or_default(), page.unwrap_or(1), tenant).await.map_err(htmx_error)?;
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert(
+        "users_page".to_string(),
+        serde_json::to_value(&users).map_err(|e| htmx_error(MeltDown::new(MeltType::SerializationFailed, e.to_string())))?,
+    );
+
+    Ok(app_context.render_with("admin/partials/users_table", TenantData::new(tenant, context_data)))
+}
+
+#[post("/<tenant>/admin/partials/users/<id>/activate", data = "<action_form>")]
+pub async fn activate_user(tenant: &str, id: i32, jwt: JWT, action_form: Form<AdminUserActionForm>, app_context: AppContext<'_>) -> HtmxResult {
+    verify_csrf_for_state_change(&app_context, &action_form.authenticity_token).map_err(htmx_error)?;
+
+    let mut user = Users::get_manageable(id, jwt.get_role(), tenant).await.map_err(htmx_error)?;
+    user.activate_user(tenant).await.map_err(htmx_error)?;
+
+    cata_log!(Info, format!("Admin {} activated user {} (tenant: {})", jwt.get_username(), user.username, tenant));
+    Ok(HtmxSuccess::with_notification(format!("{} has been activated.", user.username)).with_header("HX-Trigger", USERS_CHANGED_EVENT))
+}
+
+#[post("/<tenant>/admin/partials/users/<id>/deactivate", data = "<action_form>")]
+pub async fn deactivate_user(tenant: &str, id: i32, jwt: JWT, action_form: Form<AdminUserActionForm>, app_context: AppContext<'_>) -> HtmxResult {
+    verify_csrf_for_state_change(&app_context, &action_form.authenticity_token).map_err(htmx_error)?;
+
+    if jwt.user_id() == id {
+        return Err(HtmxError::with_notification(Status::BadRequest, "You cannot deactivate your own account."));
+    }
+
+    let user = Users::get_manageable(id, jwt.get_role(), tenant).await.map_err(htmx_error)?;
+    Users::deactivate_user(id, tenant).await.map_err(htmx_error)?;
+    token_registry::invalidate_user_tokens(tenant, id);
+
+    cata_log!(Info, format!("Admin {} deactivated user {} (tenant: {})", jwt.get_username(), user.username, tenant));
+    Ok(HtmxSuccess::with_notification(format!("{} has been deactivated and signed out.", user.username)).with_header("HX-Trigger", USERS_CHANGED_EVENT))
+}
+
+#[post("/<tenant>/admin/partials/users/<id>/force_password_reset", data = "<action_form>")]
+pub async fn force_password_reset(tenant: &str, id: i32, jwt: JWT, action_form: Form<AdminUserActionForm>, app_context: AppContext<'_>) -> HtmxResult {
+    verify_csrf_for_state_change(&app_context, &action_form.authenticity_token).map_err(htmx_error)?;
+
+    let user = Users::get_manageable(id, jwt.get_role(), tenant).await.map_err(htmx_error)?;
+    Users::require_password_change(id, tenant).await.map_err(htmx_error)?;
+
+    cata_log!(Info, format!("Admin {} forced a password reset for user {} (tenant: {})", jwt.get_username(), user.username, tenant));
+    Ok(HtmxSuccess::with_notification(format!("{} must choose a new password at their next login.", user.username)).with_header("HX-Trigger", USERS_CHANGED_EVENT))
+}
+
+pub fn admin_user_partial_routes() -> Vec<Route> {
+    routes![users_table, activate_user, deactivate_user, force_password_reset]
+}
+
 pub fn admin_partial_routes() -> Vec<Route> {
     routes![create_duplicate_admin, test_content, test_error_with_notification, test_info, test_notification, test_warning,]
 }
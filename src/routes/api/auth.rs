@@ -0,0 +1,252 @@
+use chrono::Utc;
+use rocket::{get, http::Status, post, response::status::Custom, routes, serde::json::Json, Route};
+use serde_json::{json, Value};
+
+use crate::{
+    cata_log,
+    meltdown::*,
+    middleware::*,
+    services::default::{
+        jwt_service::{self, LoginStep},
+        token_registry,
+    },
+    structs::*,
+    vessel::structs::Vessel,
+};
+
+type ApiResult = Result<Json<Value>, Custom<Json<Value>>>;
+
+fn api_error(status: Status, code: &str, message: &str) -> Custom<Json<Value>> {
+    Custom(
+        status,
+        Json(json!({
+            "success": false,
+            "error": {
+                "code": code,
+                "message": message
+            }
+        })),
+    )
+}
+
+async fn ensure_tenant(tenant: &str) -> Result<(), Custom<Json<Value>>> {
+    match Vessel::tenant_exists(tenant).await {
+        Ok(true) => Ok(()),
+        Ok(false) => {
+            cata_log!(Warning, format!("API auth request for non-existent tenant: {}", tenant));
+            Err(api_error(Status::NotFound, "not_found", "Tenant not found"))
+        }
+        Err(e) => {
+            cata_log!(Error, format!("Error checking tenant existence: {}", e.log_message()));
+            Err(api_error(Status::InternalServerError, "database_error", "Database error"))
+        }
+    }
+}
+
+fn token_pair_json(token_pair: jwt_service::TokenPair) -> Json<Value> {
+    let expires_in = token_pair.access_claims.exp as i64 - Utc::now().timestamp();
+    let refresh_expires_in = token_pair.refresh_claims.exp as i64 - Utc::now().timestamp();
+
+    Json(json!({
+        "success": true,
+        "data": {
+            "token_type": "Bearer",
+            "access_token": token_pair.access_token,
+            "refresh_token": token_pair.refresh_token,
+            "expires_in": expires_in,
+            "refresh_expires_in": refresh_expires_in,
+            "user": {
+                "id": token_pair.access_claims.sub,
+                "username": token_pair.access_claims.username,
+                "role": token_pair.access_claims.role,
+                "password_change_required": token_pair.access_claims.password_change_required
+            }
+        }
+    }))
+}
+
+#[post("/<tenant>/api/v1/auth/login", format = "json", data = "<login>")]
+pub async fn post_api_login(tenant: &str, login: Json<ApiLoginRequest>, client: ClientInfo) -> ApiResult {
+    ensure_tenant(tenant).await?;
+
+    let login = login.into_inner();
+
+    let user = match Users::authenticate(&login.username, &login.password, &client, tenant).await {
+        Ok(user) => user,
+        Err(error) if matches!(error.melt_type, MeltType::AccountLocked) => return Err(api_error(Status::TooManyRequests, "account_locked", &error.user_message())),
+        Err(error) if matches!(error.melt_type, MeltType::InvalidCredentials) => return Err(api_error(Status::Unauthorized, "invalid_credentials", &error.user_message())),
+        Err(error) => {
+            cata_log!(Error, format!("API login failed for user: {} (tenant: {}): {}", login.username, tenant, error.log_message()));
+            return Err(api_error(Status::InternalServerError, "login_error", &error.user_message()));
+        }
+    };
+
+    let remember = login.remember_me.unwrap_or(false);
+    let device_info = Some(format!("API login at {} for tenant: {}", Utc::now().to_rfc3339(), tenant));
+
+    let login_step = user.start_session(tenant, remember, device_info).await.map_err(|error| {
+        cata_log!(Error, format!("Failed to generate API tokens (tenant: {}): {}", tenant, error.log_message()));
+        api_error(Status::InternalServerError, "token_error", &error.user_message())
+    })?;
+
+    match login_step {
+        LoginStep::Complete(token_pair) => {
+            cata_log!(Info, format!("User {} logged in via API (tenant: {})", user.username, tenant));
+            Ok(token_pair_json(token_pair))
+        }
+        LoginStep::TwoFactorPending { enrolment_required: true, .. } => {
+            cata_log!(Warning, format!("API login for user {} blocked until two-factor enrolment (tenant: {})", user.username, tenant));
+            Err(api_error(
+                Status::Forbidden,
+                "two_factor_enrolment_required",
+                "Two-factor authentication must be set up through the web login before using the API",
+            ))
+        }
+        LoginStep::TwoFactorPending { token, claims, .. } => Ok(Json(json!({
+            "success": true,
+            "data": {
+                "two_factor_required": true,
+                "two_factor_token": token,
+                "expires_in": claims.exp as i64 - Utc::now().timestamp()
+            }
+        }))),
+    }
+}
+
+#[post("/<tenant>/api/v1/auth/refresh", format = "json", data = "<refresh>")]
+pub async fn post_api_refresh(tenant: &str, refresh: Json<ApiRefreshRequest>) -> ApiResult {
+    ensure_tenant(tenant).await?;
+
+    let token_info = jwt_service::validate_refresh_token(&refresh.refresh_token).map_err(|error| {
+        cata_log!(Warning, format!("Invalid API refresh token (tenant: {}): {}", tenant, error.log_message()));
+        api_error(Status::Unauthorized, "invalid_token", "Session expired. Please log in again.")
+    })?;
+
+    if token_info.auth_system != AuthSystem::Tenant || token_info.tenant_name != tenant {
+        cata_log!(Warning, format!("API refresh token for tenant '{}' presented to tenant '{}'", token_info.tenant_name, tenant));
+        return Err(api_error(Status::Unauthorized, "invalid_token", "Session expired. Please log in again."));
+    }
+
+    let user_id = token_info.user_id;
+
+    token_registry::mark_refresh_token_used(tenant, user_id, &token_info.jti);
+
+    let user = Users::get_user_by_id(user_id, tenant).await.map_err(|error| {
+        cata_log!(Error, format!("Failed to get user {} (tenant: {}): {}", user_id, tenant, error.log_message()));
+        api_error(Status::Unauthorized, "invalid_token", "User account issue. Please log in again.")
+    })?;
+
+    let token_pair = jwt_service::generate_token_pair(&user, tenant, token_info.remember, token_info.device_info).map_err(|error| {
+        cata_log!(Error, format!("Failed to generate new API tokens (tenant: {}): {}", tenant, error.log_message()));
+        api_error(Status::InternalServerError, "token_error", &error.user_message())
+    })?;
+
+    cata_log!(Info, format!("Refreshed API tokens for user {} (tenant: {})", user_id, tenant));
+    Ok(token_pair_json(token_pair))
+}
+
+#[post("/<tenant>/api/v1/auth/two_factor", format = "json", data = "<two_factor>")]
+pub async fn post_api_two_factor(tenant: &str, two_factor: Json<ApiTwoFactorRequest>, client: ClientInfo) -> ApiResult {
+    ensure_tenant(tenant).await?;
+
+    let claims = match jwt_service::validate_two_factor_token(&two_factor.two_factor_token) {
+        Ok(claims) if claims.auth_system == AuthSystem::Tenant && claims.tenant_name.as_deref() == Some(tenant) => claims,
+        _ => return Err(api_error(Status::Unauthorized, "invalid_token", "Your login session expired. Please log in again.")),
+    };
+
+    let user_id = claims.sub.parse::<i32>().map_err(|_| api_error(Status::Unauthorized, "invalid_token", "Invalid token"))?;
+
+    let user = Users::get_user_by_id(user_id, tenant).await.map_err(|error| {
+        cata_log!(Error, format!("Failed to get user {} (tenant: {}): {}", user_id, tenant, error.log_message()));
+        api_error(Status::Unauthorized, "invalid_token", "User account issue. Please log in again.")
+    })?;
+
+    if !user.active {
+        cata_log!(Warning, format!("Two-factor login via API for inactive user: {} (tenant: {})", user.username, tenant));
+        return Err(api_error(Status::Unauthorized, "invalid_token", "Your login session expired. Please log in again."));
+    }
+
+    user.verify_two_factor_login(&two_factor.code, claims.iat, &client, tenant).await.map_err(|error| match error.melt_type {
+        MeltType::AccountLocked => api_error(Status::TooManyRequests, "account_locked", &error.user_message()),
+        MeltType::InvalidToken => api_error(Status::Unauthorized, "invalid_token", &error.user_message()),
+        MeltType::InvalidCredentials => api_error(Status::Unauthorized, "invalid_code", &error.user_message()),
+        _ => api_error(error.status_code(), "two_factor_error", &error.user_message()),
+    })?;
+
+    let token_pair = jwt_service::generate_token_pair(&user, tenant, claims.remember, claims.device_info).map_err(|error| {
+        cata_log!(Error, format!("Failed to generate API tokens (tenant: {}): {}", tenant, error.log_message()));
+        api_error(Status::InternalServerError, "token_error", &error.user_message())
+    })?;
+
+    cata_log!(Info, format!("User {} completed two-factor login via API (tenant: {})", user.username, tenant));
+    Ok(token_pair_json(token_pair))
+}
+
+#[get("/<tenant>/api/v1/auth/me")]
+pub async fn get_api_me(tenant: &str, jwt: JWT) -> ApiResult {
+    if !jwt.is_tenant_auth() || jwt.get_tenant_name().map(|name| name.as_str()) != Some(tenant) {
+        return Err(api_error(Status::Forbidden, "forbidden", "Token is not valid for this tenant"));
+    }
+
+    Ok(Json(json!({
+        "success": true,
+        "data": {
+            "id": jwt.user_id(),
+            "username": jwt.get_username(),
+            "role": jwt.get_role(),
+            "tenant": tenant,
+            "password_change_required": jwt.password_change_required(),
+            "expires_at": jwt.get_expiration()
+        }
+    })))
+}
+
+#[post("/<tenant>/api/v1/auth/change_password", format = "json", data = "<change>")]
+pub async fn post_api_change_password(tenant: &str, jwt: JWT, change: Json<ApiChangePasswordRequest>) -> ApiResult {
+    if !jwt.is_tenant_auth() || jwt.get_tenant_name().map(|name| name.as_str()) != Some(tenant) {
+        return Err(api_error(Status::Forbidden, "forbidden", "Token is not valid for this tenant"));
+    }
+
+    jwt.refuse_impersonation("password change")
+        .map_err(|error| api_error(Status::Forbidden, "impersonation_forbidden", &error.user_message()))?;
+
+    let change = change.into_inner();
+
+    if change.password.is_empty() || change.password != change.confirm_password {
+        return Err(api_error(Status::BadRequest, "validation_failed", "Passwords do not match."));
+    }
+
+    let user = Users::get_user_by_id(jwt.user_id(), tenant).await.map_err(|error| {
+        cata_log!(Error, format!("Failed to get user {} (tenant: {}): {}", jwt.user_id(), tenant, error.log_message()));
+        api_error(Status::Unauthorized, "invalid_token", "User account issue. Please log in again.")
+    })?;
+
+    if !matches!(user.verify_password(change.current_password).await, Ok(true)) {
+        return Err(api_error(Status::Unauthorized, "invalid_credentials", "Current password is incorrect."));
+    }
+
+    if matches!(user.verify_password(change.password.clone()).await, Ok(true)) {
+        return Err(api_error(Status::BadRequest, "validation_failed", "Please choose a password different from your current one."));
+    }
+
+    Users::change_password_by_id(user.id, &change.password, tenant).await.map_err(|error| {
+        cata_log!(Error, format!("Failed to change password for user {} (tenant: {}): {}", user.id, tenant, error.log_message()));
+        api_error(error.status_code(), "password_change_failed", &error.user_message())
+    })?;
+
+    let user = Users::get_user_by_id(user.id, tenant)
+        .await
+        .map_err(|error| api_error(error.status_code(), "database_error", &error.user_message()))?;
+
+    let token_pair = jwt_service::generate_token_pair(&user, tenant, jwt.0.remember, jwt.get_device_info().cloned()).map_err(|error| {
+        cata_log!(Error, format!("Failed to generate API tokens (tenant: {}): {}", tenant, error.log_message()));
+        api_error(Status::InternalServerError, "token_error", &error.user_message())
+    })?;
+
+    cata_log!(Info, format!("User {} changed their password via API (tenant: {})", user.username, tenant));
+    Ok(token_pair_json(token_pair))
+}
+
+pub fn api_auth_routes() -> Vec<Route> {
+    routes![post_api_login, post_api_refresh, post_api_two_factor, post_api_change_password, get_api_me]
+}
@@ -1,7 +1,9 @@
 pub mod admin_partials;
+pub mod auth;
 pub mod user_partials;
 pub mod v1;
 
 pub use admin_partials::*;
+pub use auth::*;
 pub use user_partials::*;
 pub use v1::*;
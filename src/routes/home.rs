@@ -5,14 +5,14 @@ use rocket::{
     http::{Cookie, CookieJar},
     post,
     response::{Flash, Redirect},
-    routes, uri, Route,
+    routes, uri, Responder, Route,
 };
 use rocket_dyn_templates::Template;
 
 use crate::{cata_log, meltdown::*, middleware::*, routes::*, services::default::*, structs::*, vessel::structs::Vessel};
 
 #[post("/<tenant>/auth/login", data = "<login_form>")]
-async fn post_login(tenant: &str, login_form: Form<LoginForm>, cookies: &CookieJar<'_>, app_context: AppContext<'_>, jwt: Option<JWT>) -> Result<Flash<Redirect>, Flash<Redirect>> {
+async fn post_login(tenant: &str, login_form: Form<LoginForm>, cookies: &CookieJar<'_>, app_context: AppContext<'_>, jwt: Option<JWT>, client: ClientInfo) -> Result<Flash<Redirect>, Flash<Redirect>> {
     match Vessel::tenant_exists(tenant).await {
         Ok(exists) => {
             if !exists {
@@ -60,54 +60,180 @@ async fn post_login(tenant: &str, login_form: Form<LoginForm>, cookies: &CookieJ
         }
     }
 
-    let user = match Users::get_user_by_username(login.username.clone(), tenant).await {
+    let user = match Users::authenticate(&login.username, &login.password, &client, tenant).await {
         Ok(user) => user,
-        Err(_) => {
-            cata_log!(Warning, format!("Login attempt with invalid username: {} for tenant: {}", login.username, tenant));
-            return Err(Flash::error(Redirect::to(uri!(get_login(tenant))), MeltDown::invalid_credentials().user_message()));
-        }
+        Err(error) => return Err(Flash::error(Redirect::to(uri!(get_login(tenant))), error.user_message())),
     };
 
-    match user.verify_password(login.password.clone()).await {
-        Ok(true) => {
-            let remember = login.remember_me.unwrap_or(false);
+    let remember = login.remember_me.unwrap_or(false);
 
-            let device_info = Some(format!("Login at {} for tenant: {}", Utc::now().to_rfc3339(), tenant));
+    let device_info = Some(format!("Login at {} for tenant: {}", Utc::now().to_rfc3339(), tenant));
 
-            crate::services::default::jwt_service::set_current_tenant(tenant);
+    let token_pair = match user.start_session(tenant, remember, device_info).await {
+        Ok(LoginStep::Complete(pair)) => pair,
+        Ok(LoginStep::TwoFactorPending { token, enrolment_required, .. }) => {
+            cata_log!(
+                Info,
+                format!(
+                    "User {} passed password check, two-factor {} pending (tenant: {})",
+                    user.username,
+                    if enrolment_required { "enrolment" } else { "verification" },
+                    tenant
+                )
+            );
+            cookies.add(Cookie::build(Cookie::new("two_factor_token", token)).http_only(true).secure(true).build());
 
-            let token_pair = match crate::services::default::jwt_service::generate_token_pair(&user, remember, device_info) {
-                Ok(pair) => pair,
-                Err(error) => {
-                    return Err(Flash::error(Redirect::to(uri!(get_login(tenant))), error.user_message()));
-                }
+            let message = if enrolment_required {
+                "Your administrator requires two-factor authentication. Please set it up to continue."
+            } else {
+                "Enter the code from your authenticator app."
             };
+            return Ok(Flash::success(Redirect::to(uri!(get_two_factor(tenant))), message));
+        }
+        Err(error) => {
+            return Err(Flash::error(Redirect::to(uri!(get_login(tenant))), error.user_message()));
+        }
+    };
 
-            cookies.add(Cookie::build(Cookie::new("access_token", token_pair.access_token)).http_only(true).secure(true).build());
-            cookies.add(Cookie::build(Cookie::new("refresh_token", token_pair.refresh_token)).http_only(true).secure(true).build());
-            cookies.add(Cookie::build(Cookie::new("user_id", user.id.to_string())).http_only(true).secure(true).build());
+    let access_expiry = token_pair.access_claims.exp as i64 - Utc::now().timestamp();
+    let refresh_expiry = token_pair.refresh_claims.exp as i64 - Utc::now().timestamp();
 
-            let access_expiry = token_pair.access_claims.exp as i64 - Utc::now().timestamp();
-            let refresh_expiry = token_pair.refresh_claims.exp as i64 - Utc::now().timestamp();
+    cata_log!(
+        Debug,
+        format!(
+            "Issuing token pair for user {}: access token expires in {}s, refresh token expires in {}s, auth_system: {:?} (tenant: {})",
+            user.id, access_expiry, refresh_expiry, token_pair.access_claims.auth_system, tenant
+        )
+    );
 
-            cata_log!(
-                Debug,
-                format!(
-                    "Issuing token pair for user {}: access token expires in {}s, refresh token expires in {}s, auth_system: {:?} (tenant: {})",
-                    user.id, access_expiry, refresh_expiry, token_pair.access_claims.auth_system, tenant
-                )
-            );
-            cata_log!(Info, format!("User {} logged in successfully (tenant: {})", user.username, tenant));
+    set_auth_cookies(cookies, token_pair, user.id);
 
-            let is_admin = user.role == "admin";
-            let redirect_uri = if is_admin { uri!(admin::get_admin_dashboard(tenant)) } else { uri!(user::get_user_dashboard(tenant)) };
+    cata_log!(Info, format!("User {} logged in successfully (tenant: {})", user.username, tenant));
+
+    if user.should_change_password {
+        return Ok(Flash::success(Redirect::to(uri!(get_change_password(tenant))), "Please choose a new password to continue."));
+    }
 
-            Ok(Flash::success(Redirect::to(redirect_uri), "Successfully logged in."))
+    Ok(Flash::success(Redirect::to(dashboard_uri(&user.role, tenant).await), "Successfully logged in."))
+}
+
+pub fn set_auth_cookies(cookies: &CookieJar<'_>, token_pair: TokenPair, user_id: i32) {
+    cookies.add(Cookie::build(Cookie::new("access_token", token_pair.access_token)).http_only(true).secure(true).build());
+    cookies.add(Cookie::build(Cookie::new("refresh_token", token_pair.refresh_token)).http_only(true).secure(true).build());
+    cookies.add(Cookie::build(Cookie::new("user_id", user_id.to_string())).http_only(true).secure(true).build());
+}
+
+pub async fn dashboard_uri(role: &str, tenant: &str) -> String {
+    if permission_registry::role_has_permission(tenant, role, ADMIN_ACCESS).await.unwrap_or(false) {
+        uri!(admin::get_admin_dashboard(tenant)).to_string()
+    } else {
+        uri!(user::get_user_dashboard(tenant)).to_string()
+    }
+}
+
+async fn pending_two_factor_user(tenant: &str, cookies: &CookieJar<'_>) -> Result<(Users, Claims), Flash<Redirect>> {
+    let expired = || Flash::error(Redirect::to(uri!(get_login(tenant))), "Your login session expired. Please log in again.");
+
+    let token = cookies.get("two_factor_token").map(|cookie| cookie.value().to_string()).ok_or_else(expired)?;
+
+    let claims = match validate_two_factor_token(&token) {
+        Ok(claims) if claims.auth_system == AuthSystem::Tenant && claims.tenant_name.as_deref() == Some(tenant) => claims,
+        _ => {
+            cookies.remove(Cookie::new("two_factor_token", ""));
+            return Err(expired());
+        }
+    };
+
+    let user_id = claims.sub.parse::<i32>().map_err(|_| expired())?;
+
+    match Users::get_user_by_id(user_id, tenant).await {
+        Ok(user) if user.active => Ok((user, claims)),
+        _ => {
+            cookies.remove(Cookie::new("two_factor_token", ""));
+            Err(expired())
         }
-        Ok(false) | Err(_) => {
-            cata_log!(Warning, format!("Failed login attempt for user: {} (tenant: {})", login.username, tenant));
-            Err(Flash::error(Redirect::to(uri!(get_login(tenant))), MeltDown::invalid_credentials().user_message()))
+    }
+}
+
+#[get("/<tenant>/auth/two_factor")]
+async fn get_two_factor(tenant: &str, cookies: &CookieJar<'_>, app_context: AppContext<'_>) -> Result<Template, Flash<Redirect>> {
+    let (user, _) = pending_two_factor_user(tenant, cookies).await?;
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String("Two-factor verification".to_string()));
+
+    let two_factor = UserTwoFactor::find_by_user_id(user.id, tenant)
+        .await
+        .map_err(|error| Flash::error(Redirect::to(uri!(get_login(tenant))), error.user_message()))?;
+
+    if !two_factor.as_ref().is_some_and(|two_factor| two_factor.enabled) {
+        let enrolment = UserTwoFactor::begin_enrolment(user.id, tenant)
+            .await
+            .map_err(|error| Flash::error(Redirect::to(uri!(get_login(tenant))), error.user_message()))?;
+        let qr_svg = enrolment
+            .provisioning_uri(&user.username, tenant)
+            .and_then(|provisioning_uri| totp_qr_svg(&provisioning_uri))
+            .map_err(|error| Flash::error(Redirect::to(uri!(get_login(tenant))), error.user_message()))?;
+
+        context_data.insert("enrolment".to_string(), serde_json::Value::Bool(true));
+        context_data.insert("qr_svg".to_string(), serde_json::Value::String(qr_svg));
+        context_data.insert("secret".to_string(), serde_json::Value::String(enrolment.secret));
+    }
+
+    Ok(app_context.render_with("auth/two_factor", TenantData::new(tenant, context_data)))
+}
+
+#[derive(Responder)]
+enum TwoFactorOutcome {
+    RecoveryCodes(Template),
+    Complete(Flash<Redirect>),
+}
+
+#[post("/<tenant>/auth/two_factor", data = "<two_factor_form>")]
+async fn post_two_factor(tenant: &str, two_factor_form: Form<TwoFactorCodeForm>, cookies: &CookieJar<'_>, app_context: AppContext<'_>, client: ClientInfo) -> Result<TwoFactorOutcome, Flash<Redirect>> {
+    let form = two_factor_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Err(Flash::error(Redirect::to(uri!(get_two_factor(tenant))), csrf_error.user_message()));
+    }
+
+    let (user, claims) = pending_two_factor_user(tenant, cookies).await?;
+
+    let recovery_codes = match UserTwoFactor::is_enabled(user.id, tenant).await {
+        Ok(true) => match user.verify_two_factor_login(&form.code, claims.iat, &client, tenant).await {
+            Ok(()) => None,
+            Err(error) if matches!(error.melt_type, MeltType::InvalidToken | MeltType::AccountLocked) => {
+                cookies.remove(Cookie::new("two_factor_token", ""));
+                return Err(Flash::error(Redirect::to(uri!(get_login(tenant))), error.user_message()));
+            }
+            Err(error) => return Err(Flash::error(Redirect::to(uri!(get_two_factor(tenant))), error.user_message())),
+        },
+        Ok(false) => match UserTwoFactor::confirm_enrolment(user.id, &form.code, tenant).await {
+            Ok(codes) => {
+                cata_log!(Info, format!("User {} enabled two-factor authentication during login (tenant: {})", user.username, tenant));
+                Some(codes)
+            }
+            Err(error) => return Err(Flash::error(Redirect::to(uri!(get_two_factor(tenant))), error.user_message())),
+        },
+        Err(error) => return Err(Flash::error(Redirect::to(uri!(get_login(tenant))), error.user_message())),
+    };
+
+    let token_pair = generate_token_pair(&user, tenant, claims.remember, claims.device_info).map_err(|error| Flash::error(Redirect::to(uri!(get_login(tenant))), error.user_message()))?;
+
+    cookies.remove(Cookie::new("two_factor_token", ""));
+    set_auth_cookies(cookies, token_pair, user.id);
+
+    cata_log!(Info, format!("User {} completed two-factor login (tenant: {})", user.username, tenant));
+
+    match recovery_codes {
+        Some(codes) => {
+            let mut context_data = serde_json::Map::new();
+            context_data.insert("title".to_string(), serde_json::Value::String("Recovery codes".to_string()));
+            context_data.insert("recovery_codes".to_string(), serde_json::json!(codes));
+            context_data.insert("continue_url".to_string(), serde_json::Value::String(dashboard_uri(&user.role, tenant).await));
+            Ok(TwoFactorOutcome::RecoveryCodes(app_context.render_with("auth/recovery_codes", TenantData::new(tenant, context_data))))
         }
+        None => Ok(TwoFactorOutcome::Complete(Flash::success(Redirect::to(dashboard_uri(&user.role, tenant).await), "Successfully logged in."))),
     }
 }
 
@@ -184,8 +310,8 @@ fn get_logout(tenant: &str, cookies: &CookieJar<'_>, jwt: Option<JWT>) -> Flash<
     Flash::success(Redirect::to(uri!(get_login(tenant))), "Successfully logged out.")
 }
 
-#[get("/<tenant>/auth/register")]
-async fn get_register(tenant: &str, app_context: AppContext<'_>) -> Result<Template, Flash<Redirect>> {
+#[get("/<tenant>/auth/register?<invite>")]
+async fn get_register(tenant: &str, invite: Option<&str>, app_context: AppContext<'_>) -> Result<Template, Flash<Redirect>> {
     match Vessel::tenant_exists(tenant).await {
         Ok(exists) => {
             if !exists {
@@ -201,9 +327,30 @@ async fn get_register(tenant: &str, app_context: AppContext<'_>) -> Result<Templ
         }
     }
 
+    let policy = RegistrationPolicy::load(tenant).await.map_err(|error| Flash::error(Redirect::to(uri!(get_login(tenant))), error.user_message()))?;
+
+    let invitation = match invite.filter(|token| !token.is_empty()) {
+        Some(token) => match Invitation::find_valid(token, tenant).await {
+            Ok(invitation) => Some((invitation, token)),
+            Err(error) => return Err(Flash::error(Redirect::to(uri!(get_login(tenant))), error.user_message())),
+        },
+        None => None,
+    };
+
+    if invitation.is_none() || policy.mode == RegistrationMode::Disabled {
+        policy.check_open().map_err(|error| Flash::error(Redirect::to(uri!(get_login(tenant))), error.user_message()))?;
+    }
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("registration_mode".to_string(), serde_json::Value::String(policy.mode.as_str().to_string()));
+    context_data.insert("allowed_domains".to_string(), serde_json::json!(policy.allowed_domains));
+    if let Some((invitation, token)) = invitation {
+        context_data.insert("invitation_email".to_string(), serde_json::Value::String(invitation.email));
+        context_data.insert("invitation_token".to_string(), serde_json::Value::String(token.to_string()));
+    }
+
     cata_log!(Info, format!("Rendering registration page for tenant: {}", tenant));
-    let tenant_data = TenantData::new(tenant, ());
-    Ok(app_context.render_with("auth/register", tenant_data))
+    Ok(app_context.render_with("auth/register", TenantData::new(tenant, context_data)))
 }
 
 #[post("/<tenant>/auth/register", data = "<register_form>")]
@@ -225,18 +372,220 @@ async fn post_register(tenant: &str, register_form: Form<RegisterForm>, app_cont
 
     let register = register_form.into_inner();
 
+    let register_uri = uri!(get_register(tenant, register.invitation.as_deref().filter(|token| !token.is_empty())));
+
     if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &register.authenticity_token) {
-        return Err(Flash::error(Redirect::to(uri!(get_register(tenant))), csrf_error.user_message()));
+        return Err(Flash::error(Redirect::to(register_uri), csrf_error.user_message()));
     }
 
-    match Users::register_user(register.clone(), tenant).await {
-        Ok(()) => {
-            cata_log!(Info, format!("User registered successfully (tenant: {})", tenant));
-            Ok(Flash::success(Redirect::to(uri!(get_login(tenant))), "Successfully registered."))
+    match Users::register_with_policy(register.clone(), tenant).await {
+        Ok(user) => {
+            cata_log!(Info, format!("User registered successfully with role {} (tenant: {})", user.role, tenant));
+
+            if user.is_email_verified() {
+                return Ok(Flash::success(Redirect::to(uri!(get_login(tenant))), "Successfully registered."));
+            }
+
+            if let Err(error) = user.send_email_verification(tenant).await {
+                cata_log!(Warning, format!("Failed to send verification email (tenant: {}): {}", tenant, error.log_message()));
+                if !user.active {
+                    return Err(Flash::error(
+                        Redirect::to(uri!(get_login(tenant))),
+                        "Registered, but the activation email could not be sent. Please contact an administrator.",
+                    ));
+                }
+                return Ok(Flash::success(Redirect::to(uri!(get_login(tenant))), "Successfully registered."));
+            }
+
+            if !user.active {
+                return Ok(Flash::success(Redirect::to(uri!(get_login(tenant))), "Successfully registered. Check your email to activate your account."));
+            }
+
+            Ok(Flash::success(Redirect::to(uri!(get_login(tenant))), "Successfully registered. Check your email to verify your address."))
         }
         Err(err_msg) => {
             cata_log!(Error, format!("Registration error (tenant: {}): {}", tenant, err_msg.log_message()));
-            Err(Flash::error(Redirect::to(uri!(get_register(tenant))), err_msg.user_message()))
+            Err(Flash::error(Redirect::to(register_uri), err_msg.user_message()))
+        }
+    }
+}
+
+#[get("/<tenant>/auth/change_password")]
+async fn get_change_password(tenant: &str, jwt: JWT, app_context: AppContext<'_>) -> Result<Template, Flash<Redirect>> {
+    if !jwt.is_tenant_auth() || jwt.get_tenant_name().map(|name| name.as_str()) != Some(tenant) {
+        return Err(Flash::error(Redirect::to(uri!(get_login(tenant))), "Please log in to this tenant first."));
+    }
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String("Change your password".to_string()));
+    context_data.insert("required".to_string(), serde_json::Value::Bool(jwt.password_change_required()));
+
+    Ok(app_context.render_with("auth/change_password", TenantData::new(tenant, context_data)))
+}
+
+#[post("/<tenant>/auth/change_password", data = "<password_form>")]
+async fn post_change_password(tenant: &str, jwt: JWT, password_form: Form<UpdatePassword<'_>>, cookies: &CookieJar<'_>, app_context: AppContext<'_>) -> Result<Flash<Redirect>, Flash<Redirect>> {
+    if !jwt.is_tenant_auth() || jwt.get_tenant_name().map(|name| name.as_str()) != Some(tenant) {
+        return Err(Flash::error(Redirect::to(uri!(get_login(tenant))), "Please log in to this tenant first."));
+    }
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, password_form.authenticity_token) {
+        return Err(Flash::error(Redirect::to(uri!(get_change_password(tenant))), csrf_error.user_message()));
+    }
+
+    if let Err(error) = jwt.refuse_impersonation("password change") {
+        return Err(Flash::error(Redirect::to(dashboard_uri(jwt.get_role(), tenant).await), error.user_message()));
+    }
+
+    if password_form.password.is_empty() || password_form.password != password_form.confirm_password {
+        return Err(Flash::error(Redirect::to(uri!(get_change_password(tenant))), "Passwords do not match."));
+    }
+
+    let user = match Users::get_user_by_id(jwt.user_id(), tenant).await {
+        Ok(user) => user,
+        Err(error) => return Err(Flash::error(Redirect::to(uri!(get_login(tenant))), error.user_message())),
+    };
+
+    if !jwt.password_change_required() && !user.should_change_password {
+        cata_log!(Warning, format!("Rejected forced password change for user {} without a pending change (tenant: {})", user.username, tenant));
+        return Err(Flash::error(Redirect::to(uri!(user::get_user_profile(tenant))), "Please change your password from your profile page."));
+    }
+
+    if matches!(user.verify_password(password_form.password.to_string()).await, Ok(true)) {
+        return Err(Flash::error(Redirect::to(uri!(get_change_password(tenant))), "Please choose a password different from your current one."));
+    }
+
+    if let Err(error) = Users::change_password_by_id(user.id, password_form.password, tenant).await {
+        cata_log!(Error, format!("Failed to change password for user {} (tenant: {}): {}", user.id, tenant, error.log_message()));
+        return Err(Flash::error(Redirect::to(uri!(get_change_password(tenant))), error.user_message()));
+    }
+
+    let user = match Users::get_user_by_id(user.id, tenant).await {
+        Ok(user) => user,
+        Err(error) => return Err(Flash::error(Redirect::to(uri!(get_login(tenant))), error.user_message())),
+    };
+
+    let device_info = jwt.get_device_info().cloned();
+    match generate_token_pair(&user, tenant, jwt.0.remember, device_info) {
+        Ok(token_pair) => set_auth_cookies(cookies, token_pair, user.id),
+        Err(error) => {
+            cata_log!(Error, format!("Failed to reissue tokens after password change (tenant: {}): {}", tenant, error.log_message()));
+            return Err(Flash::error(Redirect::to(uri!(get_login(tenant))), "Password changed. Please log in again."));
+        }
+    }
+
+    cata_log!(Info, format!("User {} changed their password (tenant: {})", user.username, tenant));
+    Ok(Flash::success(Redirect::to(dashboard_uri(&user.role, tenant).await), "Your password has been changed."))
+}
+
+#[get("/<tenant>/auth/forgot")]
+async fn get_forgot_password(tenant: &str, app_context: AppContext<'_>) -> Result<Template, Flash<Redirect>> {
+    match Vessel::tenant_exists(tenant).await {
+        Ok(exists) => {
+            if !exists {
+                cata_log!(Warning, format!("Attempted to access password reset for non-existent tenant: {}", tenant));
+                let error = MeltDown::new(MeltType::NotFound, "Tenant not found");
+                return Err(Flash::error(Redirect::to(uri!("/vessel/auth/login")), error.user_message()));
+            }
+        }
+        Err(e) => {
+            cata_log!(Error, format!("Error checking tenant existence: {}", e.log_message()));
+            let error = MeltDown::new(MeltType::DatabaseError, "Database error");
+            return Err(Flash::error(Redirect::to(uri!("/vessel/auth/login")), error.user_message()));
+        }
+    }
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String("Forgot password".to_string()));
+
+    Ok(app_context.render_with("auth/forgot", TenantData::new(tenant, context_data)))
+}
+
+#[post("/<tenant>/auth/forgot", data = "<forgot_form>")]
+async fn post_forgot_password(tenant: &str, forgot_form: Form<ForgotPasswordForm>, app_context: AppContext<'_>) -> Result<Flash<Redirect>, Flash<Redirect>> {
+    match Vessel::tenant_exists(tenant).await {
+        Ok(exists) => {
+            if !exists {
+                cata_log!(Warning, format!("Attempted password reset for non-existent tenant: {}", tenant));
+                let error = MeltDown::new(MeltType::NotFound, "Tenant not found");
+                return Err(Flash::error(Redirect::to(uri!("/vessel/auth/login")), error.user_message()));
+            }
+        }
+        Err(e) => {
+            cata_log!(Error, format!("Error checking tenant existence: {}", e.log_message()));
+            let error = MeltDown::new(MeltType::DatabaseError, "Database error");
+            return Err(Flash::error(Redirect::to(uri!("/vessel/auth/login")), error.user_message()));
+        }
+    }
+
+    let forgot = forgot_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &forgot.authenticity_token) {
+        return Err(Flash::error(Redirect::to(uri!(get_forgot_password(tenant))), csrf_error.user_message()));
+    }
+
+    if let Err(error) = Users::request_password_reset(&forgot.email, tenant).await {
+        cata_log!(Error, format!("Password reset request failed (tenant: {}): {}", tenant, error.log_message()));
+    }
+
+    Ok(Flash::success(
+        Redirect::to(uri!(get_login(tenant))),
+        "If an account matches that email address, a password reset link is on its way.",
+    ))
+}
+
+#[get("/<tenant>/auth/reset/<token>")]
+async fn get_reset_password(tenant: &str, token: &str, app_context: AppContext<'_>) -> Result<Template, Flash<Redirect>> {
+    if let Err(error) = UserToken::peek(token, TokenPurpose::PasswordReset, tenant).await {
+        cata_log!(Warning, format!("Rejected password reset link (tenant: {}): {}", tenant, error.log_message()));
+        return Err(Flash::error(Redirect::to(uri!(get_forgot_password(tenant))), error.user_message()));
+    }
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String("Choose a new password".to_string()));
+
+    Ok(app_context.render_with("auth/reset", TenantData::new(tenant, context_data)))
+}
+
+#[post("/<tenant>/auth/reset/<token>", data = "<reset_form>")]
+async fn post_reset_password(tenant: &str, token: &str, reset_form: Form<ResetPasswordForm>, cookies: &CookieJar<'_>, app_context: AppContext<'_>) -> Result<Flash<Redirect>, Flash<Redirect>> {
+    let reset = reset_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &reset.authenticity_token) {
+        return Err(Flash::error(Redirect::to(uri!(get_reset_password(tenant, token))), csrf_error.user_message()));
+    }
+
+    if reset.password.is_empty() || reset.password != reset.confirm_password {
+        return Err(Flash::error(Redirect::to(uri!(get_reset_password(tenant, token))), "Passwords do not match."));
+    }
+
+    match Users::reset_password_with_token(token, &reset.password, tenant).await {
+        Ok(user) => {
+            cookies.remove(Cookie::new("access_token", ""));
+            cookies.remove(Cookie::new("refresh_token", ""));
+            cookies.remove(Cookie::new("user_id", ""));
+
+            cata_log!(Info, format!("User {} reset their password (tenant: {})", user.id, tenant));
+            Ok(Flash::success(Redirect::to(uri!(get_login(tenant))), "Your password has been reset. Please log in."))
+        }
+        Err(error) if matches!(error.melt_type, MeltType::ValidationFailed) => Err(Flash::error(Redirect::to(uri!(get_reset_password(tenant, token))), error.user_message())),
+        Err(error) => {
+            cata_log!(Warning, format!("Password reset failed (tenant: {}): {}", tenant, error.log_message()));
+            Err(Flash::error(Redirect::to(uri!(get_forgot_password(tenant))), error.user_message()))
+        }
+    }
+}
+
+#[get("/<tenant>/auth/verify/<token>")]
+async fn get_verify_email(tenant: &str, token: &str) -> Result<Flash<Redirect>, Flash<Redirect>> {
+    match Users::verify_email_with_token(token, tenant).await {
+        Ok(user) => {
+            cata_log!(Info, format!("User {} verified their email address (tenant: {})", user.id, tenant));
+            Ok(Flash::success(Redirect::to(uri!(get_login(tenant))), "Your email address has been verified."))
+        }
+        Err(error) => {
+            cata_log!(Warning, format!("Email verification failed (tenant: {}): {}", tenant, error.log_message()));
+            Err(Flash::error(Redirect::to(uri!(get_login(tenant))), error.user_message()))
         }
     }
 }
@@ -262,6 +611,13 @@ async fn get_login(tenant: &str, app_context: AppContext<'_>, jwt: Option<JWT>,
 
     context_data.insert("title".to_string(), serde_json::Value::String(format!("{} Login", tenant)));
 
+    match OidcProvider::get_enabled(tenant).await {
+        Ok(providers) => {
+            context_data.insert("oidc_providers".to_string(), serde_json::to_value(&providers).unwrap_or_default());
+        }
+        Err(error) => cata_log!(Error, format!("Failed to load sign-in providers (tenant: {}): {}", tenant, error.log_message())),
+    }
+
     let show_login = if let Some(ref jwt) = jwt {
         if jwt.is_tenant_auth() {
             if let Some(jwt_tenant) = jwt.get_tenant_name() {
@@ -276,7 +632,7 @@ async fn get_login(tenant: &str, app_context: AppContext<'_>, jwt: Option<JWT>,
                 }
             }
 
-            let is_admin = jwt.is_admin();
+            let is_admin = jwt.is_admin().await;
             let redirect_uri = if is_admin { uri!(admin::get_admin_dashboard(tenant)) } else { uri!(user::get_user_dashboard(tenant)) };
             cata_log!(Info, format!("User {} is already logged in to tenant system (tenant: {})", jwt.get_username(), tenant));
             return Err(Flash::success(Redirect::to(redirect_uri), "Already logged in to tenant system."));
@@ -329,7 +685,7 @@ pub async fn get_home(tenant: &str, app_context: AppContext<'_>, jwt: Option<JWT
 
     let show_home = if let Some(ref jwt) = jwt {
         if jwt.is_tenant_auth() {
-            let is_admin = jwt.is_admin();
+            let is_admin = jwt.is_admin().await;
             let redirect_uri = if is_admin { uri!(admin::get_admin_dashboard(tenant)) } else { uri!(user::get_user_dashboard(tenant)) };
             cata_log!(Info, format!("User {} is already logged in to tenant system, redirecting to dashboard (tenant: {})", jwt.get_username(), tenant));
             return Err(Flash::success(Redirect::to(redirect_uri), "Already logged in to tenant system."));
@@ -391,9 +747,7 @@ async fn refresh_token(tenant: &str, cookies: &CookieJar<'_>) -> Result<(), Flas
         }
     };
 
-    crate::services::default::jwt_service::set_current_tenant(tenant);
-
-    let token_pair = match crate::services::default::jwt_service::generate_token_pair(&user, token_info.remember, token_info.device_info) {
+    let token_pair = match generate_token_pair(&user, tenant, token_info.remember, token_info.device_info) {
         Ok(pair) => pair,
         Err(error) => {
             cata_log!(Error, format!("Failed to generate new tokens (tenant: {}): {}", tenant, error.log_message()));
@@ -401,9 +755,7 @@ async fn refresh_token(tenant: &str, cookies: &CookieJar<'_>) -> Result<(), Flas
         }
     };
 
-    cookies.add(Cookie::build(Cookie::new("access_token", token_pair.access_token)).http_only(true).secure(true).build());
-    cookies.add(Cookie::build(Cookie::new("refresh_token", token_pair.refresh_token)).http_only(true).secure(true).build());
-    cookies.add(Cookie::build(Cookie::new("user_id", user_id.to_string())).http_only(true).secure(true).build());
+    set_auth_cookies(cookies, token_pair, user_id);
 
     cata_log!(Info, format!("Refreshed tokens for user {} (tenant: {})", user_id, tenant));
 
@@ -411,5 +763,22 @@ async fn refresh_token(tenant: &str, cookies: &CookieJar<'_>) -> Result<(), Flas
 }
 
 pub fn routes() -> Vec<Route> {
-    routes![get_home, get_login, get_logout, get_register, post_login, post_register, refresh_token]
+    routes![
+        get_home,
+        get_login,
+        get_logout,
+        get_register,
+        post_login,
+        post_register,
+        get_two_factor,
+        post_two_factor,
+        get_forgot_password,
+        post_forgot_password,
+        get_reset_password,
+        post_reset_password,
+        get_verify_email,
+        get_change_password,
+        post_change_password,
+        refresh_token
+    ]
 }
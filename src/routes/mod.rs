@@ -1,9 +1,13 @@
 pub mod admin;
 pub mod api;
 pub mod home;
+pub mod oidc;
+pub mod spark_assets;
 pub mod user;
 
 pub use admin::*;
 pub use api::*;
 pub use home::*;
+pub use oidc::*;
+pub use spark_assets::*;
 pub use user::*;
@@ -0,0 +1,159 @@
+use chrono::Utc;
+use rocket::{
+    get,
+    http::{Cookie, CookieJar, SameSite},
+    response::{Flash, Redirect},
+    routes, Route,
+};
+
+use crate::{
+    cata_log,
+    meltdown::*,
+    middleware::*,
+    routes::home::{dashboard_uri, set_auth_cookies},
+    services::default::{jwt_service::LoginStep, oidc_service},
+    structs::*,
+    vessel::structs::Vessel,
+};
+
+fn login_redirect(tenant: &str, message: String) -> Flash<Redirect> {
+    Flash::error(Redirect::to(format!("/{}/auth/login", tenant)), message)
+}
+
+async fn ensure_tenant(tenant: &str) -> Result<(), Flash<Redirect>> {
+    match Vessel::tenant_exists(tenant).await {
+        Ok(true) => Ok(()),
+        Ok(false) => {
+            cata_log!(Warning, format!("Attempted single sign-on for non-existent tenant: {}", tenant));
+            let error = MeltDown::new(MeltType::NotFound, "Tenant not found");
+            Err(Flash::error(Redirect::to("/vessel/auth/login"), error.user_message()))
+        }
+        Err(e) => {
+            cata_log!(Error, format!("Error checking tenant existence: {}", e.log_message()));
+            let error = MeltDown::new(MeltType::DatabaseError, "Database error");
+            Err(Flash::error(Redirect::to("/vessel/auth/login"), error.user_message()))
+        }
+    }
+}
+
+#[get("/<tenant>/auth/oidc/<provider>/login")]
+pub async fn get_oidc_login(tenant: &str, provider: &str, cookies: &CookieJar<'_>) -> Result<Redirect, Flash<Redirect>> {
+    ensure_tenant(tenant).await?;
+
+    let provider = OidcProvider::get_enabled_by_slug(provider, tenant).await.map_err(|error| login_redirect(tenant, error.user_message()))?;
+
+    let (authorization_url, state) = oidc_service::authorization_url(&provider, tenant).await.map_err(|error| {
+        cata_log!(Error, format!("Failed to start single sign-on with {} (tenant: {}): {}", provider.slug, tenant, error.log_message()));
+        login_redirect(tenant, error.user_message())
+    })?;
+
+    cookies.add(
+        Cookie::build(Cookie::new(OIDC_STATE_COOKIE, state))
+            .http_only(true)
+            .secure(true)
+            .same_site(SameSite::Lax)
+            .max_age(rocket::time::Duration::seconds(oidc_service::oidc_settings().flow_ttl_secs))
+            .build(),
+    );
+
+    cata_log!(Info, format!("Redirecting to identity provider {} (tenant: {})", provider.slug, tenant));
+    Ok(Redirect::to(authorization_url))
+}
+
+#[get("/<tenant>/auth/oidc/<provider>/callback?<code>&<state>&<error>&<error_description>")]
+pub async fn get_oidc_callback(
+    tenant: &str,
+    provider: &str,
+    code: Option<&str>,
+    state: Option<&str>,
+    error: Option<&str>,
+    error_description: Option<&str>,
+    cookies: &CookieJar<'_>,
+    client: ClientInfo,
+) -> Result<Flash<Redirect>, Flash<Redirect>> {
+    ensure_tenant(tenant).await?;
+
+    let expected_state = cookies.get(OIDC_STATE_COOKIE).map(|cookie| cookie.value().to_string());
+    cookies.remove(Cookie::new(OIDC_STATE_COOKIE, ""));
+
+    let provider = OidcProvider::get_enabled_by_slug(provider, tenant).await.map_err(|error| login_redirect(tenant, error.user_message()))?;
+    let attempt_name = format!("oidc:{}", provider.slug);
+
+    let rejected = |details: String, message: String| {
+        cata_log!(Warning, format!("Single sign-on with {} rejected (tenant: {}): {}", provider.slug, tenant, details));
+        login_redirect(tenant, message)
+    };
+
+    if let Some(error) = error {
+        LoginAttempt::log_attempt(&attempt_name, None, Some(LoginFailureReason::SsoRejected), &client, tenant).await;
+        return Err(rejected(
+            format!("{}: {}", error, error_description.unwrap_or_default()),
+            "Sign-in was cancelled or rejected by your identity provider.".to_string(),
+        ));
+    }
+
+    let (Some(code), Some(state)) = (code, state) else {
+        return Err(rejected("Callback without code or state".to_string(), "Single sign-on failed. Please try again.".to_string()));
+    };
+
+    if expected_state.as_deref() != Some(state) {
+        return Err(rejected("State does not match the browser session".to_string(), "Your sign-in attempt has expired. Please try again.".to_string()));
+    }
+
+    let flow = oidc_service::take_pending_flow(state, &provider, tenant).map_err(|error| rejected(error.log_message(), error.user_message()))?;
+
+    let claims = match oidc_service::exchange_code(&provider, code, &flow).await {
+        Ok(claims) => claims,
+        Err(error) => {
+            LoginAttempt::log_attempt(&attempt_name, None, Some(LoginFailureReason::SsoRejected), &client, tenant).await;
+            return Err(rejected(error.log_message(), error.user_message()));
+        }
+    };
+
+    let user = match Users::from_oidc_login(&provider, &claims, tenant).await {
+        Ok(user) => user,
+        Err(error) => {
+            LoginAttempt::log_attempt(&attempt_name, None, Some(LoginFailureReason::SsoRejected), &client, tenant).await;
+            return Err(rejected(error.log_message(), error.user_message()));
+        }
+    };
+
+    let device_info = Some(format!("Single sign-on via {} at {} for tenant: {}", provider.slug, Utc::now().to_rfc3339(), tenant));
+
+    let token_pair = match user.start_session(tenant, false, device_info).await {
+        Ok(LoginStep::Complete(pair)) => pair,
+        Ok(LoginStep::TwoFactorPending { token, enrolment_required, .. }) => {
+            LoginAttempt::log_attempt(&user.username, Some(user.id), None, &client, tenant).await;
+            cata_log!(
+                Info,
+                format!(
+                    "User {} signed in via {}, two-factor {} pending (tenant: {})",
+                    user.username,
+                    provider.slug,
+                    if enrolment_required { "enrolment" } else { "verification" },
+                    tenant
+                )
+            );
+            cookies.add(Cookie::build(Cookie::new("two_factor_token", token)).http_only(true).secure(true).same_site(SameSite::Lax).build());
+
+            let message = if enrolment_required {
+                "Your administrator requires two-factor authentication. Please set it up to continue."
+            } else {
+                "Enter the code from your authenticator app."
+            };
+            return Ok(Flash::success(Redirect::to(format!("/{}/auth/two_factor", tenant)), message));
+        }
+        Err(error) => return Err(login_redirect(tenant, error.user_message())),
+    };
+
+    set_auth_cookies(cookies, token_pair, user.id);
+    LoginAttempt::log_attempt(&user.username, Some(user.id), None, &client, tenant).await;
+
+    cata_log!(Info, format!("User {} logged in via {} (tenant: {})", user.username, provider.slug, tenant));
+
+    Ok(Flash::success(Redirect::to(dashboard_uri(&user.role, tenant).await), "Successfully logged in."))
+}
+
+pub fn oidc_routes() -> Vec<Route> {
+    routes![get_oidc_login, get_oidc_callback]
+}
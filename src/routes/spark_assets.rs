@@ -0,0 +1,19 @@
+use std::path::PathBuf;
+
+use rocket::{get, http::ContentType, routes, Route};
+
+use crate::services::sparks::spark_public_file;
+
+#[get("/public/sparks/<spark>/<path..>")]
+pub fn get_spark_asset(spark: &str, path: PathBuf) -> Option<(ContentType, Vec<u8>)> {
+    let asset_path = path.iter().map(|segment| segment.to_string_lossy()).collect::<Vec<_>>().join("/");
+    let content = spark_public_file(spark, &asset_path)?;
+
+    let content_type = path.extension().and_then(|ext| ext.to_str()).and_then(ContentType::from_extension).unwrap_or(ContentType::Binary);
+
+    Some((content_type, content.into_owned()))
+}
+
+pub fn spark_asset_routes() -> Vec<Route> {
+    routes![get_spark_asset]
+}
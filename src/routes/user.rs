@@ -1,7 +1,22 @@
-use rocket::{get, routes, Route};
+use rocket::{
+    form::Form,
+    get,
+    http::CookieJar,
+    post,
+    response::{Flash, Redirect},
+    routes, uri, Route,
+};
 use rocket_dyn_templates::Template;
 
-use crate::{meltdown::*, middleware::*, vessel::structs::Vessel};
+use crate::{
+    cata_log,
+    meltdown::*,
+    middleware::*,
+    routes::home::set_auth_cookies,
+    services::default::{generate_token_pair, token_registry, totp_service},
+    structs::*,
+    vessel::structs::Vessel,
+};
 
 #[get("/<tenant>/user/dashboard")]
 pub async fn get_user_dashboard(tenant: &str, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
@@ -22,6 +37,253 @@ pub async fn get_user_dashboard(tenant: &str, app_context: AppContext<'_>) -> Re
     Ok(app_context.render_with("user/index", tenant_data))
 }
 
+#[get("/<tenant>/user/security")]
+pub async fn get_user_security(tenant: &str, jwt: JWT, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
+    let user_id = jwt_to_id(&jwt)?;
+
+    let user = Users::get_user_by_id(user_id, tenant).await?;
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String("Security".to_string()));
+    context_data.insert("email".to_string(), serde_json::json!(user.email));
+    context_data.insert("email_verified".to_string(), serde_json::Value::Bool(user.is_email_verified()));
+
+    if UserTwoFactor::is_enabled(user_id, tenant).await? {
+        let policy_enforced = jwt.is_admin().await && TenantSettings::get_bool(REQUIRE_ADMIN_TWO_FACTOR, tenant).await?;
+        context_data.insert("two_factor_enabled".to_string(), serde_json::Value::Bool(true));
+        context_data.insert("policy_enforced".to_string(), serde_json::Value::Bool(policy_enforced));
+    } else {
+        let enrolment = UserTwoFactor::begin_enrolment(user_id, tenant).await?;
+        let provisioning_uri = enrolment.provisioning_uri(jwt.get_username(), tenant)?;
+
+        context_data.insert("two_factor_enabled".to_string(), serde_json::Value::Bool(false));
+        context_data.insert("qr_svg".to_string(), serde_json::Value::String(totp_service::totp_qr_svg(&provisioning_uri)?));
+        context_data.insert("secret".to_string(), serde_json::Value::String(enrolment.secret));
+    }
+
+    Ok(app_context.render_with("user/security", TenantData::new(tenant, context_data)))
+}
+
+#[post("/<tenant>/user/security/two_factor/enable", data = "<enable_form>")]
+pub async fn post_enable_two_factor(tenant: &str, jwt: JWT, enable_form: Form<TwoFactorCodeForm>, app_context: AppContext<'_>) -> Result<Template, Flash<Redirect>> {
+    let form = enable_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Err(Flash::error(Redirect::to(uri!(get_user_security(tenant))), csrf_error.user_message()));
+    }
+
+    if let Err(error) = jwt.refuse_impersonation("two-factor enrolment") {
+        return Err(Flash::error(Redirect::to(uri!(get_user_security(tenant))), error.user_message()));
+    }
+
+    match UserTwoFactor::confirm_enrolment(jwt.user_id(), &form.code, tenant).await {
+        Ok(recovery_codes) => {
+            cata_log!(Info, format!("User {} enabled two-factor authentication (tenant: {})", jwt.get_username(), tenant));
+
+            let mut context_data = serde_json::Map::new();
+            context_data.insert("title".to_string(), serde_json::Value::String("Recovery codes".to_string()));
+            context_data.insert("recovery_codes".to_string(), serde_json::json!(recovery_codes));
+            context_data.insert("continue_url".to_string(), serde_json::Value::String(uri!(get_user_security(tenant)).to_string()));
+            Ok(app_context.render_with("auth/recovery_codes", TenantData::new(tenant, context_data)))
+        }
+        Err(error) => {
+            cata_log!(Warning, format!("Two-factor enrolment failed for user {} (tenant: {}): {}", jwt.get_username(), tenant, error.log_message()));
+            Err(Flash::error(Redirect::to(uri!(get_user_security(tenant))), error.user_message()))
+        }
+    }
+}
+
+#[post("/<tenant>/user/security/two_factor/disable", data = "<disable_form>")]
+pub async fn post_disable_two_factor(tenant: &str, jwt: JWT, disable_form: Form<TwoFactorDisableForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = disable_form.into_inner();
+    let security_uri = uri!(get_user_security(tenant));
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to(security_uri), csrf_error.user_message());
+    }
+
+    if let Err(error) = jwt.refuse_impersonation("two-factor removal") {
+        return Flash::error(Redirect::to(security_uri), error.user_message());
+    }
+
+    if jwt.is_admin().await && TenantSettings::get_bool(REQUIRE_ADMIN_TWO_FACTOR, tenant).await.unwrap_or(true) {
+        return Flash::error(Redirect::to(security_uri), "Two-factor authentication is required for administrators of this tenant.");
+    }
+
+    let user = match Users::get_user_by_id(jwt.user_id(), tenant).await {
+        Ok(user) => user,
+        Err(error) => return Flash::error(Redirect::to(security_uri), error.user_message()),
+    };
+
+    if !matches!(user.verify_password(form.password.clone()).await, Ok(true)) {
+        return Flash::error(Redirect::to(security_uri), MeltDown::invalid_credentials().user_message());
+    }
+
+    if !matches!(UserTwoFactor::verify(user.id, &form.code, tenant).await, Ok(true)) {
+        return Flash::error(Redirect::to(security_uri), "Invalid verification code.");
+    }
+
+    match UserTwoFactor::disable(user.id, tenant).await {
+        Ok(()) => {
+            cata_log!(Info, format!("User {} disabled two-factor authentication (tenant: {})", user.username, tenant));
+            Flash::success(Redirect::to(security_uri), "Two-factor authentication disabled.")
+        }
+        Err(error) => Flash::error(Redirect::to(security_uri), error.user_message()),
+    }
+}
+
+#[post("/<tenant>/user/security/verify_email", data = "<resend_form>")]
+pub async fn post_resend_verification(tenant: &str, jwt: JWT, resend_form: Form<ResendVerificationForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let security_uri = uri!(get_user_security(tenant));
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &resend_form.authenticity_token) {
+        return Flash::error(Redirect::to(security_uri), csrf_error.user_message());
+    }
+
+    let user = match Users::get_user_by_id(jwt.user_id(), tenant).await {
+        Ok(user) => user,
+        Err(error) => return Flash::error(Redirect::to(security_uri), error.user_message()),
+    };
+
+    if user.is_email_verified() {
+        return Flash::success(Redirect::to(security_uri), "Your email address is already verified.");
+    }
+
+    match user.send_email_verification(tenant).await {
+        Ok(()) => Flash::success(Redirect::to(security_uri), "Verification email sent."),
+        Err(error) => {
+            cata_log!(Warning, format!("Failed to resend verification email for user {} (tenant: {}): {}", user.id, tenant, error.log_message()));
+            Flash::error(Redirect::to(security_uri), error.user_message())
+        }
+    }
+}
+
+#[get("/<tenant>/user/profile")]
+pub async fn get_user_profile(tenant: &str, jwt: JWT, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
+    let user = UserListItem::from(Users::get_user_by_id(jwt.user_id(), tenant).await?);
+
+    let mut context_data = serde_json::Map::new();
+    context_data.insert("title".to_string(), serde_json::Value::String("Profile".to_string()));
+    context_data.insert("profile".to_string(), serde_json::to_value(&user).map_err(|e| MeltDown::new(MeltType::SerializationFailed, e.to_string()))?);
+
+    Ok(app_context.render_with("user/profile", TenantData::new(tenant, context_data)))
+}
+
+#[post("/<tenant>/user/profile", data = "<profile_form>")]
+pub async fn post_user_profile(tenant: &str, jwt: JWT, profile_form: Form<ProfileForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = profile_form.into_inner();
+    let profile_uri = uri!(get_user_profile(tenant));
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to(profile_uri), csrf_error.user_message());
+    }
+
+    if form.first_name.trim().is_empty() || form.last_name.trim().is_empty() {
+        return Flash::error(Redirect::to(profile_uri), "First and last name are required.");
+    }
+
+    let mut user = match Users::get_user_by_id(jwt.user_id(), tenant).await {
+        Ok(user) => user,
+        Err(error) => return Flash::error(Redirect::to(profile_uri), error.user_message()),
+    };
+
+    let email = Some(form.email.trim()).filter(|email| !email.is_empty());
+    let email_changed = user.email.as_deref() != email;
+
+    if email_changed {
+        if let Err(error) = jwt.refuse_impersonation("email change") {
+            return Flash::error(Redirect::to(profile_uri), error.user_message());
+        }
+    }
+
+    if let Err(error) = user.update_profile(form.first_name.trim(), form.last_name.trim(), email, tenant).await {
+        return Flash::error(Redirect::to(profile_uri), error.user_message());
+    }
+
+    cata_log!(Info, format!("User {} updated their profile (tenant: {})", user.username, tenant));
+
+    if email_changed && user.email.is_some() {
+        if let Err(error) = user.send_email_verification(tenant).await {
+            cata_log!(Warning, format!("Failed to send verification email for user {} (tenant: {}): {}", user.id, tenant, error.log_message()));
+            return Flash::success(Redirect::to(profile_uri), "Profile updated, but the verification email could not be sent.");
+        }
+        return Flash::success(Redirect::to(profile_uri), "Profile updated. Please check your inbox to verify your new email address.");
+    }
+
+    Flash::success(Redirect::to(profile_uri), "Profile updated.")
+}
+
+#[post("/<tenant>/user/profile/password", data = "<password_form>")]
+pub async fn post_user_profile_password(tenant: &str, jwt: JWT, password_form: Form<ProfilePasswordForm>, cookies: &CookieJar<'_>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = password_form.into_inner();
+    let profile_uri = uri!(get_user_profile(tenant));
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to(profile_uri), csrf_error.user_message());
+    }
+
+    if let Err(error) = jwt.refuse_impersonation("password change") {
+        return Flash::error(Redirect::to(profile_uri), error.user_message());
+    }
+
+    if form.password.is_empty() || form.password != form.confirm_password {
+        return Flash::error(Redirect::to(profile_uri), "Passwords do not match.");
+    }
+
+    let user = match Users::get_user_by_id(jwt.user_id(), tenant).await {
+        Ok(user) => user,
+        Err(error) => return Flash::error(Redirect::to(profile_uri), error.user_message()),
+    };
+
+    if !matches!(user.verify_password(form.current_password.clone()).await, Ok(true)) {
+        cata_log!(Warning, format!("User {} entered a wrong current password while changing password (tenant: {})", user.username, tenant));
+        return Flash::error(Redirect::to(profile_uri), "Your current password is incorrect.");
+    }
+
+    if form.password == form.current_password {
+        return Flash::error(Redirect::to(profile_uri), "Please choose a password different from your current one.");
+    }
+
+    if let Err(error) = Users::change_password_by_id(user.id, &form.password, tenant).await {
+        return Flash::error(Redirect::to(profile_uri), error.user_message());
+    }
+
+    token_registry::invalidate_user_tokens(tenant, user.id);
+
+    match generate_token_pair(&user, tenant, jwt.0.remember, jwt.get_device_info().cloned()) {
+        Ok(token_pair) => set_auth_cookies(cookies, token_pair, user.id),
+        Err(error) => {
+            cata_log!(Error, format!("Failed to reissue tokens after password change (tenant: {}): {}", tenant, error.log_message()));
+            return Flash::error(Redirect::to(format!("/{}/auth/login", tenant)), "Password changed. Please log in again.");
+        }
+    }
+
+    cata_log!(Info, format!("User {} changed their password from the profile page (tenant: {})", user.username, tenant));
+    Flash::success(Redirect::to(profile_uri), "Your password has been changed. Other sessions have been signed out.")
+}
+
+#[post("/<tenant>/user/profile/logout_everywhere", data = "<logout_form>")]
+pub async fn post_logout_everywhere(tenant: &str, jwt: JWT, logout_form: Form<LogoutEverywhereForm>, app_context: AppContext<'_>) -> Result<Redirect, Flash<Redirect>> {
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &logout_form.authenticity_token) {
+        return Err(Flash::error(Redirect::to(uri!(get_user_profile(tenant))), csrf_error.user_message()));
+    }
+
+    token_registry::invalidate_user_tokens(tenant, jwt.user_id());
+    cata_log!(Info, format!("User {} signed out of all sessions (tenant: {})", jwt.get_username(), tenant));
+
+    Ok(Redirect::to(format!("/{}/auth/logout", tenant)))
+}
+
 pub fn user_routes() -> Vec<Route> {
-    routes![get_user_dashboard]
+    routes![
+        get_user_dashboard,
+        get_user_security,
+        post_enable_two_factor,
+        post_disable_two_factor,
+        post_resend_verification,
+        get_user_profile,
+        post_user_profile,
+        post_user_profile_password,
+        post_logout_everywhere
+    ]
 }
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, env, thread};
+use std::env;
 
 use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header as JWTHeader, Validation};
@@ -14,18 +14,61 @@ use crate::{
     vessel::structs::Vessel,
 };
 
-thread_local! {
-    pub static CURRENT_TENANT: RefCell<Option<String>> = RefCell::new(None);
+const TWO_FACTOR_TOKEN_EXPIRY_MINS: i64 = 5;
+
+pub const VESSEL_ROLE: &str = "vessel";
+pub const IMPERSONATOR_ACCESS_COOKIE: &str = "impersonator_access_token";
+pub const IMPERSONATOR_REFRESH_COOKIE: &str = "impersonator_refresh_token";
+
+pub trait TokenSubject {
+    fn subject_id(&self) -> i32;
+    fn subject_username(&self) -> &str;
+    fn subject_role(&self) -> &str;
+    fn auth_system(&self) -> AuthSystem;
+
+    fn password_change_required(&self) -> bool {
+        false
+    }
 }
 
-pub fn set_current_tenant(tenant: &str) {
-    CURRENT_TENANT.with(|current| {
-        *current.borrow_mut() = Some(tenant.to_string());
-    });
+impl TokenSubject for Users {
+    fn subject_id(&self) -> i32 {
+        self.id
+    }
+
+    fn subject_username(&self) -> &str {
+        &self.username
+    }
+
+    fn subject_role(&self) -> &str {
+        &self.role
+    }
+
+    fn auth_system(&self) -> AuthSystem {
+        AuthSystem::Tenant
+    }
+
+    fn password_change_required(&self) -> bool {
+        self.should_change_password
+    }
 }
 
-pub fn get_current_tenant() -> Option<String> {
-    CURRENT_TENANT.with(|current| current.borrow().clone())
+impl TokenSubject for Vessel {
+    fn subject_id(&self) -> i32 {
+        self.id
+    }
+
+    fn subject_username(&self) -> &str {
+        &self.username
+    }
+
+    fn subject_role(&self) -> &str {
+        VESSEL_ROLE
+    }
+
+    fn auth_system(&self) -> AuthSystem {
+        AuthSystem::Vessel
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -36,6 +79,11 @@ pub struct TokenPair {
     pub refresh_claims: Claims,
 }
 
+pub enum LoginStep {
+    Complete(TokenPair),
+    TwoFactorPending { token: String, claims: Claims, enrolment_required: bool },
+}
+
 #[derive(Debug)]
 pub struct RefreshTokenInfo {
     pub jti: String,
@@ -43,6 +91,8 @@ pub struct RefreshTokenInfo {
     pub token_version: u32,
     pub remember: bool,
     pub device_info: Option<String>,
+    pub tenant_name: String,
+    pub auth_system: AuthSystem,
 }
 
 pub fn get_jwt_settings() -> JwtSettings {
@@ -55,93 +105,42 @@ pub fn get_jwt_settings() -> JwtSettings {
     }
 }
 
-pub fn generate_access_token(user: &Users, refresh_jti: Option<String>, device_info: Option<String>) -> Result<(String, Claims), MeltDown> {
-    let jwt_settings = get_jwt_settings();
-
-    let expiry_duration = Duration::minutes(jwt_settings.access_token_expiry_mins as i64);
+pub fn jwt_secret() -> String {
+    env::var("JWT_SECRET").unwrap_or_else(|_| "your-256-bit-secret".to_string())
+}
 
+fn build_claims<S: TokenSubject>(subject: &S, tenant_name: &str, token_type: TokenType, expiry_duration: Duration, remember: bool, refresh_jti: Option<String>, device_info: Option<String>) -> Claims {
     let now = Utc::now();
     let expiration = now.checked_add_signed(expiry_duration).unwrap_or(now).timestamp() as usize;
     let issued_at = now.timestamp() as usize;
 
-    let jti = Uuid::new_v4().to_string();
+    let user_id = subject.subject_id();
+    let token_version = token_registry::get_token_version(tenant_name, user_id);
 
-    let tenant_name = get_current_tenant().unwrap_or_else(|| {
-        cata_log!(Warning, "No tenant name set in current context, this might lead to authentication issues");
-        "unknown_tenant".to_string()
-    });
-
-    let token_version = token_registry::get_token_version(&tenant_name, user.id);
-
-    token_registry::register_user(&tenant_name, user.id);
+    token_registry::register_user(tenant_name, user_id);
 
-    let claims = Claims {
-        sub: user.id.to_string(),
-        username: user.username.clone(),
-        role: user.role.clone(),
+    Claims {
+        sub: user_id.to_string(),
+        username: subject.subject_username().to_string(),
+        role: subject.subject_role().to_string(),
         exp: expiration,
         iat: issued_at,
         nbf: issued_at,
-        jti,
-        token_type: TokenType::Access,
+        jti: Uuid::new_v4().to_string(),
+        token_type,
         ver: token_version,
-        remember: false,
+        remember,
         refresh_jti,
         device_info,
-        tenant_name: Some(tenant_name),
-        auth_system: AuthSystem::Tenant,
-    };
-
-    let secret = env::var("JWT_SECRET").unwrap_or_else(|_| "your-256-bit-secret".to_string());
-
-    match encode(&JWTHeader::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())) {
-        Ok(token) => Ok((token, claims)),
-        Err(e) => {
-            let error_message = format!("Error encoding JWT: {}", e);
-            cata_log!(Error, &error_message);
-            Err(MeltDown::new(MeltType::Unknown, "Failed to generate JWT token").with_context("error", &error_message))
-        }
+        tenant_name: Some(tenant_name.to_string()),
+        auth_system: subject.auth_system(),
+        password_change_required: subject.password_change_required(),
+        impersonated_by: None,
     }
 }
 
-pub fn generate_access_token_for_vessel(vessel: &Vessel, refresh_jti: Option<String>, device_info: Option<String>) -> Result<(String, Claims), MeltDown> {
-    let jwt_settings = get_jwt_settings();
-
-    let expiry_duration = Duration::minutes(jwt_settings.access_token_expiry_mins as i64);
-
-    let now = Utc::now();
-    let expiration = now.checked_add_signed(expiry_duration).unwrap_or(now).timestamp() as usize;
-    let issued_at = now.timestamp() as usize;
-
-    let jti = Uuid::new_v4().to_string();
-
-    let tenant_name = vessel.name.clone();
-    let user_id = vessel.id;
-
-    let token_version = token_registry::get_token_version(&tenant_name, user_id);
-
-    token_registry::register_user(&tenant_name, user_id);
-
-    let claims = Claims {
-        sub: vessel.id.to_string(),
-        username: vessel.username.clone(),
-        role: "vessel".to_string(),
-        exp: expiration,
-        iat: issued_at,
-        nbf: issued_at,
-        jti,
-        token_type: TokenType::Access,
-        ver: token_version,
-        remember: false,
-        refresh_jti,
-        device_info,
-        tenant_name: Some(tenant_name),
-        auth_system: AuthSystem::Vessel,
-    };
-
-    let secret = env::var("JWT_SECRET").unwrap_or_else(|_| "your-256-bit-secret".to_string());
-
-    match encode(&JWTHeader::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())) {
+fn sign_claims(claims: Claims) -> Result<(String, Claims), MeltDown> {
+    match encode(&JWTHeader::default(), &claims, &EncodingKey::from_secret(jwt_secret().as_bytes())) {
         Ok(token) => Ok((token, claims)),
         Err(e) => {
             let error_message = format!("Error encoding JWT: {}", e);
@@ -151,60 +150,26 @@ pub fn generate_access_token_for_vessel(vessel: &Vessel, refresh_jti: Option<Str
     }
 }
 
-pub fn generate_refresh_token(user: &Users, remember: bool, device_info: Option<String>) -> Result<(String, Claims), MeltDown> {
-    let jwt_settings = get_jwt_settings();
-
-    let expiry_duration = if remember {
-        Duration::days(jwt_settings.refresh_token_expiry_days_remember as i64)
-    } else {
-        Duration::days(jwt_settings.refresh_token_expiry_days as i64)
-    };
-
-    let now = Utc::now();
-    let expiration = now.checked_add_signed(expiry_duration).unwrap_or(now).timestamp() as usize;
-    let issued_at = now.timestamp() as usize;
-
-    let jti = Uuid::new_v4().to_string();
-
-    let tenant_name = get_current_tenant().unwrap_or_else(|| {
-        cata_log!(Warning, "No tenant name set in current context, this might lead to authentication issues");
-        "unknown_tenant".to_string()
-    });
-
-    let token_version = token_registry::get_token_version(&tenant_name, user.id);
-
-    token_registry::register_user(&tenant_name, user.id);
-
-    let claims = Claims {
-        sub: user.id.to_string(),
-        username: user.username.clone(),
-        role: user.role.clone(),
-        exp: expiration,
-        iat: issued_at,
-        nbf: issued_at,
-        jti,
-        token_type: TokenType::Refresh,
-        ver: token_version,
-        remember,
-        refresh_jti: None,
-        device_info,
-        tenant_name: Some(tenant_name),
-        auth_system: AuthSystem::Tenant,
-    };
+fn issue_token<S: TokenSubject>(
+    subject: &S,
+    tenant_name: &str,
+    token_type: TokenType,
+    expiry_duration: Duration,
+    remember: bool,
+    refresh_jti: Option<String>,
+    device_info: Option<String>,
+) -> Result<(String, Claims), MeltDown> {
+    sign_claims(build_claims(subject, tenant_name, token_type, expiry_duration, remember, refresh_jti, device_info))
+}
 
-    let secret = env::var("JWT_SECRET").unwrap_or_else(|_| "your-256-bit-secret".to_string());
+pub fn generate_access_token<S: TokenSubject>(subject: &S, tenant_name: &str, refresh_jti: Option<String>, device_info: Option<String>) -> Result<(String, Claims), MeltDown> {
+    let jwt_settings = get_jwt_settings();
+    let expiry_duration = Duration::minutes(jwt_settings.access_token_expiry_mins as i64);
 
-    match encode(&JWTHeader::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())) {
-        Ok(token) => Ok((token, claims)),
-        Err(e) => {
-            let error_message = format!("Error encoding JWT: {}", e);
-            cata_log!(Error, &error_message);
-            Err(MeltDown::new(MeltType::Unknown, "Failed to generate JWT token").with_context("error", &error_message))
-        }
-    }
+    issue_token(subject, tenant_name, TokenType::Access, expiry_duration, false, refresh_jti, device_info)
 }
 
-pub fn generate_refresh_token_for_vessel(vessel: &Vessel, remember: bool, device_info: Option<String>) -> Result<(String, Claims), MeltDown> {
+pub fn generate_refresh_token<S: TokenSubject>(subject: &S, tenant_name: &str, remember: bool, device_info: Option<String>) -> Result<(String, Claims), MeltDown> {
     let jwt_settings = get_jwt_settings();
 
     let expiry_duration = if remember {
@@ -213,51 +178,12 @@ pub fn generate_refresh_token_for_vessel(vessel: &Vessel, remember: bool, device
         Duration::days(jwt_settings.refresh_token_expiry_days as i64)
     };
 
-    let now = Utc::now();
-    let expiration = now.checked_add_signed(expiry_duration).unwrap_or(now).timestamp() as usize;
-    let issued_at = now.timestamp() as usize;
-
-    let jti = Uuid::new_v4().to_string();
-
-    let tenant_name = vessel.name.clone();
-    let user_id = vessel.id;
-
-    let token_version = token_registry::get_token_version(&tenant_name, user_id);
-
-    token_registry::register_user(&tenant_name, user_id);
-
-    let claims = Claims {
-        sub: vessel.id.to_string(),
-        username: vessel.username.clone(),
-        role: "vessel".to_string(),
-        exp: expiration,
-        iat: issued_at,
-        nbf: issued_at,
-        jti,
-        token_type: TokenType::Refresh,
-        ver: token_version,
-        remember,
-        refresh_jti: None,
-        device_info,
-        tenant_name: Some(tenant_name),
-        auth_system: AuthSystem::Vessel,
-    };
-
-    let secret = env::var("JWT_SECRET").unwrap_or_else(|_| "your-256-bit-secret".to_string());
-
-    match encode(&JWTHeader::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())) {
-        Ok(token) => Ok((token, claims)),
-        Err(e) => {
-            let error_message = format!("Error encoding JWT: {}", e);
-            cata_log!(Error, &error_message);
-            Err(MeltDown::new(MeltType::Unknown, "Failed to generate JWT token").with_context("error", &error_message))
-        }
-    }
+    issue_token(subject, tenant_name, TokenType::Refresh, expiry_duration, remember, None, device_info)
 }
 
-pub fn generate_token_pair(user: &Users, remember: bool, device_info: Option<String>) -> Result<TokenPair, MeltDown> {
-    let (refresh_token, refresh_claims) = generate_refresh_token(user, remember, device_info.clone())?;
-    let (access_token, access_claims) = generate_access_token(user, Some(refresh_claims.jti.clone()), device_info)?;
+pub fn generate_token_pair<S: TokenSubject>(subject: &S, tenant_name: &str, remember: bool, device_info: Option<String>) -> Result<TokenPair, MeltDown> {
+    let (refresh_token, refresh_claims) = generate_refresh_token(subject, tenant_name, remember, device_info.clone())?;
+    let (access_token, access_claims) = generate_access_token(subject, tenant_name, Some(refresh_claims.jti.clone()), device_info)?;
 
     Ok(TokenPair {
         access_token,
@@ -267,20 +193,35 @@ pub fn generate_token_pair(user: &Users, remember: bool, device_info: Option<Str
     })
 }
 
-pub fn generate_token_pair_for_vessel(vessel: &Vessel, remember: bool, device_info: Option<String>) -> Result<TokenPair, MeltDown> {
-    let (refresh_token, refresh_claims) = generate_refresh_token_for_vessel(vessel, remember, device_info.clone())?;
-    let (access_token, access_claims) = generate_access_token_for_vessel(vessel, Some(refresh_claims.jti.clone()), device_info)?;
+pub fn generate_impersonation_token<S: TokenSubject>(subject: &S, tenant_name: &str, impersonator: &str, device_info: Option<String>) -> Result<(String, Claims), MeltDown> {
+    let expiry_duration = Duration::minutes(get_jwt_settings().impersonation_token_expiry_mins as i64);
 
-    Ok(TokenPair {
-        access_token,
-        refresh_token,
-        access_claims,
-        refresh_claims,
-    })
+    let mut claims = build_claims(subject, tenant_name, TokenType::Access, expiry_duration, false, None, device_info);
+    claims.impersonated_by = Some(impersonator.to_string());
+
+    sign_claims(claims)
+}
+
+pub fn generate_two_factor_token<S: TokenSubject>(subject: &S, tenant_name: &str, remember: bool, device_info: Option<String>) -> Result<(String, Claims), MeltDown> {
+    let expiry_duration = Duration::minutes(TWO_FACTOR_TOKEN_EXPIRY_MINS);
+
+    issue_token(subject, tenant_name, TokenType::TwoFactor, expiry_duration, remember, None, device_info)
+}
+
+pub fn validate_two_factor_token(token: &str) -> Result<Claims, MeltDown> {
+    let claims = validate_token(token)?;
+
+    if claims.token_type != TokenType::TwoFactor {
+        return Err(MeltDown::new(MeltType::Unauthorized, "Invalid token type"));
+    }
+
+    claims.sub.parse::<i32>().map_err(|_| MeltDown::new(MeltType::ValidationFailed, "Invalid user ID in token"))?;
+
+    Ok(claims)
 }
 
 pub fn validate_token(token: &str) -> Result<Claims, MeltDown> {
-    let secret = env::var("JWT_SECRET").unwrap_or_else(|_| "your-256-bit-secret".to_string());
+    let secret = jwt_secret();
 
     let mut validation = Validation::default();
     validation.validate_exp = true;
@@ -344,5 +285,7 @@ pub fn validate_refresh_token(token: &str) -> Result<RefreshTokenInfo, MeltDown>
         token_version: claims.ver,
         remember: claims.remember,
         device_info: claims.device_info,
+        tenant_name,
+        auth_system: claims.auth_system,
     })
 }
@@ -0,0 +1,85 @@
+use crate::{
+    bootstrap::{LockoutSettings, APP_CONFIG},
+    meltdown::*,
+};
+
+const MAX_ESCALATION_STEPS: u32 = 16;
+
+pub fn lockout_settings() -> LockoutSettings {
+    APP_CONFIG.get().map(|config| config.security.lockout.clone()).unwrap_or_default()
+}
+
+pub fn failure_window_start(settings: &LockoutSettings, last_success: Option<i64>, now: i64) -> i64 {
+    let window_start = now - settings.window_secs;
+    last_success.map_or(window_start, |last_success| window_start.max(last_success))
+}
+
+pub fn lockout_remaining_with(settings: &LockoutSettings, failures: &[i64], now: i64) -> Option<i64> {
+    let count = failures.len() as u32;
+    if settings.max_failures == 0 || count < settings.max_failures {
+        return None;
+    }
+
+    let latest_failure = failures.iter().copied().max()?;
+    let escalation = (count - settings.max_failures).min(MAX_ESCALATION_STEPS);
+    let duration = settings.base_lockout_secs.saturating_mul(1_i64 << escalation).min(settings.max_lockout_secs);
+
+    let remaining = latest_failure + duration - now;
+    (remaining > 0).then_some(remaining)
+}
+
+pub fn account_locked(remaining_secs: i64) -> MeltDown {
+    let minutes = (remaining_secs + 59) / 60;
+
+    MeltDown::new(MeltType::AccountLocked, format!("Account locked for another {}s", remaining_secs))
+        .with_context("retry_after", remaining_secs.to_string())
+        .with_user_message(format!("Too many failed login attempts. Please try again in {} minute{}.", minutes, if minutes == 1 { "" } else { "s" }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> LockoutSettings {
+        LockoutSettings {
+            max_failures: 3,
+            window_secs: 900,
+            base_lockout_secs: 60,
+            max_lockout_secs: 600,
+        }
+    }
+
+    #[test]
+    fn no_lockout_below_threshold() {
+        assert_eq!(lockout_remaining_with(&settings(), &[100, 110], 120), None);
+    }
+
+    #[test]
+    fn locks_for_base_duration_at_threshold() {
+        assert_eq!(lockout_remaining_with(&settings(), &[100, 110, 120], 130), Some(50));
+    }
+
+    #[test]
+    fn escalates_and_caps_lockout() {
+        assert_eq!(lockout_remaining_with(&settings(), &[100, 110, 120, 130], 130), Some(120));
+        assert_eq!(lockout_remaining_with(&settings(), &[100, 110, 120, 130, 140, 150, 160], 160), Some(600));
+    }
+
+    #[test]
+    fn lockout_expires() {
+        assert_eq!(lockout_remaining_with(&settings(), &[100, 110, 120], 180), None);
+    }
+
+    #[test]
+    fn zero_max_failures_disables_lockout() {
+        let settings = LockoutSettings { max_failures: 0, ..settings() };
+        assert_eq!(lockout_remaining_with(&settings, &[100; 20], 100), None);
+    }
+
+    #[test]
+    fn window_starts_after_last_success() {
+        assert_eq!(failure_window_start(&settings(), None, 1000), 100);
+        assert_eq!(failure_window_start(&settings(), Some(500), 1000), 500);
+        assert_eq!(failure_window_start(&settings(), Some(50), 1000), 100);
+    }
+}
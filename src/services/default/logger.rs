@@ -3,12 +3,17 @@ use std::{
     io::Write,
     panic::{self, PanicHookInfo},
     path::Path,
-    process, thread,
+    process,
+    sync::RwLock,
+    thread,
     time::{SystemTime, UNIX_EPOCH},
 };
 
 use backtrace::Backtrace;
 use chrono::Local;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use rocket::form::{self, DataField, FromFormField, ValueField};
 
 #[derive(Clone, Copy)]
 enum TimestampPrecision {
@@ -49,6 +54,114 @@ impl LogLevel {
     }
 }
 
+const REDACTED: &str = "***";
+
+const DEFAULT_REDACT_PATTERNS: &[&str] = &[
+    r#"(?i)(?:password|passwd|pwd|secret|api_key|authenticity_token|access_token|refresh_token|two_factor_token)\b["']?\s*[:=]\s*["']?(?P<secret>[^\s"',;&|)\]}]+)"#,
+    r"(?i)\bbearer\s+(?P<secret>[A-Za-z0-9._~+/=-]+)",
+    r"\beyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]*",
+    r"\$2[abxy]?\$\d{2}\$[./A-Za-z0-9]{53}",
+    r"\$argon2(?:id|i|d)\$\S+",
+];
+
+static REDACT_PATTERNS: Lazy<RwLock<Vec<Regex>>> = Lazy::new(|| RwLock::new(compile_redact_patterns(DEFAULT_REDACT_PATTERNS.iter().copied())));
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Secret(value)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Secret(value)
+    }
+}
+
+impl<T> std::fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> std::fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret({})", REDACTED)
+    }
+}
+
+#[rocket::async_trait]
+impl<'v, T: FromFormField<'v>> FromFormField<'v> for Secret<T> {
+    fn from_value(field: ValueField<'v>) -> form::Result<'v, Self> {
+        T::from_value(field).map(Secret)
+    }
+
+    async fn from_data(field: DataField<'v, '_>) -> form::Result<'v, Self> {
+        T::from_data(field).await.map(Secret)
+    }
+
+    fn default() -> Option<Self> {
+        T::default().map(Secret)
+    }
+}
+
+fn compile_redact_patterns<'a>(patterns: impl Iterator<Item = &'a str>) -> Vec<Regex> {
+    patterns
+        .filter_map(|pattern| match Regex::new(pattern) {
+            Ok(regex) => Some(regex),
+            Err(e) => {
+                eprintln!("Ignoring invalid log redaction pattern '{}': {}", pattern, e);
+                None
+            }
+        })
+        .collect()
+}
+
+pub fn configure_redaction(extra_patterns: &[String]) {
+    let patterns = compile_redact_patterns(DEFAULT_REDACT_PATTERNS.iter().copied().chain(extra_patterns.iter().map(String::as_str)));
+
+    if let Ok(mut current) = REDACT_PATTERNS.write() {
+        *current = patterns;
+    }
+}
+
+pub fn redact(message: &str) -> String {
+    let patterns = match REDACT_PATTERNS.read() {
+        Ok(patterns) => patterns,
+        Err(_) => return message.to_string(),
+    };
+
+    let mut redacted = message.to_string();
+    for pattern in patterns.iter() {
+        if !pattern.is_match(&redacted) {
+            continue;
+        }
+
+        redacted = pattern
+            .replace_all(&redacted, |caps: &Captures| match (caps.get(0), caps.name("secret")) {
+                (Some(whole), Some(secret)) => {
+                    let text = whole.as_str();
+                    format!("{}{}{}", &text[..secret.start() - whole.start()], REDACTED, &text[secret.end() - whole.start()..])
+                }
+                _ => REDACTED.to_string(),
+            })
+            .into_owned();
+    }
+
+    redacted
+}
+
 pub struct CatalystLogger;
 
 impl CatalystLogger {
@@ -80,7 +193,7 @@ impl CatalystLogger {
         let file_path = Path::new(log_dir).join(format!("{}.log", level.as_str().to_lowercase()));
 
         let timestamp = Self::get_timestamp(&level);
-        let file_log_entry = format!("{} [{}] {}\n", timestamp, level.as_str(), message);
+        let file_log_entry = format!("{} [{}] {}\n", timestamp, level.as_str(), redact(message));
 
         if let Err(e) = OpenOptions::new().append(true).create(true).open(&file_path).and_then(|mut file| file.write_all(file_log_entry.as_bytes())) {
             eprintln!("Failed to write log: {}", e);
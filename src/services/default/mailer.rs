@@ -0,0 +1,166 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+};
+
+use chrono::Utc;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    Message, SmtpTransport, Transport,
+};
+
+use crate::{
+    bootstrap::{MailBackend, MailSettings, APP_CONFIG},
+    cata_log,
+    meltdown::*,
+};
+
+#[derive(Debug, Clone)]
+pub struct OutgoingMail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+pub trait Mailer: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn send(&self, mail: &OutgoingMail) -> Result<(), MeltDown>;
+}
+
+pub struct SmtpMailer {
+    from: Mailbox,
+    transport: SmtpTransport,
+}
+
+pub struct FileDropMailer {
+    from: Mailbox,
+    dir: PathBuf,
+}
+
+static MAILER: OnceLock<Arc<dyn Mailer>> = OnceLock::new();
+
+fn parse_mailbox(address: &str) -> Result<Mailbox, MeltDown> {
+    address
+        .parse::<Mailbox>()
+        .map_err(|e| MeltDown::new(MeltType::InvalidInput, format!("Invalid email address '{}': {}", address, e)).with_user_message("Invalid email address."))
+}
+
+fn build_message(from: &Mailbox, mail: &OutgoingMail) -> Result<Message, MeltDown> {
+    Message::builder()
+        .from(from.clone())
+        .to(parse_mailbox(&mail.to)?)
+        .subject(mail.subject.clone())
+        .header(ContentType::TEXT_PLAIN)
+        .body(mail.body.clone())
+        .map_err(|e| MeltDown::new(MeltType::ExternalServiceError, format!("Failed to build email: {}", e)))
+}
+
+impl SmtpMailer {
+    pub fn from_settings(settings: &MailSettings) -> Result<Self, MeltDown> {
+        if settings.smtp_host.is_empty() {
+            return Err(MeltDown::new(MeltType::ConfigurationError, "settings.mail.smtp_host is required for the smtp mail backend"));
+        }
+
+        let builder = if settings.smtp_starttls {
+            SmtpTransport::starttls_relay(&settings.smtp_host)
+        } else {
+            SmtpTransport::relay(&settings.smtp_host)
+        }
+        .map_err(|e| MeltDown::new(MeltType::ConfigurationError, format!("Invalid SMTP relay '{}': {}", settings.smtp_host, e)))?
+        .port(settings.smtp_port);
+
+        let builder = match &settings.smtp_username {
+            Some(username) => builder.credentials(Credentials::new(username.clone(), std::env::var("SMTP_PASSWORD").unwrap_or_default())),
+            None => builder,
+        };
+
+        Ok(SmtpMailer {
+            from: parse_mailbox(&settings.from)?,
+            transport: builder.build(),
+        })
+    }
+}
+
+impl Mailer for SmtpMailer {
+    fn name(&self) -> &'static str {
+        "smtp"
+    }
+
+    fn send(&self, mail: &OutgoingMail) -> Result<(), MeltDown> {
+        let message = build_message(&self.from, mail)?;
+
+        self.transport
+            .send(&message)
+            .map(|_| ())
+            .map_err(|e| MeltDown::new(MeltType::ExternalServiceError, format!("SMTP delivery failed: {}", e)).with_context("to", mail.to.clone()))
+    }
+}
+
+impl FileDropMailer {
+    pub fn new(from: &str, dir: impl Into<PathBuf>) -> Result<Self, MeltDown> {
+        Ok(FileDropMailer {
+            from: parse_mailbox(from)?,
+            dir: dir.into(),
+        })
+    }
+
+    pub fn from_settings(settings: &MailSettings) -> Result<Self, MeltDown> {
+        Self::new(&settings.from, &settings.drop_dir)
+    }
+}
+
+impl Mailer for FileDropMailer {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn send(&self, mail: &OutgoingMail) -> Result<(), MeltDown> {
+        let message = build_message(&self.from, mail)?;
+
+        fs::create_dir_all(&self.dir).map_err(|e| MeltDown::new(MeltType::FileOperationFailed, format!("Failed to create mail drop directory: {}", e)))?;
+
+        let path = self.dir.join(format!("{}-{}.eml", Utc::now().format("%Y%m%d%H%M%S"), uuid::Uuid::new_v4()));
+
+        fs::write(&path, message.formatted()).map_err(|e| MeltDown::new(MeltType::FileOperationFailed, format!("Failed to write email: {}", e)).with_context("path", path.display().to_string()))?;
+
+        cata_log!(Debug, format!("Dropped email for {} at {}", mail.to, path.display()));
+        Ok(())
+    }
+}
+
+pub fn mail_settings() -> MailSettings {
+    APP_CONFIG.get().map(|config| config.settings.mail.clone()).unwrap_or_default()
+}
+
+fn configured_mailer() -> Result<Arc<dyn Mailer>, MeltDown> {
+    let settings = mail_settings();
+
+    Ok(match settings.backend {
+        MailBackend::Smtp => Arc::new(SmtpMailer::from_settings(&settings)?),
+        MailBackend::File => Arc::new(FileDropMailer::from_settings(&settings)?),
+    })
+}
+
+pub fn set_mailer(mailer: Arc<dyn Mailer>) -> bool {
+    MAILER.set(mailer).is_ok()
+}
+
+pub fn mailer() -> Result<Arc<dyn Mailer>, MeltDown> {
+    if let Some(mailer) = MAILER.get() {
+        return Ok(mailer.clone());
+    }
+
+    let mailer = configured_mailer()?;
+    cata_log!(Info, format!("Using '{}' mail backend", mailer.name()));
+    Ok(MAILER.get_or_init(|| mailer).clone())
+}
+
+pub async fn send_mail(mail: OutgoingMail) -> Result<(), MeltDown> {
+    let mailer = mailer()?;
+
+    tokio::task::spawn_blocking(move || mailer.send(&mail))
+        .await
+        .map_err(|e| MeltDown::new(MeltType::Unknown, format!("Task join error: {}", e)))?
+}
@@ -1,11 +1,25 @@
 //pub mod cronjobs;
 pub mod jwt_service;
+pub mod lockout_service;
 pub mod logger;
+pub mod mailer;
+pub mod oidc_service;
+pub mod password_service;
+pub mod permission_registry;
+pub mod signed_token;
 pub mod storage;
 pub mod token_registry;
+pub mod totp_service;
 
 //pub use cronjobs::*;
 pub use jwt_service::*;
+pub use lockout_service::*;
 pub use logger::*;
+pub use mailer::*;
+pub use oidc_service::*;
+pub use password_service::*;
+pub use permission_registry::*;
+pub use signed_token::*;
 pub use storage::*;
 pub use token_registry::*;
+pub use totp_service::*;
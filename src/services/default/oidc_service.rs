@@ -0,0 +1,278 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::Utc;
+use jsonwebtoken::{jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use once_cell::sync::Lazy;
+use rand::{distributions::Alphanumeric, Rng};
+use reqwest::Url;
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    bootstrap::{OidcSettings, APP_CONFIG},
+    cata_log,
+    meltdown::*,
+    services::default::mailer,
+    structs::*,
+};
+
+const STATE_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 32;
+const CODE_VERIFIER_LENGTH: usize = 64;
+
+static HTTP_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(oidc_settings().http_timeout_secs))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+});
+
+static DISCOVERY_CACHE: Lazy<RwLock<HashMap<String, (Instant, OidcDiscovery)>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+static JWKS_CACHE: Lazy<RwLock<HashMap<String, (Instant, JwkSet)>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+static PENDING_FLOWS: Lazy<Mutex<HashMap<String, OidcPendingFlow>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn oidc_settings() -> OidcSettings {
+    APP_CONFIG.get().map(|config| config.security.oidc.clone()).unwrap_or_default()
+}
+
+fn random_string(length: usize) -> String {
+    rand::thread_rng().sample_iter(&Alphanumeric).take(length).map(char::from).collect()
+}
+
+fn external_error(details: impl Into<String>, issuer: &str) -> MeltDown {
+    MeltDown::new(MeltType::ExternalServiceError, details)
+        .with_context("issuer", issuer.to_string())
+        .with_user_message("Single sign-on is currently unavailable. Please try again later.")
+}
+
+pub fn validate_issuer_url(issuer: &str) -> Result<Url, MeltDown> {
+    let url = Url::parse(issuer.trim()).map_err(|_| MeltDown::invalid_input("OIDC issuer").with_user_message("The issuer must be a valid URL."))?;
+
+    match url.scheme() {
+        "https" => Ok(url),
+        "http" if oidc_settings().allow_insecure_issuers => Ok(url),
+        _ => Err(MeltDown::new(MeltType::ValidationFailed, format!("Insecure OIDC issuer: {}", issuer)).with_user_message("The issuer must use https.")),
+    }
+}
+
+async fn fetch_json<T: serde::de::DeserializeOwned>(url: &str, issuer: &str) -> Result<T, MeltDown> {
+    let response = HTTP_CLIENT.get(url).send().await.map_err(|e| external_error(format!("OIDC request to {} failed: {}", url, e), issuer))?;
+
+    if !response.status().is_success() {
+        return Err(external_error(format!("OIDC request to {} returned {}", url, response.status()), issuer));
+    }
+
+    response.json::<T>().await.map_err(|e| external_error(format!("Invalid OIDC response from {}: {}", url, e), issuer))
+}
+
+pub async fn discover(issuer: &str) -> Result<OidcDiscovery, MeltDown> {
+    let cache_ttl = Duration::from_secs(oidc_settings().discovery_cache_secs);
+
+    if let Some((fetched_at, discovery)) = DISCOVERY_CACHE.read().unwrap().get(issuer) {
+        if fetched_at.elapsed() < cache_ttl {
+            return Ok(discovery.clone());
+        }
+    }
+
+    validate_issuer_url(issuer)?;
+
+    let url = format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'));
+    let discovery: OidcDiscovery = fetch_json(&url, issuer).await?;
+
+    if discovery.issuer.trim_end_matches('/') != issuer.trim_end_matches('/') {
+        return Err(external_error(format!("OIDC discovery issuer mismatch: expected {}, got {}", issuer, discovery.issuer), issuer));
+    }
+
+    cata_log!(Debug, format!("Loaded OIDC discovery document for {}", issuer));
+    DISCOVERY_CACHE.write().unwrap().insert(issuer.to_string(), (Instant::now(), discovery.clone()));
+    Ok(discovery)
+}
+
+async fn jwks(discovery: &OidcDiscovery, refresh: bool) -> Result<JwkSet, MeltDown> {
+    let cache_ttl = Duration::from_secs(oidc_settings().discovery_cache_secs);
+
+    if !refresh {
+        if let Some((fetched_at, keys)) = JWKS_CACHE.read().unwrap().get(&discovery.jwks_uri) {
+            if fetched_at.elapsed() < cache_ttl {
+                return Ok(keys.clone());
+            }
+        }
+    }
+
+    let keys: JwkSet = fetch_json(&discovery.jwks_uri, &discovery.issuer).await?;
+    JWKS_CACHE.write().unwrap().insert(discovery.jwks_uri.clone(), (Instant::now(), keys.clone()));
+    Ok(keys)
+}
+
+pub fn invalidate_provider(issuer: &str) {
+    if let Some((_, discovery)) = DISCOVERY_CACHE.write().unwrap().remove(issuer) {
+        JWKS_CACHE.write().unwrap().remove(&discovery.jwks_uri);
+    }
+}
+
+pub fn redirect_uri(provider: &OidcProvider, tenant_name: &str) -> String {
+    format!("{}/{}/auth/oidc/{}/callback", mailer::mail_settings().base_url.trim_end_matches('/'), tenant_name, provider.slug)
+}
+
+pub fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+fn prune_expired_flows(flows: &mut HashMap<String, OidcPendingFlow>, now: i64) {
+    let ttl = oidc_settings().flow_ttl_secs;
+    flows.retain(|_, flow| now - flow.created_at < ttl);
+}
+
+pub async fn authorization_url(provider: &OidcProvider, tenant_name: &str) -> Result<(String, String), MeltDown> {
+    let discovery = discover(&provider.issuer).await?;
+
+    let state = random_string(STATE_LENGTH);
+    let nonce = random_string(NONCE_LENGTH);
+    let code_verifier = random_string(CODE_VERIFIER_LENGTH);
+    let redirect_uri = redirect_uri(provider, tenant_name);
+
+    let url = Url::parse_with_params(
+        &discovery.authorization_endpoint,
+        &[
+            ("response_type", "code"),
+            ("client_id", provider.client_id.as_str()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("scope", provider.scopes.as_str()),
+            ("state", state.as_str()),
+            ("nonce", nonce.as_str()),
+            ("code_challenge", code_challenge(&code_verifier).as_str()),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map_err(|e| external_error(format!("Invalid authorization endpoint: {}", e), &provider.issuer))?;
+
+    let now = Utc::now().timestamp();
+    let mut flows = PENDING_FLOWS.lock().unwrap();
+    prune_expired_flows(&mut flows, now);
+    flows.insert(
+        state.clone(),
+        OidcPendingFlow {
+            tenant_name: tenant_name.to_string(),
+            provider_slug: provider.slug.clone(),
+            nonce,
+            code_verifier,
+            created_at: now,
+        },
+    );
+
+    Ok((url.to_string(), state))
+}
+
+pub fn take_pending_flow(state: &str, provider: &OidcProvider, tenant_name: &str) -> Result<OidcPendingFlow, MeltDown> {
+    let now = Utc::now().timestamp();
+    let mut flows = PENDING_FLOWS.lock().unwrap();
+    prune_expired_flows(&mut flows, now);
+
+    flows
+        .remove(state)
+        .filter(|flow| flow.tenant_name == tenant_name && flow.provider_slug == provider.slug)
+        .ok_or_else(|| MeltDown::new(MeltType::InvalidToken, "Unknown or expired OIDC state").with_user_message("Your sign-in attempt has expired. Please try again."))
+}
+
+pub async fn exchange_code(provider: &OidcProvider, code: &str, flow: &OidcPendingFlow) -> Result<OidcClaims, MeltDown> {
+    let discovery = discover(&provider.issuer).await?;
+    let redirect_uri = redirect_uri(provider, &flow.tenant_name);
+
+    let response = HTTP_CLIENT
+        .post(&discovery.token_endpoint)
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("client_id", provider.client_id.as_str()),
+            ("client_secret", provider.client_secret.as_str()),
+            ("code_verifier", flow.code_verifier.as_str()),
+        ])
+        .send()
+        .await
+        .map_err(|e| external_error(format!("OIDC token request failed: {}", e), &provider.issuer))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(external_error(format!("OIDC token endpoint returned {}: {}", status, body), &provider.issuer));
+    }
+
+    let tokens: OidcTokenResponse = response.json().await.map_err(|e| external_error(format!("Invalid OIDC token response: {}", e), &provider.issuer))?;
+
+    validate_id_token(provider, &discovery, &tokens.id_token, &flow.nonce).await
+}
+
+async fn validate_id_token(provider: &OidcProvider, discovery: &OidcDiscovery, id_token: &str, nonce: &str) -> Result<OidcClaims, MeltDown> {
+    let invalid = |details: String| {
+        MeltDown::new(MeltType::InvalidToken, details)
+            .with_context("issuer", provider.issuer.clone())
+            .with_user_message("Single sign-on failed. Please try again.")
+    };
+
+    let header = jsonwebtoken::decode_header(id_token).map_err(|e| invalid(format!("Malformed ID token: {}", e)))?;
+
+    if matches!(header.alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
+        return Err(invalid(format!("Unsupported ID token algorithm: {:?}", header.alg)));
+    }
+
+    let find_key = |keys: &JwkSet| match header.kid.as_deref() {
+        Some(kid) => keys.find(kid).cloned(),
+        None if keys.keys.len() == 1 => keys.keys.first().cloned(),
+        None => None,
+    };
+
+    let jwk = match find_key(&jwks(discovery, false).await?) {
+        Some(jwk) => jwk,
+        None => find_key(&jwks(discovery, true).await?).ok_or_else(|| invalid(format!("No signing key for kid {:?}", header.kid)))?,
+    };
+
+    let key = DecodingKey::from_jwk(&jwk).map_err(|e| invalid(format!("Unusable signing key: {}", e)))?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_issuer(&[discovery.issuer.as_str()]);
+    validation.set_audience(&[provider.client_id.as_str()]);
+
+    let claims = jsonwebtoken::decode::<OidcClaims>(id_token, &key, &validation).map_err(|e| invalid(format!("ID token rejected: {}", e)))?.claims;
+
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(invalid("ID token nonce mismatch".to_string()));
+    }
+
+    Ok(claims)
+}
+
+fn claim_values(claims: &OidcClaims, path: &str) -> Vec<String> {
+    let mut segments = path.split('.');
+    let Some(mut value) = segments.next().and_then(|first| claims.extra.get(first)) else {
+        return Vec::new();
+    };
+
+    for segment in segments {
+        match value.get(segment) {
+            Some(next) => value = next,
+            None => return Vec::new(),
+        }
+    }
+
+    match value {
+        JsonValue::String(single) => vec![single.clone()],
+        JsonValue::Array(items) => items.iter().filter_map(|item| item.as_str().map(str::to_string)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+pub fn map_role(provider: &OidcProvider, claims: &OidcClaims) -> Option<String> {
+    let role_claim = provider.role_claim.as_deref().map(str::trim).filter(|claim| !claim.is_empty())?;
+    let values = claim_values(claims, role_claim);
+
+    provider.role_mapping.lines().filter_map(|line| line.split_once('=')).find_map(|(claim_value, role)| {
+        let (claim_value, role) = (claim_value.trim(), role.trim());
+        (!role.is_empty() && values.iter().any(|value| value == claim_value)).then(|| role.to_string())
+    })
+}
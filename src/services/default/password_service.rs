@@ -0,0 +1,269 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    sync::RwLock,
+};
+
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Algorithm, Argon2, Params, Version,
+};
+use once_cell::sync::Lazy;
+
+use crate::{
+    bootstrap::{PasswordAlgorithm, PasswordSettings, APP_CONFIG},
+    cata_log,
+    meltdown::*,
+};
+
+static BREACHED_LISTS: Lazy<RwLock<HashMap<String, HashSet<String>>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+pub fn password_settings() -> PasswordSettings {
+    APP_CONFIG.get().map(|config| config.security.password.clone()).unwrap_or_default()
+}
+
+fn load_breached_list(path: &str) -> HashSet<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => {
+            let entries: HashSet<String> = contents.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')).map(str::to_lowercase).collect();
+            cata_log!(Info, format!("Loaded {} breached passwords from {}", entries.len(), path));
+            entries
+        }
+        Err(e) => {
+            cata_log!(Warning, format!("Could not read breached password list {}: {}", path, e));
+            HashSet::new()
+        }
+    }
+}
+
+fn is_breached(path: &str, password: &str) -> bool {
+    let candidate = password.to_lowercase();
+
+    if let Some(list) = BREACHED_LISTS.read().unwrap().get(path) {
+        return list.contains(&candidate);
+    }
+
+    let list = load_breached_list(path);
+    let breached = list.contains(&candidate);
+    BREACHED_LISTS.write().unwrap().insert(path.to_string(), list);
+    breached
+}
+
+pub fn validate_password_with(settings: &PasswordSettings, password: &str) -> Result<(), MeltDown> {
+    let length = password.chars().count();
+    let mut problems = Vec::new();
+
+    if length < settings.min_length {
+        problems.push(format!("be at least {} characters long", settings.min_length));
+    }
+    if length > settings.max_length {
+        problems.push(format!("be at most {} characters long", settings.max_length));
+    }
+    if settings.require_uppercase && !password.chars().any(char::is_uppercase) {
+        problems.push("contain an uppercase letter".to_string());
+    }
+    if settings.require_lowercase && !password.chars().any(char::is_lowercase) {
+        problems.push("contain a lowercase letter".to_string());
+    }
+    if settings.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+        problems.push("contain a digit".to_string());
+    }
+    if settings.require_symbol && !password.chars().any(|c| !c.is_alphanumeric() && !c.is_whitespace()) {
+        problems.push("contain a symbol".to_string());
+    }
+
+    if !problems.is_empty() {
+        let message = format!("Password must {}.", problems.join(", "));
+        return Err(MeltDown::new(MeltType::ValidationFailed, "Password does not meet policy")
+            .with_context("violations", problems.join("; "))
+            .with_user_message(message));
+    }
+
+    if let Some(path) = &settings.breached_list {
+        if is_breached(path, password) {
+            return Err(MeltDown::new(MeltType::ValidationFailed, "Password found in breached password list").with_user_message("This password is too common or has appeared in a data breach. Please choose another."));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn validate_password(password: &str) -> Result<(), MeltDown> {
+    validate_password_with(&password_settings(), password)
+}
+
+fn argon2_for(settings: &PasswordSettings) -> Result<Argon2<'static>, MeltDown> {
+    let params =
+        Params::new(settings.argon2_memory_kib, settings.argon2_iterations, settings.argon2_parallelism, None).map_err(|e| MeltDown::new(MeltType::ConfigurationError, format!("Invalid Argon2 parameters: {}", e)))?;
+
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
+
+fn hash_with(settings: &PasswordSettings, password: &str) -> Result<String, MeltDown> {
+    match settings.algorithm {
+        PasswordAlgorithm::Bcrypt => bcrypt::hash(password, settings.bcrypt_cost).map_err(|e| MeltDown::from(e).with_context("operation", "password_hashing")),
+        PasswordAlgorithm::Argon2id => {
+            let salt = SaltString::generate(&mut OsRng);
+            argon2_for(settings)?
+                .hash_password(password.as_bytes(), &salt)
+                .map(|hash| hash.to_string())
+                .map_err(|e| MeltDown::new(MeltType::ConfigurationError, format!("Password hashing error: {}", e)).with_context("operation", "password_hashing"))
+        }
+    }
+}
+
+fn verify_with(password: &str, password_hash: &str) -> Result<bool, MeltDown> {
+    if password_hash.starts_with("$argon2") {
+        let parsed = PasswordHash::new(password_hash).map_err(|e| MeltDown::new(MeltType::ConfigurationError, format!("Invalid Argon2 hash: {}", e)))?;
+        return Ok(Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok());
+    }
+
+    bcrypt::verify(password, password_hash).map_err(MeltDown::from)
+}
+
+pub fn needs_rehash_with(settings: &PasswordSettings, password_hash: &str) -> bool {
+    if password_hash.starts_with("$argon2") {
+        let current = PasswordHash::new(password_hash)
+            .ok()
+            .and_then(|parsed| Params::try_from(&parsed).ok().map(|params| (parsed.algorithm.as_str() == "argon2id", params)));
+
+        return match current {
+            Some((is_argon2id, params)) => !is_argon2id || params.m_cost() < settings.argon2_memory_kib || params.t_cost() < settings.argon2_iterations || params.p_cost() < settings.argon2_parallelism,
+            None => true,
+        };
+    }
+
+    match settings.algorithm {
+        PasswordAlgorithm::Argon2id => true,
+        PasswordAlgorithm::Bcrypt => password_hash.split('$').nth(2).and_then(|cost| cost.parse::<u32>().ok()).is_none_or(|cost| cost < settings.bcrypt_cost),
+    }
+}
+
+pub fn needs_rehash(password_hash: &str) -> bool {
+    needs_rehash_with(&password_settings(), password_hash)
+}
+
+pub async fn hash_password(password: String) -> Result<String, MeltDown> {
+    let settings = password_settings();
+
+    tokio::task::spawn_blocking(move || hash_with(&settings, &password))
+        .await
+        .map_err(|e| MeltDown::new(MeltType::Unknown, format!("Task join error: {}", e)))?
+}
+
+pub async fn verify_password_hash(password: String, password_hash: String) -> Result<bool, MeltDown> {
+    tokio::task::spawn_blocking(move || verify_with(&password, &password_hash))
+        .await
+        .map_err(|e| MeltDown::new(MeltType::Unknown, format!("Task join error: {}", e)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argon2_settings() -> PasswordSettings {
+        PasswordSettings {
+            algorithm: PasswordAlgorithm::Argon2id,
+            argon2_memory_kib: 64,
+            argon2_iterations: 1,
+            argon2_parallelism: 1,
+            ..PasswordSettings::default()
+        }
+    }
+
+    #[test]
+    fn bcrypt_hashes_below_configured_cost_need_rehash() {
+        let settings = PasswordSettings {
+            bcrypt_cost: 12,
+            ..PasswordSettings::default()
+        };
+
+        assert!(needs_rehash_with(&settings, "$2b$10$abcdefghijklmnopqrstuuvwxyzABCDEFGHIJKLMNOPQRSTUVWXY"));
+        assert!(!needs_rehash_with(&settings, "$2b$12$abcdefghijklmnopqrstuuvwxyzABCDEFGHIJKLMNOPQRSTUVWXY"));
+        assert!(!needs_rehash_with(&settings, "$2b$14$abcdefghijklmnopqrstuuvwxyzABCDEFGHIJKLMNOPQRSTUVWXY"));
+        assert!(needs_rehash_with(&settings, "not a hash"));
+    }
+
+    #[test]
+    fn bcrypt_hashes_need_rehash_when_argon2id_is_configured() {
+        assert!(needs_rehash_with(&argon2_settings(), "$2b$12$abcdefghijklmnopqrstuuvwxyzABCDEFGHIJKLMNOPQRSTUVWXY"));
+    }
+
+    #[test]
+    fn argon2_hashes_follow_configured_parameters() {
+        let settings = argon2_settings();
+        let hash = hash_with(&settings, "correct horse").unwrap();
+
+        assert!(!needs_rehash_with(&settings, &hash));
+        assert!(needs_rehash_with(
+            &PasswordSettings {
+                argon2_memory_kib: 128,
+                ..argon2_settings()
+            },
+            &hash
+        ));
+        assert!(needs_rehash_with(
+            &PasswordSettings {
+                argon2_iterations: 2,
+                ..argon2_settings()
+            },
+            &hash
+        ));
+        assert!(!needs_rehash_with(
+            &PasswordSettings {
+                algorithm: PasswordAlgorithm::Bcrypt,
+                ..argon2_settings()
+            },
+            &hash
+        ));
+    }
+
+    #[test]
+    fn hashes_verify_for_both_algorithms() {
+        let argon2_hash = hash_with(&argon2_settings(), "correct horse").unwrap();
+        let bcrypt_hash = hash_with(
+            &PasswordSettings {
+                bcrypt_cost: 4,
+                ..PasswordSettings::default()
+            },
+            "correct horse",
+        )
+        .unwrap();
+
+        assert!(verify_with("correct horse", &argon2_hash).unwrap());
+        assert!(!verify_with("wrong horse", &argon2_hash).unwrap());
+        assert!(verify_with("correct horse", &bcrypt_hash).unwrap());
+        assert!(!verify_with("wrong horse", &bcrypt_hash).unwrap());
+    }
+
+    #[test]
+    fn reports_every_policy_violation() {
+        let settings = PasswordSettings {
+            min_length: 10,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            ..PasswordSettings::default()
+        };
+
+        let error = validate_password_with(&settings, "short").unwrap_err();
+        assert!(matches!(error.melt_type, MeltType::ValidationFailed));
+        assert_eq!(
+            error.user_message(),
+            "Password must be at least 10 characters long, contain an uppercase letter, contain a digit, contain a symbol."
+        );
+
+        assert!(validate_password_with(&settings, "Longer-pass1").is_ok());
+    }
+
+    #[test]
+    fn enforces_maximum_length() {
+        let settings = PasswordSettings {
+            max_length: 12,
+            ..PasswordSettings::default()
+        };
+
+        assert!(validate_password_with(&settings, "abcdefghijkl").is_ok());
+        assert!(validate_password_with(&settings, "abcdefghijklm").is_err());
+    }
+}
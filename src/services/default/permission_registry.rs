@@ -0,0 +1,45 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::RwLock,
+};
+
+use once_cell::sync::Lazy;
+
+use crate::{cata_log, meltdown::*, structs::Roles};
+
+type RolePermissionMap = HashMap<String, HashSet<String>>;
+
+static TENANT_PERMISSIONS: Lazy<RwLock<HashMap<String, RolePermissionMap>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+async fn load_tenant(tenant_name: &str) -> Result<RolePermissionMap, MeltDown> {
+    let map = Roles::permission_map(tenant_name).await?;
+    cata_log!(Debug, format!("Loaded permissions for {} roles (tenant: {})", map.len(), tenant_name));
+
+    TENANT_PERMISSIONS.write().unwrap().insert(tenant_name.to_string(), map.clone());
+    Ok(map)
+}
+
+pub async fn permissions_for_role(tenant_name: &str, role: &str) -> Result<HashSet<String>, MeltDown> {
+    if let Some(map) = TENANT_PERMISSIONS.read().unwrap().get(tenant_name) {
+        return Ok(map.get(role).cloned().unwrap_or_default());
+    }
+
+    Ok(load_tenant(tenant_name).await?.get(role).cloned().unwrap_or_default())
+}
+
+pub async fn role_has_permission(tenant_name: &str, role: &str, permission: &str) -> Result<bool, MeltDown> {
+    Ok(permissions_for_role(tenant_name, role).await?.contains(permission))
+}
+
+pub async fn role_within(tenant_name: &str, role: &str, actor_role: &str) -> Result<bool, MeltDown> {
+    let granted = permissions_for_role(tenant_name, role).await?;
+    let held = permissions_for_role(tenant_name, actor_role).await?;
+
+    Ok(granted.is_subset(&held))
+}
+
+pub fn invalidate_tenant(tenant_name: &str) {
+    if TENANT_PERMISSIONS.write().unwrap().remove(tenant_name).is_some() {
+        cata_log!(Debug, format!("Invalidated cached permissions for tenant: {}", tenant_name));
+    }
+}
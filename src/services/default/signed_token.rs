@@ -0,0 +1,73 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+
+use crate::{meltdown::*, services::default::jwt_service, structs::TokenPurpose};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const NONCE_LENGTH: usize = 32;
+
+pub struct SignedToken {
+    pub token: String,
+    pub user_id: i32,
+    pub expires_at: i64,
+}
+
+fn signer(purpose: TokenPurpose, payload: &str) -> Result<HmacSha256, MeltDown> {
+    let mut mac = HmacSha256::new_from_slice(jwt_service::jwt_secret().as_bytes()).map_err(|e| MeltDown::new(MeltType::ConfigurationError, format!("Invalid signing key: {}", e)))?;
+    mac.update(purpose.as_str().as_bytes());
+    mac.update(b":");
+    mac.update(payload.as_bytes());
+    Ok(mac)
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return None;
+    }
+
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok()).collect()
+}
+
+pub fn sign_token(purpose: TokenPurpose, user_id: i32) -> Result<SignedToken, MeltDown> {
+    let expires_at = Utc::now().timestamp() + purpose.lifetime_secs();
+    let nonce: String = rand::thread_rng().sample_iter(&Alphanumeric).take(NONCE_LENGTH).map(char::from).collect();
+
+    let payload = format!("{}.{}.{}", user_id, expires_at, nonce);
+    let signature = format!("{:x}", signer(purpose, &payload)?.finalize().into_bytes());
+
+    Ok(SignedToken {
+        token: format!("{}.{}", payload, signature),
+        user_id,
+        expires_at,
+    })
+}
+
+pub fn verify_token(purpose: TokenPurpose, token: &str) -> Result<SignedToken, MeltDown> {
+    let invalid = || MeltDown::new(MeltType::InvalidToken, format!("Invalid {} token", purpose.as_str())).with_user_message("This link is invalid or has expired.");
+
+    let (payload, signature) = token.rsplit_once('.').ok_or_else(invalid)?;
+    let signature = from_hex(signature).ok_or_else(invalid)?;
+
+    signer(purpose, payload)?.verify_slice(&signature).map_err(|_| invalid())?;
+
+    let mut parts = payload.splitn(3, '.');
+    let user_id = parts.next().and_then(|part| part.parse::<i32>().ok()).ok_or_else(invalid)?;
+    let expires_at = parts.next().and_then(|part| part.parse::<i64>().ok()).ok_or_else(invalid)?;
+
+    if expires_at <= Utc::now().timestamp() {
+        return Err(MeltDown::new(MeltType::TokenExpired, format!("Expired {} token", purpose.as_str())).with_user_message("This link is invalid or has expired."));
+    }
+
+    Ok(SignedToken {
+        token: token.to_string(),
+        user_id,
+        expires_at,
+    })
+}
+
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
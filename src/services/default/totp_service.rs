@@ -0,0 +1,132 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use qrcode::{render::svg, QrCode};
+use rand::{distributions::Alphanumeric, Rng};
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use crate::meltdown::*;
+
+const TOTP_DIGITS: usize = 6;
+const TOTP_SKEW: i64 = 1;
+const TOTP_STEP: u64 = 30;
+const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_LENGTH: usize = 10;
+
+pub enum SecondFactorCheck {
+    Totp(i64),
+    RecoveryCode(Vec<String>),
+    Invalid,
+}
+
+pub fn generate_totp_secret() -> String {
+    Secret::generate_secret().to_encoded().to_string()
+}
+
+fn build_totp(secret: &str, issuer: &str, account_name: &str) -> Result<TOTP, MeltDown> {
+    let secret_bytes = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|e| MeltDown::new(MeltType::ConfigurationError, format!("Invalid TOTP secret: {:?}", e)))?;
+
+    TOTP::new(
+        Algorithm::SHA1,
+        TOTP_DIGITS,
+        TOTP_SKEW as u8,
+        TOTP_STEP,
+        secret_bytes,
+        Some(issuer.replace(':', "-")),
+        account_name.replace(':', "-"),
+    )
+    .map_err(|e| MeltDown::new(MeltType::ConfigurationError, format!("Invalid TOTP configuration: {}", e)))
+}
+
+pub fn totp_provisioning_uri(secret: &str, issuer: &str, account_name: &str) -> Result<String, MeltDown> {
+    Ok(build_totp(secret, issuer, account_name)?.get_url())
+}
+
+pub fn totp_qr_svg(provisioning_uri: &str) -> Result<String, MeltDown> {
+    let code = QrCode::new(provisioning_uri.as_bytes()).map_err(|e| MeltDown::new(MeltType::Unknown, format!("Failed to build QR code: {}", e)))?;
+
+    Ok(code.render::<svg::Color>().min_dimensions(200, 200).quiet_zone(true).build())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+pub fn verify_totp_code(secret: &str, code: &str, last_used_step: Option<i64>) -> Result<Option<i64>, MeltDown> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if code.len() != TOTP_DIGITS || !code.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(None);
+    }
+
+    let totp = build_totp(secret, "catalyst", "verification")?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| MeltDown::new(MeltType::Unknown, format!("System clock error: {}", e)))?
+        .as_secs();
+    let current_step = (now / TOTP_STEP) as i64;
+
+    for step in (current_step - TOTP_SKEW)..=(current_step + TOTP_SKEW) {
+        if step < 0 || last_used_step.is_some_and(|last| step <= last) {
+            continue;
+        }
+
+        let expected = totp.generate(step as u64 * TOTP_STEP);
+        if constant_time_eq(expected.as_bytes(), code.as_bytes()) {
+            return Ok(Some(step));
+        }
+    }
+
+    Ok(None)
+}
+
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_lowercase()
+}
+
+pub fn hash_recovery_code(code: &str) -> String {
+    format!("{:x}", Sha256::digest(normalize_recovery_code(code).as_bytes()))
+}
+
+pub fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = rand::thread_rng();
+
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let raw: String = (&mut rng).sample_iter(&Alphanumeric).take(RECOVERY_CODE_LENGTH).map(char::from).collect::<String>().to_lowercase();
+            format!("{}-{}", &raw[..RECOVERY_CODE_LENGTH / 2], &raw[RECOVERY_CODE_LENGTH / 2..])
+        })
+        .collect()
+}
+
+pub fn recovery_code_hashes(codes: &[String]) -> JsonValue {
+    JsonValue::Array(codes.iter().map(|code| JsonValue::String(hash_recovery_code(code))).collect())
+}
+
+pub fn check_second_factor(secret: &str, recovery_codes: &JsonValue, last_used_step: Option<i64>, code: &str) -> Result<SecondFactorCheck, MeltDown> {
+    if let Some(step) = verify_totp_code(secret, code, last_used_step)? {
+        return Ok(SecondFactorCheck::Totp(step));
+    }
+
+    let candidate = hash_recovery_code(code);
+    if normalize_recovery_code(code).len() != RECOVERY_CODE_LENGTH {
+        return Ok(SecondFactorCheck::Invalid);
+    }
+
+    let hashes: Vec<String> = recovery_codes.as_array().map(|codes| codes.iter().filter_map(|code| code.as_str().map(String::from)).collect()).unwrap_or_default();
+
+    match hashes.iter().position(|hash| constant_time_eq(hash.as_bytes(), candidate.as_bytes())) {
+        Some(index) => {
+            let mut remaining = hashes;
+            remaining.remove(index);
+            Ok(SecondFactorCheck::RecoveryCode(remaining))
+        }
+        None => Ok(SecondFactorCheck::Invalid),
+    }
+}
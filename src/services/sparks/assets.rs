@@ -0,0 +1,89 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use rocket_dyn_templates::Engines;
+use serde_json::{json, Value};
+
+use crate::{
+    cata_log,
+    services::sparks::registry::{compiled_spark, get_available_sparks, SparkAssetSource},
+};
+
+fn spark_assets() -> Vec<(&'static str, &'static SparkAssetSource)> {
+    get_available_sparks()
+        .iter()
+        .filter_map(|name| compiled_spark(name))
+        .filter_map(|registration| registration.assets.as_ref().map(|assets| (registration.name, assets)))
+        .collect()
+}
+
+fn spark_templates() -> Vec<(String, String)> {
+    let mut templates = Vec::new();
+
+    for (spark_name, assets) in spark_assets() {
+        let count = templates.len();
+
+        for file in (assets.list)() {
+            let (Some(relative), Some(content)) = (file.strip_prefix("templates/"), (assets.get)(&file)) else {
+                continue;
+            };
+
+            match String::from_utf8(content.into_owned()) {
+                Ok(content) => templates.push((format!("sparks/{}/{}", spark_name, relative.strip_suffix(".tera").unwrap_or(relative)), content)),
+                Err(e) => cata_log!(Error, format!("Spark template {} from '{}' is not valid UTF-8: {}", file, spark_name, e)),
+            }
+        }
+
+        if templates.len() > count {
+            cata_log!(Debug, format!("Registered {} template(s) for spark '{}' under sparks/{}/", templates.len() - count, spark_name, spark_name));
+        }
+    }
+
+    templates
+}
+
+pub fn register_spark_templates(engines: &mut Engines) {
+    if let Err(e) = engines.tera.add_raw_templates(spark_templates()) {
+        cata_log!(Error, format!("Failed to register spark templates: {}", e));
+    }
+}
+
+pub fn spark_public_file(spark_name: &str, path: &str) -> Option<Cow<'static, [u8]>> {
+    if path.split('/').any(|segment| segment == "..") || !get_available_sparks().iter().any(|name| name == spark_name) {
+        return None;
+    }
+
+    let assets = compiled_spark(spark_name)?.assets.as_ref()?;
+    (assets.get)(&format!("public/{}", path))
+}
+
+fn merge_missing(target: &mut Value, source: Value) {
+    if let (Value::Object(target), Value::Object(source)) = (target, source) {
+        for (key, value) in source {
+            match target.get_mut(&key) {
+                Some(existing) => merge_missing(existing, value),
+                None => {
+                    target.insert(key, value);
+                }
+            }
+        }
+    }
+}
+
+pub fn merge_spark_translations(translations: &mut HashMap<String, Value>) {
+    for (spark_name, assets) in spark_assets() {
+        for file in (assets.list)() {
+            let Some(language) = file.strip_prefix("locale/").and_then(|name| name.strip_suffix(".json")) else {
+                continue;
+            };
+
+            match (assets.get)(&file).map(|content| serde_json::from_slice::<Value>(&content)) {
+                Some(Ok(spark_translations)) => {
+                    merge_missing(translations.entry(language.to_string()).or_insert_with(|| json!({})), spark_translations);
+                    cata_log!(Debug, format!("Merged {} translations from spark '{}'", language, spark_name));
+                }
+                Some(Err(e)) => cata_log!(Error, format!("Failed to parse {} from spark '{}': {}", file, spark_name, e)),
+                None => {}
+            }
+        }
+    }
+}
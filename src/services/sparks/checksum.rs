@@ -0,0 +1,138 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+use toml::Value as TomlValue;
+
+pub const SPARK_LOCK_FILE: &str = "sparks.lock";
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.file_name().is_some_and(|name| name == ".git") {
+            continue;
+        }
+
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn spark_checksum(spark_dir: &Path) -> io::Result<String> {
+    let mut files = Vec::new();
+    collect_files(spark_dir, &mut files)?;
+    files.sort();
+
+    let mut hasher = Sha256::new();
+
+    for file in files {
+        let relative = file.strip_prefix(spark_dir).unwrap_or(&file).to_string_lossy().replace('\\', "/");
+        let content = fs::read(&file)?;
+
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update((content.len() as u64).to_le_bytes());
+        hasher.update(&content);
+    }
+
+    Ok(format!("sha256:{:x}", hasher.finalize()))
+}
+
+pub fn spark_directories(sparks_dir: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+    let mut sparks = Vec::new();
+
+    for entry in fs::read_dir(sparks_dir)? {
+        let path = entry?.path();
+        let manifest_path = path.join("manifest.toml");
+
+        if !manifest_path.is_file() {
+            continue;
+        }
+
+        let manifest = fs::read_to_string(&manifest_path)?.parse::<TomlValue>().ok();
+        let name = manifest
+            .as_ref()
+            .and_then(|manifest| manifest.get("spark"))
+            .and_then(|spark| spark.get("name"))
+            .and_then(|name| name.as_str())
+            .map(str::to_string)
+            .or_else(|| path.file_name().map(|name| name.to_string_lossy().to_string()));
+
+        if let Some(name) = name {
+            sparks.push((name, path));
+        }
+    }
+
+    sparks.sort();
+    Ok(sparks)
+}
+
+pub fn checksum_mismatches(lock_content: &str, checksums: &[(&str, &str)]) -> Result<Vec<String>, String> {
+    let lock = lock_content.parse::<TomlValue>().map_err(|e| format!("Failed to parse {}: {}", SPARK_LOCK_FILE, e))?;
+    let entries = lock.get("spark").and_then(|sparks| sparks.as_array()).cloned().unwrap_or_default();
+
+    let mut mismatches = Vec::new();
+
+    for (name, checksum) in checksums {
+        let locked = entries.iter().find(|entry| entry.get("name").and_then(|n| n.as_str()) == Some(name));
+
+        match locked.and_then(|entry| entry.get("checksum")).and_then(|c| c.as_str()) {
+            Some(locked) if locked == *checksum => {}
+            Some(locked) => mismatches.push(format!("Spark '{}' has checksum {}, but {} records {}", name, checksum, SPARK_LOCK_FILE, locked)),
+            None => mismatches.push(format!("Spark '{}' is not recorded in {}", name, SPARK_LOCK_FILE)),
+        }
+    }
+
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCK: &str = r#"
+[[spark]]
+name = "audit"
+checksum = "aaa"
+
+[[spark]]
+name = "vault"
+checksum = "bbb"
+"#;
+
+    #[test]
+    fn matching_checksums_have_no_mismatches() {
+        assert!(checksum_mismatches(LOCK, &[("audit", "aaa"), ("vault", "bbb")]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reports_changed_and_unrecorded_sparks() {
+        let mismatches = checksum_mismatches(LOCK, &[("audit", "ccc"), ("alerts", "ddd")]).unwrap();
+
+        assert_eq!(
+            mismatches,
+            vec![
+                format!("Spark 'audit' has checksum ccc, but {} records aaa", SPARK_LOCK_FILE),
+                format!("Spark 'alerts' is not recorded in {}", SPARK_LOCK_FILE)
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_lock_file_records_nothing() {
+        assert_eq!(checksum_mismatches("", &[("audit", "aaa")]).unwrap(), vec![format!("Spark 'audit' is not recorded in {}", SPARK_LOCK_FILE)]);
+    }
+
+    #[test]
+    fn rejects_unparseable_lock_file() {
+        assert!(checksum_mismatches("[[spark]\nname =", &[]).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::{BTreeMap, HashMap};
+
+use semver::{Version, VersionReq};
+use toml::Value as TomlValue;
+
+pub const CATALYST_DEPENDENCY: &str = "catalyst";
+pub const CATALYST_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub fn manifest_dependencies(manifest: &TomlValue) -> Result<BTreeMap<String, VersionReq>, String> {
+    let Some(dependencies) = manifest.get("dependencies") else {
+        return Ok(BTreeMap::new());
+    };
+
+    let table = dependencies.as_table().ok_or("[dependencies] section must be a table")?;
+
+    table
+        .iter()
+        .map(|(name, requirement)| {
+            let requirement = requirement.as_str().ok_or(format!("Dependency '{}' must be a version requirement string", name))?;
+            let requirement = VersionReq::parse(requirement).map_err(|e| format!("Invalid version requirement '{}' for dependency '{}': {}", requirement, name, e))?;
+            Ok((name.clone(), requirement))
+        })
+        .collect()
+}
+
+fn manifest_version(manifest: &TomlValue) -> Result<Version, String> {
+    let version = manifest.get("spark").and_then(|spark| spark.get("version")).and_then(|v| v.as_str()).ok_or("Missing [spark] version")?;
+    Version::parse(version).map_err(|e| format!("Invalid spark version '{}': {}", version, e))
+}
+
+fn visit<'a>(name: &'a str, graph: &'a BTreeMap<String, BTreeMap<String, VersionReq>>, visited: &mut HashMap<&'a str, bool>, path: &mut Vec<&'a str>, order: &mut Vec<String>) -> Result<(), String> {
+    match visited.get(name) {
+        Some(true) => return Ok(()),
+        Some(false) => {
+            let start = path.iter().position(|entry| *entry == name).unwrap_or(0);
+            let mut cycle = path[start..].to_vec();
+            cycle.push(name);
+            return Err(format!("Spark dependency cycle detected: {}", cycle.join(" -> ")));
+        }
+        None => {}
+    }
+
+    visited.insert(name, false);
+    path.push(name);
+
+    for dependency in graph[name].keys().filter(|dependency| graph.contains_key(dependency.as_str())) {
+        visit(dependency, graph, visited, path, order)?;
+    }
+
+    path.pop();
+    visited.insert(name, true);
+    order.push(name.to_string());
+    Ok(())
+}
+
+pub fn resolve_spark_order(manifests: &HashMap<String, TomlValue>) -> Result<Vec<String>, Vec<String>> {
+    let catalyst_version = Version::parse(CATALYST_VERSION).map_err(|e| vec![format!("Invalid Catalyst version '{}': {}", CATALYST_VERSION, e)])?;
+
+    let mut errors = Vec::new();
+    let mut graph = BTreeMap::new();
+    let mut versions = HashMap::new();
+
+    for (name, manifest) in manifests {
+        match manifest_version(manifest) {
+            Ok(version) => {
+                versions.insert(name.as_str(), version);
+            }
+            Err(e) => errors.push(format!("Spark '{}': {}", name, e)),
+        }
+
+        match manifest_dependencies(manifest) {
+            Ok(dependencies) => {
+                graph.insert(name.clone(), dependencies);
+            }
+            Err(e) => errors.push(format!("Spark '{}': {}", name, e)),
+        }
+    }
+
+    for (name, dependencies) in &graph {
+        for (dependency, requirement) in dependencies {
+            if dependency == CATALYST_DEPENDENCY {
+                if !requirement.matches(&catalyst_version) {
+                    errors.push(format!("Spark '{}' requires Catalyst {}, but this is Catalyst {}", name, requirement, catalyst_version));
+                }
+                continue;
+            }
+
+            match versions.get(dependency.as_str()) {
+                Some(version) if requirement.matches(version) => {}
+                Some(version) => errors.push(format!("Spark '{}' requires spark '{}' {}, but version {} is installed", name, dependency, requirement, version)),
+                None => errors.push(format!("Spark '{}' depends on spark '{}' ({}), which is not installed, configured and enabled", name, dependency, requirement)),
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut visited = HashMap::new();
+    let mut order = Vec::new();
+
+    for name in graph.keys() {
+        visit(name, &graph, &mut visited, &mut Vec::new(), &mut order).map_err(|e| vec![e])?;
+    }
+
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(name: &str, version: &str, dependencies: &[(&str, &str)]) -> (String, TomlValue) {
+        let mut source = format!("[spark]\nname = \"{}\"\nversion = \"{}\"\n\n[dependencies]\n", name, version);
+        for (dependency, requirement) in dependencies {
+            source.push_str(&format!("{} = \"{}\"\n", dependency, requirement));
+        }
+        (name.to_string(), toml::from_str(&source).unwrap())
+    }
+
+    #[test]
+    fn orders_dependencies_before_dependents() {
+        let manifests = HashMap::from([manifest("audit", "1.0.0", &[("vault", "^0.2")]), manifest("vault", "0.2.3", &[]), manifest("alerts", "1.0.0", &[("audit", ">=1")])]);
+
+        assert_eq!(resolve_spark_order(&manifests).unwrap(), vec!["vault", "audit", "alerts"]);
+    }
+
+    #[test]
+    fn reports_dependency_cycles() {
+        let manifests = HashMap::from([manifest("a", "1.0.0", &[("b", "*")]), manifest("b", "1.0.0", &[("c", "*")]), manifest("c", "1.0.0", &[("a", "*")])]);
+
+        let errors = resolve_spark_order(&manifests).unwrap_err();
+        assert_eq!(errors, vec!["Spark dependency cycle detected: a -> b -> c -> a"]);
+    }
+
+    #[test]
+    fn collects_every_version_problem() {
+        let manifests = HashMap::from([
+            manifest("audit", "1.0.0", &[("vault", "^2"), ("ledger", "^1")]),
+            manifest("vault", "1.4.0", &[]),
+            manifest("alerts", "1.0.0", &[(CATALYST_DEPENDENCY, ">=999")]),
+        ]);
+
+        let errors = resolve_spark_order(&manifests).unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(errors.contains(&"Spark 'audit' requires spark 'vault' ^2, but version 1.4.0 is installed".to_string()));
+        assert!(errors.iter().any(|error| error.starts_with("Spark 'audit' depends on spark 'ledger' (^1)")));
+        assert!(errors.iter().any(|error| error.starts_with("Spark 'alerts' requires Catalyst >=999")));
+    }
+
+    #[test]
+    fn rejects_invalid_versions_and_requirements() {
+        let manifests = HashMap::from([manifest("audit", "one", &[]), manifest("vault", "1.0.0", &[("audit", "not a requirement")])]);
+
+        let errors = resolve_spark_order(&manifests).unwrap_err();
+        assert!(errors.iter().any(|error| error.starts_with("Spark 'audit': Invalid spark version 'one'")));
+        assert!(errors.iter().any(|error| error.starts_with("Spark 'vault': Invalid version requirement 'not a requirement'")));
+    }
+}
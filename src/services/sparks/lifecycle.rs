@@ -0,0 +1,184 @@
+use std::sync::{Arc, Mutex};
+
+use diesel::prelude::*;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl, SimpleAsyncConnection};
+use once_cell::sync::Lazy;
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    tokio::task::JoinHandle,
+    Orbit, Rocket,
+};
+
+use crate::{
+    cata_log,
+    database::{db::establish_connection_with_tenant, schema::spark_migrations::dsl as spark_migrations_dsl},
+    meltdown::*,
+    services::sparks::registry::{available_spark_instances, Spark, SparkJob, SparkMigration},
+    vessel::structs::Tenant,
+};
+
+static ACTIVE_SPARKS: Lazy<Mutex<Vec<Arc<dyn Spark>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+static JOB_HANDLES: Lazy<Mutex<Vec<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+pub fn activate(spark: Box<dyn Spark>) {
+    let mut active = ACTIVE_SPARKS.lock().unwrap();
+
+    if active.iter().any(|existing| existing.name() == spark.name()) {
+        cata_log!(Debug, format!("Spark '{}' already active, skipping lifecycle registration", spark.name()));
+        return;
+    }
+
+    active.push(Arc::from(spark));
+}
+
+pub fn active_sparks() -> Vec<Arc<dyn Spark>> {
+    ACTIVE_SPARKS.lock().unwrap().clone()
+}
+
+async fn apply_migrations(spark_name: &str, migrations: &[SparkMigration], tenant_name: &str) -> Result<usize, MeltDown> {
+    if migrations.is_empty() {
+        return Ok(0);
+    }
+
+    let mut conn = establish_connection_with_tenant(tenant_name).await?;
+
+    let applied: Vec<String> = spark_migrations_dsl::spark_migrations
+        .filter(spark_migrations_dsl::spark.eq(spark_name))
+        .select(spark_migrations_dsl::version)
+        .load(&mut conn)
+        .await
+        .map_err(|e| MeltDown::from(e).with_context("operation", "load_spark_migrations"))?;
+
+    let mut count = 0;
+
+    for migration in migrations.iter().filter(|migration| !applied.iter().any(|version| version == migration.version)) {
+        conn.transaction::<_, MeltDown, _>(|conn| {
+            async move {
+                conn.batch_execute(migration.up)
+                    .await
+                    .map_err(|e| MeltDown::from(e).with_context("migration", format!("{}/{}", spark_name, migration.version)))?;
+
+                diesel::insert_into(spark_migrations_dsl::spark_migrations)
+                    .values((spark_migrations_dsl::spark.eq(spark_name), spark_migrations_dsl::version.eq(migration.version)))
+                    .execute(conn)
+                    .await
+                    .map_err(|e| MeltDown::from(e).with_context("operation", "record_spark_migration"))?;
+
+                Ok(())
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+        cata_log!(Info, format!("Applied spark migration {}/{} (tenant: {})", spark_name, migration.version, tenant_name));
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+async fn migrate_tenant<'a>(tenant_name: &str, sparks: impl Iterator<Item = &'a dyn Spark>) -> Vec<String> {
+    let mut failures = Vec::new();
+
+    for spark in sparks {
+        if let Err(e) = apply_migrations(spark.name(), &spark.migrations(), tenant_name).await {
+            cata_log!(Error, format!("Failed to migrate spark '{}' (tenant: {}): {}", spark.name(), tenant_name, e.log_message()));
+            failures.push(format!("Failed to migrate spark '{}' for tenant {}: {}", spark.name(), tenant_name, e.log_message()));
+        }
+    }
+
+    failures
+}
+
+pub async fn migrate_all_tenants() -> Vec<String> {
+    let sparks = available_spark_instances();
+
+    if sparks.iter().all(|spark| spark.migrations().is_empty()) {
+        cata_log!(Debug, "No spark migrations to apply");
+        return Vec::new();
+    }
+
+    let tenants = match Tenant::all().await {
+        Ok(tenants) => tenants,
+        Err(e) => {
+            cata_log!(Error, format!("Failed to load tenants for spark migrations: {}", e.log_message()));
+            return vec![format!("Failed to load tenants for spark migrations: {}", e.log_message())];
+        }
+    };
+
+    let mut failures = Vec::new();
+    for tenant in tenants {
+        failures.extend(migrate_tenant(&tenant.name, sparks.iter().map(|spark| spark.as_ref())).await);
+    }
+
+    failures
+}
+
+pub async fn tenant_provisioned(tenant_name: &str) -> Result<(), MeltDown> {
+    let sparks = active_sparks();
+
+    let failures = migrate_tenant(tenant_name, sparks.iter().map(|spark| spark.as_ref())).await;
+    if !failures.is_empty() {
+        return Err(MeltDown::new(MeltType::DatabaseError, failures.join("; ")).with_context("tenant", tenant_name.to_string()));
+    }
+
+    for spark in &sparks {
+        if let Err(e) = spark.on_tenant_provisioned(tenant_name).await {
+            cata_log!(Error, format!("Spark '{}' failed to handle provisioning of tenant {}: {}", spark.name(), tenant_name, e));
+            return Err(MeltDown::new(MeltType::ConfigurationError, format!("Spark '{}' failed to handle provisioning of tenant {}: {}", spark.name(), tenant_name, e)).with_context("tenant", tenant_name.to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+fn schedule_job(spark_name: String, job: SparkJob) -> JoinHandle<()> {
+    rocket::tokio::spawn(async move {
+        let mut interval = rocket::tokio::time::interval(job.interval);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            cata_log!(CronjobExecution, format!("Running spark job {}/{}", spark_name, job.name));
+
+            if let Err(e) = (job.run)().await {
+                cata_log!(CronjobError, format!("Spark job {}/{} failed: {}", spark_name, job.name, e));
+            }
+        }
+    })
+}
+
+pub struct SparkLifecycleFairing;
+
+#[rocket::async_trait]
+impl Fairing for SparkLifecycleFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Spark Lifecycle",
+            kind: Kind::Liftoff | Kind::Shutdown | Kind::Singleton,
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        for spark in active_sparks() {
+            spark.on_liftoff(rocket).await;
+
+            let mut handles = JOB_HANDLES.lock().unwrap();
+            for job in spark.scheduled_jobs() {
+                cata_log!(Info, format!("Scheduling spark job {}/{} every {}s", spark.name(), job.name, job.interval.as_secs()));
+                handles.push(schedule_job(spark.name().to_string(), job));
+            }
+        }
+    }
+
+    async fn on_shutdown(&self, _rocket: &Rocket<Orbit>) {
+        for handle in JOB_HANDLES.lock().unwrap().drain(..) {
+            handle.abort();
+        }
+
+        for spark in active_sparks() {
+            cata_log!(Info, format!("Shutting down spark '{}'", spark.name()));
+            spark.on_shutdown().await;
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::{collections::HashMap, fs, path::Path, process::Command};
+
+use serde::{Deserialize, Serialize};
+use toml::Value as TomlValue;
+
+use crate::services::sparks::{
+    checksum::{spark_checksum, spark_directories, SPARK_LOCK_FILE},
+    registry::compiled_sparks,
+};
+
+pub const COMPILED_SPARK_CHECKSUMS: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/spark_checksums.rs"));
+
+const LOCAL_SOURCE: &str = "local";
+const LOCK_HEADER: &str = "# This file is generated by `cargo run --example lock_sparks`. Do not edit it by hand.\n\n";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SparkLockFile {
+    #[serde(default, rename = "spark")]
+    pub sparks: Vec<SparkLockEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SparkLockEntry {
+    pub name: String,
+    pub source: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    pub checksum: String,
+}
+
+impl SparkLockFile {
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&content).map(Some).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = toml::to_string_pretty(self).map_err(|e| format!("Failed to serialize {}: {}", SPARK_LOCK_FILE, e))?;
+        fs::write(path, format!("{}{}", LOCK_HEADER, content)).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SparkLockEntry> {
+        self.sparks.iter().find(|entry| entry.name == name)
+    }
+}
+
+pub fn spark_source(value: Option<&TomlValue>) -> String {
+    match value {
+        Some(TomlValue::String(source)) => source.clone(),
+        Some(TomlValue::Table(table)) => table.get("source").and_then(|source| source.as_str()).unwrap_or(LOCAL_SOURCE).to_string(),
+        _ => LOCAL_SOURCE.to_string(),
+    }
+}
+
+fn manifest_version(manifest: &str) -> String {
+    manifest
+        .parse::<TomlValue>()
+        .ok()
+        .and_then(|manifest| manifest.get("spark")?.get("version")?.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+pub fn verify_spark_lock(lock_path: &Path, configured: &HashMap<String, TomlValue>) -> Result<(), Vec<String>> {
+    let lock = match SparkLockFile::load(lock_path).map_err(|e| vec![e])? {
+        Some(lock) => lock,
+        None if COMPILED_SPARK_CHECKSUMS.is_empty() => return Ok(()),
+        None => return Err(vec![format!("{} not found but {} spark(s) are compiled in", SPARK_LOCK_FILE, COMPILED_SPARK_CHECKSUMS.len())]),
+    };
+
+    let mut errors = Vec::new();
+
+    for registration in compiled_sparks() {
+        let name = registration.name;
+
+        let Some(entry) = lock.get(name) else {
+            errors.push(format!("Spark '{}' is not recorded in {}", name, SPARK_LOCK_FILE));
+            continue;
+        };
+
+        match COMPILED_SPARK_CHECKSUMS.iter().find(|(compiled, _)| *compiled == name) {
+            Some((_, checksum)) if *checksum == entry.checksum => {}
+            Some((_, checksum)) => errors.push(format!("Spark '{}' was compiled from content {} but {} records {}", name, checksum, SPARK_LOCK_FILE, entry.checksum)),
+            None => errors.push(format!("Spark '{}' has no compiled checksum", name)),
+        }
+
+        let version = manifest_version(registration.manifest);
+        if version != entry.version {
+            errors.push(format!("Spark '{}' is version {} but {} records {}", name, version, SPARK_LOCK_FILE, entry.version));
+        }
+
+        let source = spark_source(configured.get(name));
+        if source != entry.source {
+            errors.push(format!("Spark '{}' is configured from {} but {} records {}", name, source, SPARK_LOCK_FILE, entry.source));
+        }
+    }
+
+    for entry in &lock.sparks {
+        if !compiled_sparks().any(|registration| registration.name == entry.name) {
+            errors.push(format!("{} records spark '{}', which is not compiled into this binary", SPARK_LOCK_FILE, entry.name));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn resolve_commit(spark_dir: &Path) -> Option<String> {
+    if !spark_dir.join(".git").exists() {
+        return None;
+    }
+
+    let output = Command::new("git").arg("-C").arg(spark_dir).arg("rev-parse").arg("HEAD").output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+pub fn update_spark_lock(root: &Path) -> Result<SparkLockFile, String> {
+    let lock_path = root.join(SPARK_LOCK_FILE);
+    let previous = SparkLockFile::load(&lock_path)?.unwrap_or_default();
+
+    let config_path = root.join("Catalyst.toml");
+    let config = fs::read_to_string(&config_path).map_err(|e| format!("Failed to read {}: {}", config_path.display(), e))?;
+    let config = config.parse::<TomlValue>().map_err(|e| format!("Failed to parse {}: {}", config_path.display(), e))?;
+    let sources = config.get("sparks").and_then(|sparks| sparks.as_table()).cloned().unwrap_or_default();
+
+    let sparks_dir = root.join("src/services/sparks");
+    let directories = spark_directories(&sparks_dir).map_err(|e| format!("Failed to scan {}: {}", sparks_dir.display(), e))?;
+
+    let mut lock = SparkLockFile::default();
+
+    for (name, dir) in directories {
+        let checksum = spark_checksum(&dir).map_err(|e| format!("Failed to hash spark '{}': {}", name, e))?;
+        let manifest = fs::read_to_string(dir.join("manifest.toml")).map_err(|e| format!("Failed to read manifest for spark '{}': {}", name, e))?;
+        let source = spark_source(sources.get(&name));
+
+        let commit = resolve_commit(&dir).or_else(|| previous.get(&name).filter(|entry| entry.source == source && entry.checksum == checksum).and_then(|entry| entry.commit.clone()));
+
+        lock.sparks.push(SparkLockEntry {
+            name,
+            source,
+            version: manifest_version(&manifest),
+            commit,
+            checksum,
+        });
+    }
+
+    lock.save(&lock_path)?;
+    Ok(lock)
+}
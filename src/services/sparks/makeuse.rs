@@ -3,10 +3,11 @@ use std::{
     sync::{Mutex, OnceLock},
 };
 
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_path_to_error::Segment;
 use toml::Value as TomlValue;
 
-use crate::cata_log;
+use crate::{cata_log, meltdown::*, services::sparks::registry::compiled_spark, structs::TenantSparkSettings};
 
 static TEMPLATE_COMPONENTS: OnceLock<Mutex<TemplateComponents>> = OnceLock::new();
 
@@ -60,6 +61,10 @@ pub fn register_spark_overrides(spark_name: &str, overrides: TomlValue) {
     }
 }
 
+pub fn get_spark_manifest(spark_name: &str) -> Option<TomlValue> {
+    SPARK_CONFIGS.get()?.lock().ok()?.get(spark_name).map(|config| config.manifest.clone())
+}
+
 pub fn get_spark_config<T: std::str::FromStr>(spark_name: &str, key: &str) -> Option<T>
 where
     T::Err: std::fmt::Debug,
@@ -93,19 +98,20 @@ where
     T::Err: std::fmt::Debug,
 {
     match value {
-        TomlValue::Table(map) => {
-            if let Some(value) = map.get(key) {
-                match value {
-                    TomlValue::String(s) => s.parse::<T>().ok(),
-                    TomlValue::Integer(i) => i.to_string().parse::<T>().ok(),
-                    TomlValue::Float(f) => f.to_string().parse::<T>().ok(),
-                    TomlValue::Boolean(b) => b.to_string().parse::<T>().ok(),
-                    _ => None,
-                }
-            } else {
-                None
-            }
-        }
+        TomlValue::Table(map) => map.get(key).and_then(scalar_value::<T>),
+        _ => None,
+    }
+}
+
+fn scalar_value<T: std::str::FromStr>(value: &TomlValue) -> Option<T>
+where
+    T::Err: std::fmt::Debug,
+{
+    match value {
+        TomlValue::String(s) => s.parse::<T>().ok(),
+        TomlValue::Integer(i) => i.to_string().parse::<T>().ok(),
+        TomlValue::Float(f) => f.to_string().parse::<T>().ok(),
+        TomlValue::Boolean(b) => b.to_string().parse::<T>().ok(),
         _ => None,
     }
 }
@@ -142,6 +148,179 @@ pub fn get_spark_all_config(spark_name: &str) -> HashMap<String, String> {
     result
 }
 
+fn env_prefix(spark_name: &str) -> String {
+    format!("{}_", spark_name.to_uppercase())
+}
+
+fn merge_toml(base: &mut TomlValue, overlay: &TomlValue) {
+    match (base, overlay) {
+        (TomlValue::Table(base), TomlValue::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(key) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+fn parse_env_value(raw: &str) -> TomlValue {
+    toml::from_str::<toml::Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| TomlValue::String(raw.to_string()))
+}
+
+fn env_overrides(spark_name: &str) -> Vec<(Vec<String>, String, TomlValue)> {
+    let prefix = env_prefix(spark_name);
+
+    std::env::vars()
+        .filter_map(|(key, value)| {
+            let path = key.strip_prefix(&prefix)?.to_lowercase().split("__").map(str::to_string).collect::<Vec<_>>();
+            Some((path, key, parse_env_value(&value)))
+        })
+        .collect()
+}
+
+fn value_at<'a>(value: &'a TomlValue, path: &[String]) -> Option<&'a TomlValue> {
+    path.iter().try_fold(value, |current, key| current.get(key.as_str()))
+}
+
+pub fn merged_spark_config(spark_name: &str) -> TomlValue {
+    let mut merged = TomlValue::Table(toml::map::Map::new());
+
+    if let Some(configs) = SPARK_CONFIGS.get() {
+        if let Ok(configs) = configs.lock() {
+            if let Some(config) = configs.get(spark_name) {
+                merge_toml(&mut merged, &config.defaults);
+
+                if config.overrides.is_table() {
+                    merge_toml(&mut merged, &config.overrides);
+                }
+            }
+        }
+    }
+
+    for (path, _, value) in env_overrides(spark_name) {
+        let overlay = path.iter().rev().fold(value, |inner, key| TomlValue::Table(toml::map::Map::from_iter([(key.clone(), inner)])));
+        merge_toml(&mut merged, &overlay);
+    }
+
+    merged
+}
+
+fn config_source(spark_name: &str, path: &[String]) -> String {
+    if let Some((_, key, _)) = env_overrides(spark_name).into_iter().find(|(env_path, _, _)| path.starts_with(env_path)) {
+        return format!("environment variable {}", key);
+    }
+
+    if let Some(configs) = SPARK_CONFIGS.get() {
+        if let Ok(configs) = configs.lock() {
+            if let Some(config) = configs.get(spark_name) {
+                if value_at(&config.overrides, path).is_some() {
+                    return format!("Catalyst.toml [sparks.{}]", spark_name);
+                }
+
+                if value_at(&config.defaults, path).is_some() {
+                    return "manifest.toml [config.defaults]".to_string();
+                }
+            }
+        }
+    }
+
+    "not set".to_string()
+}
+
+pub fn spark_config<T: DeserializeOwned>(spark_name: &str) -> Result<T, String> {
+    deserialize_spark_config(spark_name, merged_spark_config(spark_name), |keys| config_source(spark_name, keys))
+}
+
+fn deserialize_spark_config<T: DeserializeOwned>(spark_name: &str, config: TomlValue, source: impl Fn(&[String]) -> String) -> Result<T, String> {
+    serde_path_to_error::deserialize(config).map_err(|error| {
+        let keys = error
+            .path()
+            .iter()
+            .map_while(|segment| match segment {
+                Segment::Map { key } => Some(key.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let path = match error.path().to_string().as_str() {
+            "." => format!("sparks.{}", spark_name),
+            path => format!("sparks.{}.{}", spark_name, path),
+        };
+
+        let message = error.inner().to_string();
+        format!("Invalid configuration at `{}` ({}): {}", path, source(&keys), message.lines().next().unwrap_or_default())
+    })
+}
+
+fn tenant_overrides<'a>(settings: impl IntoIterator<Item = (&'a str, &'a str)>) -> TomlValue {
+    settings.into_iter().fold(TomlValue::Table(toml::map::Map::new()), |mut overrides, (key, value)| {
+        let overlay = key
+            .split('.')
+            .rev()
+            .fold(parse_env_value(value), |inner, key| TomlValue::Table(toml::map::Map::from_iter([(key.to_string(), inner)])));
+        merge_toml(&mut overrides, &overlay);
+        overrides
+    })
+}
+
+pub fn validate_tenant_settings<'a>(spark_name: &str, settings: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<(), String> {
+    match compiled_spark(spark_name).and_then(|registration| registration.validate_tenant_config) {
+        Some(validate) => validate(spark_name, &tenant_overrides(settings)),
+        None => Ok(()),
+    }
+}
+
+async fn load_tenant_overrides(spark_name: &str, tenant_name: &str) -> Result<TomlValue, MeltDown> {
+    let settings = TenantSparkSettings::for_spark(spark_name, tenant_name).await?;
+    Ok(tenant_overrides(settings.iter().map(|setting| (setting.key.as_str(), setting.value.as_str()))))
+}
+
+pub async fn merged_spark_config_for_tenant(spark_name: &str, tenant_name: &str) -> Result<TomlValue, MeltDown> {
+    let mut merged = merged_spark_config(spark_name);
+    merge_toml(&mut merged, &load_tenant_overrides(spark_name, tenant_name).await?);
+    Ok(merged)
+}
+
+pub async fn get_spark_config_for_tenant<T: std::str::FromStr>(spark_name: &str, tenant_name: &str, key: &str) -> Option<T>
+where
+    T::Err: std::fmt::Debug,
+{
+    match load_tenant_overrides(spark_name, tenant_name).await {
+        Ok(overrides) => {
+            let path = key.split('.').map(str::to_string).collect::<Vec<_>>();
+            if let Some(value) = value_at(&overrides, &path).and_then(scalar_value::<T>) {
+                return Some(value);
+            }
+        }
+        Err(e) => cata_log!(Error, format!("Failed to load spark '{}' settings for tenant {}: {}", spark_name, tenant_name, e.log_message())),
+    }
+
+    get_spark_config(spark_name, key)
+}
+
+pub async fn spark_config_for_tenant<T: DeserializeOwned>(spark_name: &str, tenant_name: &str) -> Result<T, String> {
+    let overrides = load_tenant_overrides(spark_name, tenant_name).await.map_err(|e| e.log_message())?;
+    spark_config_with_overrides(spark_name, &overrides, &format!("tenant {} override", tenant_name))
+}
+
+pub fn spark_config_with_overrides<T: DeserializeOwned>(spark_name: &str, overrides: &TomlValue, label: &str) -> Result<T, String> {
+    let mut merged = merged_spark_config(spark_name);
+    merge_toml(&mut merged, overrides);
+
+    deserialize_spark_config(spark_name, merged, |keys| match value_at(overrides, keys) {
+        Some(_) if !keys.is_empty() => label.to_string(),
+        _ => config_source(spark_name, keys),
+    })
+}
+
 struct SparkConfig {
     manifest: TomlValue,
     defaults: TomlValue,
@@ -1,8 +1,24 @@
+pub mod assets;
+pub mod checksum;
+pub mod dependencies;
+pub mod lifecycle;
+pub mod lock;
 pub mod makeuse;
 pub mod registry;
+pub mod scaffold;
+pub mod status;
+pub mod testing;
 
+pub use assets::*;
+pub use checksum::*;
+pub use dependencies::*;
+pub use lifecycle::*;
+pub use lock::*;
 pub use makeuse::*;
 pub use registry::*;
+pub use scaffold::*;
+pub use status::*;
+pub use testing::*;
 
 //here you include the modules you want to expose to the outside world
 
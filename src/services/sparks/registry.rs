@@ -1,9 +1,14 @@
 use std::{
-    collections::HashMap,
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    future::Future,
+    pin::Pin,
     sync::{Mutex, OnceLock},
+    time::Duration,
 };
 
-use rocket::{Build, Rocket};
+use rocket::{Build, Orbit, Rocket};
+use toml::Value as TomlValue;
 
 use crate::{cata_log, services::*};
 
@@ -59,6 +64,90 @@ impl SparkRegistry {
 
 static SPARK_REGISTRY: OnceLock<Mutex<SparkRegistry>> = OnceLock::new();
 
+pub struct SparkRegistration {
+    pub name: &'static str,
+    pub create: fn() -> Box<dyn Spark>,
+    pub manifest: &'static str,
+    pub validate_config: Option<fn(&str) -> Result<(), String>>,
+    pub validate_tenant_config: Option<fn(&str, &TomlValue) -> Result<(), String>>,
+    pub assets: Option<SparkAssetSource>,
+}
+
+pub struct SparkAssetSource {
+    pub get: fn(&str) -> Option<Cow<'static, [u8]>>,
+    pub list: fn() -> Vec<Cow<'static, str>>,
+}
+
+inventory::collect!(SparkRegistration);
+
+#[macro_export]
+macro_rules! register_spark {
+    (@config) => {
+        None
+    };
+    (@config $config:ty) => {
+        Some(|name| $crate::services::sparks::makeuse::spark_config::<$config>(name).map(|_| ()))
+    };
+    (@tenant_config) => {
+        None
+    };
+    (@tenant_config $config:ty) => {
+        Some(|name, overrides| $crate::services::sparks::makeuse::spark_config_with_overrides::<$config>(name, overrides, "tenant override").map(|_| ()))
+    };
+    (@assets) => {
+        None
+    };
+    (@assets $folder:tt) => {{
+        #[derive(rust_embed::RustEmbed)]
+        #[folder = $folder]
+        #[include = "templates/**/*"]
+        #[include = "public/**/*"]
+        #[include = "locale/*"]
+        struct EmbeddedSparkAssets;
+
+        Some($crate::services::sparks::registry::SparkAssetSource {
+            get: |path| <EmbeddedSparkAssets as rust_embed::RustEmbed>::get(path).map(|file| file.data),
+            list: || <EmbeddedSparkAssets as rust_embed::RustEmbed>::iter().collect(),
+        })
+    }};
+    ($name:literal, $creator:path, $manifest:expr $(, config = $config:ty)? $(, assets = $folder:tt)? $(,)?) => {
+        inventory::submit! {
+            $crate::services::sparks::registry::SparkRegistration {
+                name: $name,
+                create: $creator,
+                manifest: $manifest,
+                validate_config: $crate::register_spark!(@config $($config)?),
+                validate_tenant_config: $crate::register_spark!(@tenant_config $($config)?),
+                assets: $crate::register_spark!(@assets $($folder)?),
+            }
+        }
+    };
+}
+
+pub fn compiled_sparks() -> impl Iterator<Item = &'static SparkRegistration> {
+    inventory::iter::<SparkRegistration>.into_iter()
+}
+
+pub fn compiled_spark(name: &str) -> Option<&'static SparkRegistration> {
+    compiled_sparks().find(|registration| registration.name == name)
+}
+
+pub type SparkHookError = Box<dyn std::error::Error + Send + Sync>;
+
+pub type SparkJobFuture = Pin<Box<dyn Future<Output = Result<(), SparkHookError>> + Send>>;
+
+pub struct SparkMigration {
+    pub version: &'static str,
+    pub up: &'static str,
+}
+
+pub struct SparkJob {
+    pub name: &'static str,
+    pub interval: Duration,
+    pub run: fn() -> SparkJobFuture,
+}
+
+#[rocket::async_trait]
 pub trait Spark: Send + Sync + 'static {
     fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>>;
 
@@ -82,6 +171,24 @@ pub trait Spark: Send + Sync + 'static {
     fn is_available(&self) -> bool {
         self.is_compatible_with_environment() && self.is_enabled()
     }
+
+    fn migrations(&self) -> Vec<SparkMigration> {
+        Vec::new()
+    }
+
+    fn scheduled_jobs(&self) -> Vec<SparkJob> {
+        Vec::new()
+    }
+
+    async fn on_tenant_provisioned(&self, _tenant: &str) -> Result<(), SparkHookError> {
+        Ok(())
+    }
+
+    async fn on_liftoff(&self, _rocket: &Rocket<Orbit>) {
+    }
+
+    async fn on_shutdown(&self) {
+    }
 }
 
 pub fn register_spark(name: &str, creator: fn() -> Box<dyn Spark>) {
@@ -102,8 +209,12 @@ pub fn init_registry() {
 pub fn register_by_name(name: &str) -> bool {
     cata_log!(Debug, format!("Attempting to register spark '{}'", name));
 
-    match name {
-        _ => {
+    match compiled_spark(name) {
+        Some(registration) => {
+            register_spark(registration.name, registration.create);
+            true
+        }
+        None => {
             cata_log!(Warning, format!("Cannot register unknown spark '{}'", name));
             false
         }
@@ -166,6 +277,16 @@ pub fn get_available_sparks() -> Vec<String> {
     }
 }
 
+pub fn available_spark_instances() -> Vec<Box<dyn Spark>> {
+    match SPARK_REGISTRY.get() {
+        Some(registry) => {
+            let registry_guard = registry.lock().unwrap();
+            registry_guard.get_compatible_sparks().iter().filter_map(|name| registry_guard.get_creator(name).map(|creator| creator())).collect()
+        }
+        None => Vec::new(),
+    }
+}
+
 pub trait SparkExtension {
     fn sparks<I>(self, spark_names: I) -> Self
     where
@@ -187,8 +308,19 @@ impl SparkExtension for Rocket<Build> {
         let registry_guard = registry.lock().unwrap();
         let mut rocket = self;
 
-        for name_ref in spark_names.into_iter() {
-            let name = name_ref.as_ref();
+        let dependency_order = registry_guard.get_compatible_sparks();
+        let mut requested = spark_names.into_iter().map(|name_ref| name_ref.as_ref().to_string()).collect::<Vec<_>>();
+        requested.sort_by_key(|name| dependency_order.iter().position(|ordered| ordered == name).unwrap_or(usize::MAX));
+
+        let mut initialized = lifecycle::active_sparks().iter().map(|spark| spark.name().to_string()).collect::<HashSet<_>>();
+
+        for name in requested.iter().map(String::as_str) {
+            let dependencies = makeuse::get_spark_manifest(name).and_then(|manifest| dependencies::manifest_dependencies(&manifest).ok()).unwrap_or_default();
+
+            if let Some(missing) = dependencies.keys().find(|dependency| *dependency != dependencies::CATALYST_DEPENDENCY && !initialized.contains(*dependency)) {
+                cata_log!(Error, format!("Skipping spark '{}': dependency '{}' is not initialized", name, missing));
+                continue;
+            }
 
             match registry_guard.get_creator(name) {
                 Some(creator) => {
@@ -197,6 +329,8 @@ impl SparkExtension for Rocket<Build> {
                         Ok(_) => {
                             cata_log!(Info, format!("Spark '{}' initialized successfully", spark.name()));
                             rocket = spark.attach_to_rocket(rocket);
+                            initialized.insert(name.to_string());
+                            lifecycle::activate(spark);
                         }
                         Err(e) => {
                             cata_log!(Error, format!("Failed to initialize spark '{}': {}", spark.name(), e));
@@ -204,8 +338,7 @@ impl SparkExtension for Rocket<Build> {
                     }
                 }
                 None => {
-                    let available_sparks = get_available_sparks();
-                    cata_log!(Error, format!("Unknown spark: '{}'. Available sparks: {:?}", name, available_sparks));
+                    cata_log!(Error, format!("Unknown spark: '{}'. Available sparks: {:?}", name, dependency_order));
                 }
             }
         }
@@ -219,4 +352,4 @@ impl SparkExtension for Rocket<Build> {
         cata_log!(Debug, format!("Spark list: {:?}", available));
         self.sparks(available)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,170 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use toml::Value as TomlValue;
+
+use crate::services::sparks::dependencies::CATALYST_VERSION;
+
+const SPARKS_DIR: &str = "src/services/sparks";
+const CATALYST_CONFIG: &str = "Catalyst.toml";
+
+fn validate_spark_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Spark name cannot be empty".to_string());
+    }
+
+    if !name.chars().next().is_some_and(|c| c.is_ascii_lowercase()) {
+        return Err(format!("Spark name '{}' must start with a lowercase letter", name));
+    }
+
+    if !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+        return Err(format!("Spark name '{}' can only contain lowercase letters, digits and underscores", name));
+    }
+
+    Ok(())
+}
+
+fn type_name(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map(|first| first.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+        })
+        .collect()
+}
+
+fn manifest_template(name: &str, description: &str, author: &str) -> String {
+    let catalyst_requirement = CATALYST_VERSION.split('.').take(2).collect::<Vec<_>>().join(".");
+
+    format!(
+        r#"[spark]
+name = "{name}"
+version = "0.1.0"
+description = {description}
+author = {author}
+
+[dependencies]
+catalyst = ">={catalyst_requirement}"
+
+[config.defaults]
+greeting = "Hello from {name}"
+"#,
+        description = TomlValue::String(description.to_string()),
+        author = TomlValue::String(author.to_string()),
+    )
+}
+
+fn module_template(name: &str, description: &str) -> String {
+    let type_name = type_name(name);
+
+    format!(
+        r#"use rocket::{{get, http::Status, routes, Build, Rocket}};
+use serde::Deserialize;
+
+use crate::{{middleware::SparkEnabled, services::sparks::registry::Spark}};
+
+crate::register_spark!("{name}", create_spark, include_str!("manifest.toml"), config = {type_name}Config);
+crate::spark_name!({type_name}Spark, "{name}");
+
+#[derive(Deserialize, Debug)]
+pub struct {type_name}Config {{
+    pub greeting: String,
+}}
+
+pub struct {type_name};
+
+#[rocket::async_trait]
+impl Spark for {type_name} {{
+    fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {{
+        Ok(())
+    }}
+
+    fn attach_to_rocket(&self, rocket: Rocket<Build>) -> Rocket<Build> {{
+        rocket.mount("/", routes![get_index])
+    }}
+
+    fn name(&self) -> &str {{
+        "{name}"
+    }}
+
+    fn description(&self) -> &str {{
+        {description:?}
+    }}
+}}
+
+#[get("/<_tenant>/{name}")]
+async fn get_index(_tenant: &str, spark: SparkEnabled<{type_name}Spark>) -> Result<String, Status> {{
+    spark.config::<{type_name}Config>().await.map(|config| config.greeting).map_err(|_| Status::InternalServerError)
+}}
+
+pub fn create_spark() -> Box<dyn Spark> {{
+    Box::new({type_name})
+}}
+"#
+    )
+}
+
+fn register_module(sparks_mod: &Path, name: &str) -> Result<(), String> {
+    let content = fs::read_to_string(sparks_mod).map_err(|e| format!("Failed to read {}: {}", sparks_mod.display(), e))?;
+    let declaration = format!("pub mod {};", name);
+
+    if content.lines().any(|line| line.trim() == declaration) {
+        return Ok(());
+    }
+
+    let separator = if content.ends_with('\n') { "" } else { "\n" };
+    fs::write(sparks_mod, format!("{}{}{}\n", content, separator, declaration)).map_err(|e| format!("Failed to update {}: {}", sparks_mod.display(), e))
+}
+
+fn register_in_config(config_path: &Path, name: &str) -> Result<(), String> {
+    let content = fs::read_to_string(config_path).map_err(|e| format!("Failed to read {}: {}", config_path.display(), e))?;
+
+    let config: TomlValue = toml::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", config_path.display(), e))?;
+    if config.get("sparks").and_then(|sparks| sparks.get(name)).is_some() {
+        return Ok(());
+    }
+
+    let entry = format!("{} = \"local\"", name);
+    let mut lines = content.lines().map(str::to_string).collect::<Vec<_>>();
+
+    match lines.iter().position(|line| line.trim() == "[sparks]") {
+        Some(index) => lines.insert(index + 1, entry),
+        None => {
+            lines.push(String::new());
+            lines.push("[sparks]".to_string());
+            lines.push(entry);
+        }
+    }
+
+    fs::write(config_path, lines.join("\n") + "\n").map_err(|e| format!("Failed to update {}: {}", config_path.display(), e))
+}
+
+pub fn scaffold_spark(root: &Path, name: &str, description: &str, author: &str) -> Result<Vec<PathBuf>, String> {
+    validate_spark_name(name)?;
+
+    let sparks_dir = root.join(SPARKS_DIR);
+    let spark_dir = sparks_dir.join(name);
+
+    if spark_dir.exists() {
+        return Err(format!("Spark directory {} already exists", spark_dir.display()));
+    }
+
+    fs::create_dir_all(&spark_dir).map_err(|e| format!("Failed to create {}: {}", spark_dir.display(), e))?;
+
+    let files = [
+        (spark_dir.join("manifest.toml"), manifest_template(name, description, author)),
+        (spark_dir.join("mod.rs"), module_template(name, description)),
+    ];
+
+    for (path, content) in &files {
+        fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+
+    register_module(&sparks_dir.join("mod.rs"), name)?;
+    register_in_config(&root.join(CATALYST_CONFIG), name)?;
+
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
@@ -0,0 +1,129 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use toml::Value as TomlValue;
+
+use crate::services::sparks::{
+    lifecycle::active_sparks,
+    makeuse::merged_spark_config,
+    registry::{compiled_spark, compiled_sparks, SparkRegistration},
+};
+
+pub const MASKED_VALUE: &str = "********";
+const SECRET_KEY_MARKERS: [&str; 6] = ["secret", "password", "token", "key", "credential", "dsn"];
+
+static URL_USERINFO: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\b([a-z][a-z0-9+.-]*://)[^/?#@\s]+@").unwrap());
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SparkStatus {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub author: String,
+    pub compatible: bool,
+    pub enabled: bool,
+    pub active: bool,
+    pub config: Value,
+}
+
+pub fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    SECRET_KEY_MARKERS.iter().any(|marker| key.contains(marker))
+}
+
+pub fn manifest_secrets(manifest: &TomlValue) -> Vec<String> {
+    manifest
+        .get("config")
+        .and_then(|config| config.get("secrets"))
+        .and_then(|secrets| secrets.as_array())
+        .map(|secrets| secrets.iter().filter_map(|secret| secret.as_str()).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+pub fn is_spark_secret(spark_name: &str, key: &str) -> bool {
+    let declared = compiled_spark(spark_name)
+        .and_then(|registration| registration.manifest.parse::<TomlValue>().ok())
+        .map(|manifest| manifest_secrets(&manifest))
+        .unwrap_or_default();
+
+    is_declared_secret(&declared, key) || is_secret_key(key)
+}
+
+fn is_declared_secret(declared: &[String], path: &str) -> bool {
+    declared.iter().any(|secret| path == secret || path.strip_prefix(secret.as_str()).is_some_and(|rest| rest.starts_with('.')))
+}
+
+pub fn mask_url_credentials(value: &str) -> String {
+    URL_USERINFO.replace_all(value, format!("${{1}}{}@", MASKED_VALUE)).into_owned()
+}
+
+pub fn mask_secrets(value: TomlValue, declared: &[String]) -> Value {
+    mask_secrets_at(value, declared, "")
+}
+
+fn mask_secrets_at(value: TomlValue, declared: &[String], path: &str) -> Value {
+    if !path.is_empty() && is_declared_secret(declared, path) {
+        return Value::String(MASKED_VALUE.to_string());
+    }
+
+    match value {
+        TomlValue::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| {
+                    let child_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                    let masked = match value {
+                        TomlValue::Table(_) => mask_secrets_at(value, declared, &child_path),
+                        _ if is_secret_key(&key) => Value::String(MASKED_VALUE.to_string()),
+                        _ => mask_secrets_at(value, declared, &child_path),
+                    };
+                    (key, masked)
+                })
+                .collect(),
+        ),
+        TomlValue::Array(items) => Value::Array(items.into_iter().map(|item| mask_secrets_at(item, declared, path)).collect()),
+        TomlValue::String(s) => Value::String(mask_url_credentials(&s)),
+        TomlValue::Integer(i) => Value::from(i),
+        TomlValue::Float(f) => Value::from(f),
+        TomlValue::Boolean(b) => Value::Bool(b),
+        TomlValue::Datetime(d) => Value::String(d.to_string()),
+    }
+}
+
+fn manifest_field(manifest: &TomlValue, field: &str) -> String {
+    manifest.get("spark").and_then(|spark| spark.get(field)).and_then(|value| value.as_str()).unwrap_or_default().to_string()
+}
+
+fn spark_status(registration: &SparkRegistration, active: &[String]) -> SparkStatus {
+    let manifest = registration.manifest.parse::<TomlValue>().unwrap_or(TomlValue::Table(Default::default()));
+    let spark = (registration.create)();
+
+    let description = match manifest_field(&manifest, "description") {
+        description if description.is_empty() => spark.description().to_string(),
+        description => description,
+    };
+
+    SparkStatus {
+        name: registration.name.to_string(),
+        version: manifest_field(&manifest, "version"),
+        description,
+        author: manifest_field(&manifest, "author"),
+        compatible: spark.is_compatible_with_environment(),
+        enabled: spark.is_enabled(),
+        active: active.iter().any(|name| name == registration.name),
+        config: mask_secrets(merged_spark_config(registration.name), &manifest_secrets(&manifest)),
+    }
+}
+
+pub fn spark_statuses() -> Vec<SparkStatus> {
+    let active = active_sparks().iter().map(|spark| spark.name().to_string()).collect::<Vec<_>>();
+
+    let mut statuses = compiled_sparks().map(|registration| spark_status(registration, &active)).collect::<Vec<_>>();
+    statuses.sort_by(|a, b| a.name.cmp(&b.name));
+    statuses
+}
+
+pub fn is_active_spark(name: &str) -> bool {
+    active_sparks().iter().any(|spark| spark.name() == name)
+}
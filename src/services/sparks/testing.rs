@@ -0,0 +1,142 @@
+use rocket::local::asynchronous::Client;
+use rocket_dyn_templates::Template;
+use toml::Value as TomlValue;
+
+use crate::{
+    meltdown::*,
+    routes::spark_asset_routes,
+    services::sparks::{
+        assets,
+        lifecycle::SparkLifecycleFairing,
+        makeuse,
+        registry::{self, SparkExtension},
+    },
+    vessel::{
+        database::{drop_tenant_database, provision_scratch_tenant_database},
+        structs::{NewTenant, NewVessel, Tenant, Vessel},
+    },
+};
+
+pub struct SparkTestHarness {
+    spark_name: String,
+    tenant_name: String,
+    overrides: toml::Table,
+}
+
+impl SparkTestHarness {
+    pub fn new(spark_name: &str) -> Self {
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+
+        Self {
+            spark_name: spark_name.to_string(),
+            tenant_name: format!("spark_test_{}_{}", spark_name, &suffix[..8]),
+            overrides: toml::Table::new(),
+        }
+    }
+
+    pub fn config(mut self, key: &str, value: impl Into<TomlValue>) -> Self {
+        let path = key.split('.').collect::<Vec<_>>();
+        insert_override(&mut self.overrides, &path, value.into());
+        self
+    }
+
+    pub fn tenant_name(&self) -> &str {
+        &self.tenant_name
+    }
+
+    fn register(&self) -> Result<(), MeltDown> {
+        let Some(registration) = registry::compiled_spark(&self.spark_name) else {
+            return Err(MeltDown::new(MeltType::ConfigurationError, format!("Spark '{}' is not compiled into this binary", self.spark_name)));
+        };
+
+        let manifest: TomlValue = toml::from_str(registration.manifest).map_err(|e| MeltDown::new(MeltType::ConfigurationError, format!("Invalid manifest for spark '{}': {}", self.spark_name, e)))?;
+        let defaults = manifest.get("config").and_then(|c| c.get("defaults")).cloned().unwrap_or_else(|| TomlValue::Table(toml::Table::new()));
+
+        registry::init_registry();
+        makeuse::init_spark_configs();
+        makeuse::init_template_registry();
+        makeuse::register_spark_manifest(&self.spark_name, manifest, defaults);
+        makeuse::register_spark_overrides(&self.spark_name, TomlValue::Table(self.overrides.clone()));
+
+        if let Some(validate_config) = registration.validate_config {
+            validate_config(&self.spark_name).map_err(|e| MeltDown::new(MeltType::ConfigurationError, e))?;
+        }
+
+        registry::register_by_name(&self.spark_name);
+        Ok(())
+    }
+
+    pub async fn client(&self) -> Result<Client, MeltDown> {
+        self.register()?;
+
+        let rocket = rocket::build()
+            .sparks([&self.spark_name])
+            .mount("/", spark_asset_routes())
+            .attach(Template::custom(assets::register_spark_templates))
+            .attach(SparkLifecycleFairing);
+
+        self.register_scratch_tenant().await?;
+        provision_scratch_tenant_database(&self.tenant_name).await?;
+
+        Client::tracked(rocket)
+            .await
+            .map_err(|e| MeltDown::new(MeltType::ConfigurationError, format!("Failed to launch spark '{}': {}", self.spark_name, e)))
+    }
+
+    pub async fn cleanup(self) -> Result<(), MeltDown> {
+        drop_tenant_database(&self.tenant_name).await?;
+
+        // Deleting the scratch vessel cascades to its tenant row and spark toggles.
+        match Vessel::find_by_name(&self.vessel_name()).await? {
+            Some(vessel) => Vessel::delete(vessel.id).await,
+            None => Ok(()),
+        }
+    }
+
+    fn vessel_name(&self) -> String {
+        format!("{}_vessel", self.tenant_name)
+    }
+
+    async fn register_scratch_tenant(&self) -> Result<(), MeltDown> {
+        if Tenant::find_by_name(&self.tenant_name).await?.is_some() {
+            return Ok(());
+        }
+
+        let vessel = Vessel::create(NewVessel {
+            name: self.vessel_name(),
+            display_name: format!("Spark test vessel for {}", self.spark_name),
+            username: self.vessel_name(),
+            email: format!("{}@spark-test.invalid", self.vessel_name()),
+            password_hash: String::new(),
+            first_name: "Spark".to_string(),
+            last_name: "Test".to_string(),
+        })
+        .await?;
+
+        Tenant::insert(NewTenant {
+            vessel_id: vessel.id,
+            name: self.tenant_name.clone(),
+            display_name: self.tenant_name.clone(),
+        })
+        .await?;
+
+        Ok(())
+    }
+}
+
+fn insert_override(table: &mut toml::Table, path: &[&str], value: TomlValue) {
+    match path {
+        [] => {}
+        [key] => {
+            table.insert(key.to_string(), value);
+        }
+        [segment, rest @ ..] => match table.entry(*segment).or_insert_with(|| TomlValue::Table(toml::Table::new())) {
+            TomlValue::Table(child) => insert_override(child, rest, value),
+            entry => {
+                let mut child = toml::Table::new();
+                insert_override(&mut child, rest, value);
+                *entry = TomlValue::Table(child);
+            }
+        },
+    }
+}
@@ -0,0 +1,91 @@
+use diesel::prelude::*;
+use rocket::form::FromForm;
+use serde::{Deserialize, Serialize};
+
+use crate::database::schema::invitations;
+
+pub const DEFAULT_INVITATION_LIFETIME_DAYS: i64 = 7;
+pub const MAX_INVITATION_LIFETIME_DAYS: i64 = 90;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationMode {
+    #[default]
+    Open,
+    InviteOnly,
+    AllowedDomains,
+    Disabled,
+}
+
+impl RegistrationMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RegistrationMode::Open => "open",
+            RegistrationMode::InviteOnly => "invite_only",
+            RegistrationMode::AllowedDomains => "allowed_domains",
+            RegistrationMode::Disabled => "disabled",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "open" => Some(RegistrationMode::Open),
+            "invite_only" => Some(RegistrationMode::InviteOnly),
+            "allowed_domains" => Some(RegistrationMode::AllowedDomains),
+            "disabled" => Some(RegistrationMode::Disabled),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RegistrationPolicy {
+    pub mode: RegistrationMode,
+    pub allowed_domains: Vec<String>,
+}
+
+#[derive(Queryable, Identifiable, Debug, Serialize, Deserialize, Clone)]
+#[diesel(table_name = invitations)]
+pub struct Invitation {
+    pub id: i32,
+    pub email: String,
+    pub role: String,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub invited_by: Option<i32>,
+    pub accepted_by: Option<i32>,
+    pub expires_at: i64,
+    pub accepted_at: Option<i64>,
+    pub revoked_at: Option<i64>,
+    pub created_at: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = invitations)]
+pub struct NewInvitation {
+    pub email: String,
+    pub role: String,
+    pub token_hash: String,
+    pub invited_by: Option<i32>,
+    pub expires_at: i64,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct InvitationForm {
+    pub email: String,
+    pub role: String,
+    pub expires_in_days: Option<i64>,
+    pub authenticity_token: String,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct InvitationRevokeForm {
+    pub authenticity_token: String,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct RegistrationPolicyForm {
+    pub mode: String,
+    pub allowed_domains: String,
+    pub authenticity_token: String,
+}
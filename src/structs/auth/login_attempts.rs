@@ -0,0 +1,60 @@
+use diesel::prelude::*;
+use rocket::form::FromForm;
+use serde::{Deserialize, Serialize};
+
+use crate::database::schema::login_attempts;
+
+pub const LOGIN_ATTEMPTS_PAGE_LIMIT: i64 = 200;
+
+#[derive(Queryable, Identifiable, Debug, Serialize, Deserialize, Clone)]
+#[diesel(table_name = login_attempts)]
+pub struct LoginAttempt {
+    pub id: i32,
+    pub username: String,
+    pub user_id: Option<i32>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub success: bool,
+    pub failure_reason: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = login_attempts)]
+pub struct NewLoginAttempt {
+    pub username: String,
+    pub user_id: Option<i32>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub success: bool,
+    pub failure_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoginFailureReason {
+    UnknownUser,
+    InvalidPassword,
+    LockedOut,
+    SsoRejected,
+    InvalidTwoFactor,
+}
+
+impl LoginFailureReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoginFailureReason::UnknownUser => "unknown_user",
+            LoginFailureReason::InvalidPassword => "invalid_password",
+            LoginFailureReason::LockedOut => "locked_out",
+            LoginFailureReason::SsoRejected => "sso_rejected",
+            LoginFailureReason::InvalidTwoFactor => "invalid_two_factor",
+        }
+    }
+}
+
+#[derive(FromForm, Deserialize, Serialize, Debug, Clone, Default)]
+pub struct LoginAttemptFilter {
+    pub username: Option<String>,
+    pub ip_address: Option<String>,
+    pub outcome: Option<String>,
+    pub since_hours: Option<i64>,
+}
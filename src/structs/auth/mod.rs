@@ -1,5 +1,21 @@
 pub mod api;
+pub mod invitations;
+pub mod login_attempts;
+pub mod oidc;
+pub mod roles;
+pub mod tenant_settings;
+pub mod tenant_spark_settings;
+pub mod two_factor;
+pub mod user_tokens;
 pub mod users;
 
 pub use api::*;
+pub use invitations::*;
+pub use login_attempts::*;
+pub use oidc::*;
+pub use roles::*;
+pub use tenant_settings::*;
+pub use tenant_spark_settings::*;
+pub use two_factor::*;
+pub use user_tokens::*;
 pub use users::*;
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+use rocket::form::FromForm;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::database::schema::{oidc_providers, user_identities};
+
+pub const OIDC_STATE_COOKIE: &str = "oidc_state";
+pub const DEFAULT_OIDC_SCOPES: &str = "openid email profile";
+
+#[derive(Queryable, Identifiable, Debug, Serialize, Deserialize, Clone)]
+#[diesel(table_name = oidc_providers)]
+pub struct OidcProvider {
+    pub id: i32,
+    pub slug: String,
+    pub display_name: String,
+    pub issuer: String,
+    pub client_id: String,
+    #[serde(skip_serializing)]
+    pub client_secret: String,
+    pub scopes: String,
+    pub role_claim: Option<String>,
+    pub role_mapping: String,
+    pub default_role: String,
+    pub auto_provision: bool,
+    pub enabled: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub link_existing_accounts: bool,
+}
+
+#[derive(Insertable, AsChangeset)]
+#[diesel(table_name = oidc_providers)]
+pub struct NewOidcProvider {
+    pub slug: String,
+    pub display_name: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scopes: String,
+    pub role_claim: Option<String>,
+    pub role_mapping: String,
+    pub default_role: String,
+    pub auto_provision: bool,
+    pub enabled: bool,
+    pub link_existing_accounts: bool,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct OidcProviderForm {
+    pub slug: String,
+    pub display_name: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scopes: String,
+    pub role_claim: String,
+    pub role_mapping: String,
+    pub default_role: String,
+    pub auto_provision: Option<bool>,
+    pub enabled: Option<bool>,
+    pub link_existing_accounts: Option<bool>,
+    pub authenticity_token: String,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct OidcProviderDeleteForm {
+    pub authenticity_token: String,
+}
+
+#[derive(Queryable, Identifiable, Debug, Serialize, Deserialize, Clone)]
+#[diesel(table_name = user_identities)]
+pub struct UserIdentity {
+    pub id: i32,
+    pub user_id: i32,
+    pub provider_id: i32,
+    pub subject: String,
+    pub email: Option<String>,
+    pub created_at: i64,
+    pub last_login_at: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = user_identities)]
+pub struct NewUserIdentity {
+    pub user_id: i32,
+    pub provider_id: i32,
+    pub subject: String,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcDiscovery {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcTokenResponse {
+    pub id_token: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct OidcPendingFlow {
+    pub tenant_name: String,
+    pub provider_slug: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub name: Option<String>,
+    pub preferred_username: Option<String>,
+    pub nonce: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, JsonValue>,
+}
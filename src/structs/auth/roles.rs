@@ -0,0 +1,80 @@
+use diesel::prelude::*;
+use rocket::form::FromForm;
+use serde::{Deserialize, Serialize};
+
+use crate::database::schema::{permissions, role_permissions, roles};
+
+pub const ADMIN_ACCESS: &str = "admin.access";
+pub const USERS_MANAGE: &str = "users.manage";
+pub const ROLES_MANAGE: &str = "roles.manage";
+pub const SECURITY_MANAGE: &str = "security.manage";
+pub const AUDIT_VIEW: &str = "audit.view";
+pub const API_KEYS_MANAGE: &str = "api_keys.manage";
+pub const POSTS_VIEW: &str = "posts.view";
+pub const POSTS_CREATE: &str = "posts.create";
+pub const POSTS_EDIT: &str = "posts.edit";
+pub const POSTS_DELETE: &str = "posts.delete";
+
+pub const DEFAULT_USER_ROLE: &str = "user";
+
+#[derive(Queryable, Identifiable, Debug, Serialize, Deserialize, Clone)]
+#[diesel(table_name = roles)]
+pub struct Roles {
+    pub id: i32,
+    pub name: String,
+    pub description: String,
+    pub is_system: bool,
+    pub hidden: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = roles)]
+pub struct NewRole {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Queryable, Identifiable, Debug, Serialize, Deserialize, Clone)]
+#[diesel(table_name = permissions)]
+pub struct Permissions {
+    pub id: i32,
+    pub name: String,
+    pub description: String,
+    pub created_at: i64,
+}
+
+#[derive(Queryable, Insertable, Debug, Clone)]
+#[diesel(table_name = role_permissions)]
+pub struct RolePermissions {
+    pub role_id: i32,
+    pub permission_id: i32,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RoleSummary {
+    pub role: Roles,
+    pub permissions: Vec<String>,
+    pub user_count: i64,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct RoleForm {
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<String>,
+    pub authenticity_token: String,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct RoleUpdateForm {
+    pub description: String,
+    pub permissions: Vec<String>,
+    pub authenticity_token: String,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct RoleDeleteForm {
+    pub authenticity_token: String,
+}
@@ -0,0 +1,25 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::database::schema::tenant_settings;
+
+pub const REQUIRE_ADMIN_TWO_FACTOR: &str = "security.require_admin_two_factor";
+pub const REGISTRATION_MODE: &str = "registration.mode";
+pub const REGISTRATION_ALLOWED_DOMAINS: &str = "registration.allowed_domains";
+
+#[derive(Queryable, Identifiable, Debug, Serialize, Deserialize, Clone)]
+#[diesel(table_name = tenant_settings)]
+pub struct TenantSettings {
+    pub id: i32,
+    pub key: String,
+    pub value: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = tenant_settings)]
+pub struct NewTenantSetting {
+    pub key: String,
+    pub value: String,
+}
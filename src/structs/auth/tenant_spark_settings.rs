@@ -0,0 +1,32 @@
+use diesel::prelude::*;
+use rocket::form::FromForm;
+use serde::{Deserialize, Serialize};
+
+use crate::database::schema::tenant_spark_settings;
+
+#[derive(Queryable, Identifiable, Debug, Serialize, Deserialize, Clone)]
+#[diesel(table_name = tenant_spark_settings)]
+pub struct TenantSparkSettings {
+    pub id: i32,
+    pub spark: String,
+    pub key: String,
+    pub value: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = tenant_spark_settings)]
+pub struct NewTenantSparkSetting {
+    pub spark: String,
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(FromForm, Debug)]
+pub struct TenantSparkSettingForm {
+    pub tenant: String,
+    pub key: String,
+    pub value: String,
+    pub authenticity_token: String,
+}
@@ -0,0 +1,60 @@
+use diesel::prelude::*;
+use rocket::form::FromForm;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::database::schema::user_two_factor;
+
+pub const MAX_TWO_FACTOR_ATTEMPTS: i64 = 5;
+
+#[derive(Queryable, Identifiable, Debug, Clone)]
+#[diesel(table_name = user_two_factor)]
+pub struct UserTwoFactor {
+    pub id: i32,
+    pub user_id: i32,
+    pub secret: String,
+    pub enabled: bool,
+    pub recovery_codes: JsonValue,
+    pub last_used_step: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = user_two_factor)]
+pub struct NewUserTwoFactor {
+    pub user_id: i32,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TwoFactorRequirement {
+    NotRequired,
+    Verify,
+    Enrol,
+}
+
+#[derive(FromForm, Serialize, Deserialize, Clone)]
+pub struct TwoFactorCodeForm {
+    pub code: String,
+    pub authenticity_token: String,
+}
+
+#[derive(FromForm, Serialize, Deserialize, Clone)]
+pub struct TwoFactorDisableForm {
+    pub password: String,
+    pub code: String,
+    pub authenticity_token: String,
+}
+
+#[derive(FromForm, Serialize, Deserialize, Clone)]
+pub struct TwoFactorPolicyForm {
+    pub require_admin_two_factor: Option<bool>,
+    pub authenticity_token: String,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct ApiTwoFactorRequest {
+    pub two_factor_token: String,
+    pub code: String,
+}
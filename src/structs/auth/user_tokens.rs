@@ -0,0 +1,66 @@
+use diesel::prelude::*;
+use rocket::form::FromForm;
+use serde::{Deserialize, Serialize};
+
+use crate::database::schema::user_tokens;
+
+#[derive(Queryable, Identifiable, Debug, Clone)]
+#[diesel(table_name = user_tokens)]
+pub struct UserToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub purpose: String,
+    pub token_hash: String,
+    pub expires_at: i64,
+    pub used_at: Option<i64>,
+    pub created_at: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = user_tokens)]
+pub struct NewUserToken {
+    pub user_id: i32,
+    pub purpose: String,
+    pub token_hash: String,
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenPurpose {
+    PasswordReset,
+    EmailVerification,
+}
+
+impl TokenPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenPurpose::PasswordReset => "password_reset",
+            TokenPurpose::EmailVerification => "email_verification",
+        }
+    }
+
+    pub fn lifetime_secs(&self) -> i64 {
+        match self {
+            TokenPurpose::PasswordReset => 60 * 60,
+            TokenPurpose::EmailVerification => 60 * 60 * 48,
+        }
+    }
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct ForgotPasswordForm {
+    pub email: String,
+    pub authenticity_token: String,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct ResetPasswordForm {
+    pub password: String,
+    pub confirm_password: String,
+    pub authenticity_token: String,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct ResendVerificationForm {
+    pub authenticity_token: String,
+}
@@ -18,6 +18,7 @@ pub struct Users {
     pub should_change_password: bool,
     pub created_at: i64,
     pub updated_at: i64,
+    pub email_verified_at: Option<i64>,
 }
 
 #[derive(Insertable)]
@@ -29,6 +30,7 @@ pub struct NewUser {
     pub email: Option<String>,
     pub password_hash: String,
     pub role: String,
+    pub should_change_password: bool,
 }
 
 #[derive(FromForm, Serialize, Deserialize, Clone)]
@@ -48,10 +50,102 @@ pub struct RegisterForm {
     pub password: String,
     pub confirm_password: String,
     pub authenticity_token: String,
+    pub invitation: Option<String>,
 }
 
 #[derive(FromForm, Deserialize, Serialize)]
 pub struct UpdatePassword<'a> {
     pub password: &'a str,
     pub confirm_password: &'a str,
+    pub authenticity_token: &'a str,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct ProfileForm {
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub authenticity_token: String,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct ProfilePasswordForm {
+    pub current_password: String,
+    pub password: String,
+    pub confirm_password: String,
+    pub authenticity_token: String,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct LogoutEverywhereForm {
+    pub authenticity_token: String,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct ApiLoginRequest {
+    pub username: String,
+    pub password: String,
+    pub remember_me: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct ApiRefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct ApiChangePasswordRequest {
+    pub current_password: String,
+    pub password: String,
+    pub confirm_password: String,
+}
+
+pub const ADMIN_USERS_PAGE_SIZE: i64 = 25;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct UserListItem {
+    pub id: i32,
+    pub username: String,
+    pub email: Option<String>,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: String,
+    pub active: bool,
+    pub should_change_password: bool,
+    pub email_verified: bool,
+    pub created_at: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct UserPage {
+    pub users: Vec<UserListItem>,
+    pub query: String,
+    pub page: i64,
+    pub total: i64,
+    pub total_pages: i64,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct AdminCreateUserForm {
+    pub username: String,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: String,
+    pub password: String,
+    pub authenticity_token: String,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct AdminUpdateUserForm {
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: String,
+    pub authenticity_token: String,
+}
+
+#[derive(FromForm, Deserialize, Serialize, Clone)]
+pub struct AdminUserActionForm {
+    pub authenticity_token: String,
 }
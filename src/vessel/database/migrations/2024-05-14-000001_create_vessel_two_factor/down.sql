@@ -0,0 +1 @@
+DROP TABLE vessel_two_factor;
\ No newline at end of file
@@ -0,0 +1,10 @@
+CREATE TABLE vessel_two_factor (
+    id SERIAL PRIMARY KEY,
+    vessel_id INTEGER NOT NULL UNIQUE REFERENCES vessels(id) ON DELETE CASCADE,
+    secret TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    recovery_codes JSONB NOT NULL DEFAULT '[]',
+    last_used_step BIGINT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1 @@
+DROP TABLE vessel_login_attempts;
\ No newline at end of file
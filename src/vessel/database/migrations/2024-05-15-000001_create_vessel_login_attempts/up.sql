@@ -0,0 +1,12 @@
+CREATE TABLE vessel_login_attempts (
+    id SERIAL PRIMARY KEY,
+    username TEXT NOT NULL,
+    vessel_id INTEGER REFERENCES vessels(id) ON DELETE SET NULL,
+    ip_address TEXT,
+    user_agent TEXT,
+    success BOOLEAN NOT NULL,
+    failure_reason TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX vessel_login_attempts_username_created_at_idx ON vessel_login_attempts (username, created_at);
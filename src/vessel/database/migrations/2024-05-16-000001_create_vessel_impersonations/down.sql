@@ -0,0 +1 @@
+DROP TABLE vessel_impersonations;
\ No newline at end of file
@@ -0,0 +1,15 @@
+CREATE TABLE vessel_impersonations (
+    id SERIAL PRIMARY KEY,
+    vessel_id INTEGER NOT NULL REFERENCES vessels(id) ON DELETE CASCADE,
+    tenant_name TEXT NOT NULL,
+    user_id INTEGER NOT NULL,
+    username TEXT NOT NULL,
+    token_jti TEXT NOT NULL UNIQUE,
+    ip_address TEXT,
+    user_agent TEXT,
+    started_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP NOT NULL,
+    ended_at TIMESTAMP
+);
+
+CREATE INDEX vessel_impersonations_vessel_id_started_at_idx ON vessel_impersonations (vessel_id, started_at);
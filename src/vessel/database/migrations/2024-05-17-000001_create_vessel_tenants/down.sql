@@ -0,0 +1 @@
+DROP TABLE tenants;
\ No newline at end of file
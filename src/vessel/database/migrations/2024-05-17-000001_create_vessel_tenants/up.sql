@@ -0,0 +1,12 @@
+CREATE TABLE tenants (
+    id SERIAL PRIMARY KEY,
+    vessel_id INTEGER NOT NULL REFERENCES vessels(id) ON DELETE CASCADE,
+    name TEXT NOT NULL UNIQUE,
+    display_name TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX tenants_vessel_id_idx ON tenants (vessel_id);
+
+INSERT INTO tenants (vessel_id, name, display_name, created_at)
+SELECT id, name, display_name, created_at FROM vessels;
@@ -0,0 +1 @@
+DROP TABLE tenant_sparks;
\ No newline at end of file
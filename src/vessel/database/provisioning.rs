@@ -3,41 +3,111 @@ use std::{fs, io::Read, path::Path, process::Command};
 use diesel_async::AsyncPgConnection;
 use tokio::task;
 
-use crate::{cata_log, meltdown::*, vessel::structs::Vessel};
+use crate::{cata_log, meltdown::*, services::sparks::lifecycle, vessel::structs::Vessel};
 
 pub async fn provision_vessel_database(name: &str, username: &str, email: &str, password_hash: &str, display_name: &str) -> Result<(), MeltDown> {
     let tenant_name = name.to_string();
 
-    create_database(&tenant_name).await?;
+    create_database(&tenant_name, true).await?;
 
     run_migrations(&tenant_name).await?;
 
     seed_database(&tenant_name).await?;
 
     // Get the vessel to access first_name and last_name
-    let vessel = match Vessel::find_by_name(name).await {
-        Ok(Some(vessel)) => vessel,
+    match Vessel::find_by_name(name).await {
+        // Use vessel data for admin user creation
+        Ok(Some(vessel)) => create_admin_user_from_vessel(&tenant_name, &vessel).await?,
         Ok(None) => {
             cata_log!(Warning, format!("Vessel '{}' not found for admin user creation", name));
             // Fall back to display_name
             create_admin_user(&tenant_name, username, email, password_hash, display_name).await?;
-            return Ok(());
         }
         Err(e) => {
             cata_log!(Error, format!("Error finding vessel: {}", e.log_message()));
             create_admin_user(&tenant_name, username, email, password_hash, display_name).await?;
-            return Ok(());
         }
-    };
+    }
 
-    // Use vessel data for admin user creation
-    create_admin_user_from_vessel(&tenant_name, &vessel).await?;
+    lifecycle::tenant_provisioned(&tenant_name).await?;
 
     cata_log!(Info, format!("Successfully provisioned vessel database '{}'", tenant_name));
     Ok(())
 }
 
-async fn create_database(name: &String) -> Result<(), MeltDown> {
+pub async fn provision_tenant_database(tenant_name: &str, vessel: &Vessel) -> Result<(), MeltDown> {
+    let tenant_name = tenant_name.to_string();
+
+    create_database(&tenant_name, false).await?;
+
+    if let Err(e) = populate_tenant_database(&tenant_name, vessel).await {
+        cata_log!(Warning, format!("Dropping partly provisioned tenant database '{}'", tenant_name));
+        if let Err(drop_error) = drop_tenant_database(&tenant_name).await {
+            cata_log!(Error, format!("Failed to drop partly provisioned tenant database '{}': {}", tenant_name, drop_error.log_message()));
+        }
+        return Err(e);
+    }
+
+    cata_log!(Info, format!("Successfully provisioned tenant database '{}' for vessel '{}'", tenant_name, vessel.name));
+    Ok(())
+}
+
+async fn populate_tenant_database(tenant_name: &String, vessel: &Vessel) -> Result<(), MeltDown> {
+    run_migrations(tenant_name).await?;
+
+    seed_database(tenant_name).await?;
+
+    create_admin_user_from_vessel(tenant_name, vessel).await?;
+
+    lifecycle::tenant_provisioned(tenant_name).await
+}
+
+pub async fn provision_scratch_tenant_database(tenant_name: &str) -> Result<(), MeltDown> {
+    let tenant_name = tenant_name.to_string();
+
+    create_database(&tenant_name, true).await?;
+
+    run_migrations(&tenant_name).await?;
+
+    seed_database(&tenant_name).await?;
+
+    lifecycle::tenant_provisioned(&tenant_name).await?;
+
+    cata_log!(Info, format!("Successfully provisioned scratch tenant database '{}'", tenant_name));
+    Ok(())
+}
+
+pub async fn drop_tenant_database(tenant_name: &str) -> Result<(), MeltDown> {
+    let db_name = tenant_name.to_string();
+    cata_log!(Info, format!("Dropping database '{}'", db_name));
+
+    task::spawn_blocking(move || {
+        let output = Command::new("dropdb").arg("-U").arg("postgres").arg("--if-exists").arg("--force").arg(&db_name).output();
+
+        match output {
+            Ok(output) if output.status.success() => {
+                cata_log!(Info, format!("Database '{}' dropped successfully", db_name));
+                Ok(())
+            }
+            Ok(output) => {
+                let error = String::from_utf8_lossy(&output.stderr);
+                cata_log!(Error, format!("Failed to drop database '{}': {}", db_name, error));
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to drop database").with_context("error", error.to_string()))
+            }
+            Err(e) => {
+                cata_log!(Error, format!("Failed to execute dropdb command: {}", e));
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to drop database").with_context("error", e.to_string()))
+            }
+        }
+    })
+    .await
+    .unwrap_or_else(|e| {
+        cata_log!(Error, format!("Task to drop database failed: {}", e));
+        Err(MeltDown::new(MeltType::DatabaseError, "Task to drop database failed").with_context("error", e.to_string()))
+    })
+}
+
+async fn create_database(name: &String, allow_existing: bool) -> Result<(), MeltDown> {
     let db_name = name.clone();
     cata_log!(Info, format!("Creating database '{}'", db_name));
 
@@ -52,9 +122,12 @@ async fn create_database(name: &String) -> Result<(), MeltDown> {
                 } else {
                     let error = String::from_utf8_lossy(&output.stderr);
 
-                    if error.contains("already exists") {
+                    if error.contains("already exists") && allow_existing {
                         cata_log!(Warning, format!("Database '{}' already exists", db_name));
                         Ok(())
+                    } else if error.contains("already exists") {
+                        cata_log!(Error, format!("Refusing to provision into existing database '{}'", db_name));
+                        Err(MeltDown::new(MeltType::ValidationFailed, format!("Database '{}' already exists", db_name)).with_user_message("A database with this name already exists. Please choose another name."))
                     } else {
                         cata_log!(Error, format!("Failed to create database '{}': {}", db_name, error));
                         Err(MeltDown::new(MeltType::DatabaseError, "Failed to create database").with_context("error", error.to_string()))
@@ -15,3 +15,73 @@ diesel::table! {
         last_name -> Text,
     }
 }
+
+diesel::table! {
+    tenants (id) {
+        id -> Int4,
+        vessel_id -> Int4,
+        name -> Text,
+        display_name -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    tenant_sparks (id) {
+        id -> Int4,
+        tenant_id -> Int4,
+        spark -> Text,
+        enabled -> Bool,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    vessel_two_factor (id) {
+        id -> Int4,
+        vessel_id -> Int4,
+        secret -> Text,
+        enabled -> Bool,
+        recovery_codes -> Jsonb,
+        last_used_step -> Nullable<Int8>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    vessel_impersonations (id) {
+        id -> Int4,
+        vessel_id -> Int4,
+        tenant_name -> Text,
+        user_id -> Int4,
+        username -> Text,
+        token_jti -> Text,
+        ip_address -> Nullable<Text>,
+        user_agent -> Nullable<Text>,
+        started_at -> Timestamp,
+        expires_at -> Timestamp,
+        ended_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    vessel_login_attempts (id) {
+        id -> Int4,
+        username -> Text,
+        vessel_id -> Nullable<Int4>,
+        ip_address -> Nullable<Text>,
+        user_agent -> Nullable<Text>,
+        success -> Bool,
+        failure_reason -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::joinable!(tenant_sparks -> tenants (tenant_id));
+diesel::joinable!(tenants -> vessels (vessel_id));
+diesel::joinable!(vessel_impersonations -> vessels (vessel_id));
+diesel::joinable!(vessel_login_attempts -> vessels (vessel_id));
+diesel::joinable!(vessel_two_factor -> vessels (vessel_id));
+
+diesel::allow_tables_to_appear_in_same_query!(vessels, tenants, tenant_sparks, vessel_impersonations, vessel_login_attempts, vessel_two_factor,);
@@ -25,7 +25,7 @@ impl<'r> FromRequest<'r> for VesselHomeGuard {
                     return Error((Status::Forbidden, error));
                 }
 
-                if jwt.get_role() == "vessel" {
+                if jwt.is_vessel_owner() {
                     Success(VesselHomeGuard)
                 } else {
                     let error = MeltDown::new(MeltType::Forbidden, "Insufficient permissions to access vessel area");
@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use crate::{
+    cata_log,
+    meltdown::*,
+    middleware::ClientInfo,
+    services::default::jwt_service,
+    structs::Users,
+    vessel::{
+        database::{db::establish_connection, schema::vessel_impersonations},
+        structs::{NewVesselImpersonation, Tenant, Vessel, VesselImpersonation},
+    },
+};
+
+impl VesselImpersonation {
+    pub async fn start(vessel: &Vessel, tenant_name: &str, user_id: i32, client: &ClientInfo) -> Result<(String, VesselImpersonation), MeltDown> {
+        if Tenant::find_for_vessel(vessel.id, tenant_name).await?.is_none() {
+            return Err(MeltDown::new(MeltType::Forbidden, format!("Vessel {} does not own tenant {}", vessel.id, tenant_name)).with_user_message("Tenant not found."));
+        }
+
+        let user = Users::get_user_by_id(user_id, tenant_name).await?;
+
+        if !user.active || !Users::is_admin(user.id, tenant_name).await? {
+            return Err(MeltDown::new(MeltType::Forbidden, format!("User {} is not an active tenant admin", user.id))
+                .with_context("tenant", tenant_name.to_string())
+                .with_user_message("Only active tenant administrators can be impersonated."));
+        }
+
+        let device_info = Some(format!("Impersonation by vessel owner {} at {}", vessel.username, Utc::now().to_rfc3339()));
+        let (token, claims) = jwt_service::generate_impersonation_token(&user, tenant_name, &vessel.username, device_info)?;
+
+        let impersonation = NewVesselImpersonation {
+            vessel_id: vessel.id,
+            tenant_name: tenant_name.to_string(),
+            user_id: user.id,
+            username: user.username.clone(),
+            token_jti: claims.jti.clone(),
+            ip_address: client.ip_address.clone(),
+            user_agent: client.user_agent.clone(),
+            expires_at: DateTime::from_timestamp(claims.exp as i64, 0).unwrap_or_default().naive_utc(),
+        };
+
+        let mut conn = establish_connection().await?;
+
+        match diesel::insert_into(vessel_impersonations::table)
+            .values(&impersonation)
+            .returning(VesselImpersonation::as_returning())
+            .get_result(&mut conn)
+            .await
+        {
+            Ok(record) => {
+                cata_log!(
+                    Warning,
+                    format!("Vessel owner {} started impersonating {} (tenant: {}, jti: {})", vessel.username, user.username, tenant_name, claims.jti)
+                );
+                Ok((token, record))
+            }
+            Err(e) => {
+                let error_message = format!("Error recording impersonation: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to record impersonation").with_context("error", &error_message))
+            }
+        }
+    }
+
+    pub async fn end(token_jti: &str) -> Result<(), MeltDown> {
+        let mut conn = establish_connection().await?;
+
+        match diesel::update(
+            vessel_impersonations::table
+                .filter(vessel_impersonations::token_jti.eq(token_jti))
+                .filter(vessel_impersonations::ended_at.is_null()),
+        )
+        .set(vessel_impersonations::ended_at.eq(Some(Utc::now().naive_utc())))
+        .execute(&mut conn)
+        .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let error_message = format!("Error ending impersonation: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to end impersonation").with_context("error", &error_message))
+            }
+        }
+    }
+
+    pub async fn recent_for_vessel(vessel_id: i32, limit: i64) -> Result<Vec<VesselImpersonation>, MeltDown> {
+        let mut conn = establish_connection().await?;
+
+        match vessel_impersonations::table
+            .filter(vessel_impersonations::vessel_id.eq(vessel_id))
+            .order(vessel_impersonations::started_at.desc())
+            .limit(limit)
+            .select(VesselImpersonation::as_select())
+            .load(&mut conn)
+            .await
+        {
+            Ok(impersonations) => Ok(impersonations),
+            Err(e) => {
+                let error_message = format!("Error loading impersonations: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to load impersonations").with_context("error", &error_message))
+            }
+        }
+    }
+}
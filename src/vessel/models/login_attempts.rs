@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use crate::{
+    cata_log,
+    meltdown::*,
+    middleware::ClientInfo,
+    services::default::lockout_service,
+    structs::LoginFailureReason,
+    vessel::{
+        database::{db::establish_connection, schema::vessel_login_attempts},
+        structs::{NewVesselLoginAttempt, VesselLoginAttempt},
+    },
+};
+
+impl VesselLoginAttempt {
+    pub async fn log_attempt(username: &str, vessel_id: Option<i32>, failure: Option<LoginFailureReason>, client: &ClientInfo) {
+        let attempt = NewVesselLoginAttempt {
+            username: username.to_string(),
+            vessel_id,
+            ip_address: client.ip_address.clone(),
+            user_agent: client.user_agent.clone(),
+            success: failure.is_none(),
+            failure_reason: failure.map(|reason| reason.as_str().to_string()),
+        };
+
+        let mut conn = match establish_connection().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                cata_log!(Error, format!("Failed to record vessel login attempt for {}: {}", username, e.log_message()));
+                return;
+            }
+        };
+
+        if let Err(e) = diesel::insert_into(vessel_login_attempts::table).values(&attempt).execute(&mut conn).await {
+            cata_log!(Error, format!("Failed to record vessel login attempt for {}: {}", username, e));
+        }
+    }
+
+    pub async fn lockout_remaining(username: &str) -> Result<Option<i64>, MeltDown> {
+        let settings = lockout_service::lockout_settings();
+        let now = Utc::now().timestamp();
+        let mut conn = establish_connection().await?;
+
+        let last_success = match vessel_login_attempts::table
+            .filter(vessel_login_attempts::username.eq(username))
+            .filter(vessel_login_attempts::success.eq(true))
+            .select(diesel::dsl::max(vessel_login_attempts::created_at))
+            .first::<Option<chrono::NaiveDateTime>>(&mut conn)
+            .await
+        {
+            Ok(last_success) => last_success.map(|timestamp| timestamp.and_utc().timestamp()),
+            Err(e) => {
+                let error_message = format!("Error loading last vessel login: {}", e);
+                cata_log!(Error, &error_message);
+                return Err(MeltDown::new(MeltType::DatabaseError, "Failed to check login attempts").with_context("error", &error_message));
+            }
+        };
+
+        let window_start = DateTime::from_timestamp(lockout_service::failure_window_start(&settings, last_success, now), 0).unwrap_or_default().naive_utc();
+
+        match vessel_login_attempts::table
+            .filter(vessel_login_attempts::username.eq(username))
+            .filter(vessel_login_attempts::success.eq(false))
+            .filter(vessel_login_attempts::failure_reason.ne(LoginFailureReason::LockedOut.as_str()))
+            .filter(vessel_login_attempts::created_at.gt(window_start))
+            .select(vessel_login_attempts::created_at)
+            .load::<chrono::NaiveDateTime>(&mut conn)
+            .await
+        {
+            Ok(failures) => {
+                let failures: Vec<i64> = failures.iter().map(|timestamp| timestamp.and_utc().timestamp()).collect();
+                Ok(lockout_service::lockout_remaining_with(&settings, &failures, now))
+            }
+            Err(e) => {
+                let error_message = format!("Error loading vessel login failures: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to check login attempts").with_context("error", &error_message))
+            }
+        }
+    }
+
+    pub async fn recent_for_username(username: &str, limit: i64) -> Result<Vec<VesselLoginAttempt>, MeltDown> {
+        let mut conn = establish_connection().await?;
+
+        match vessel_login_attempts::table
+            .filter(vessel_login_attempts::username.eq(username))
+            .order(vessel_login_attempts::created_at.desc())
+            .limit(limit)
+            .select(VesselLoginAttempt::as_select())
+            .load(&mut conn)
+            .await
+        {
+            Ok(attempts) => Ok(attempts),
+            Err(e) => {
+                let error_message = format!("Error loading vessel login attempts: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to load login attempts").with_context("error", &error_message))
+            }
+        }
+    }
+}
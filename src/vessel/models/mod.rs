@@ -1,3 +1,13 @@
+mod impersonation;
+mod login_attempts;
+mod tenant;
+mod tenant_spark;
+mod two_factor;
 mod vessel;
 
+pub use impersonation::*;
+pub use login_attempts::*;
+pub use tenant::*;
+pub use tenant_spark::*;
+pub use two_factor::*;
 pub use vessel::*;
@@ -0,0 +1,169 @@
+use std::env;
+
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use crate::{
+    cata_log,
+    meltdown::*,
+    vessel::{
+        database::{db::establish_connection, provision_tenant_database, schema::tenants},
+        structs::{NewTenant, Tenant, TenantForm, Vessel},
+    },
+};
+
+const RESERVED_DATABASE_NAMES: [&str; 4] = ["postgres", "template0", "template1", "vessel"];
+
+impl Tenant {
+    pub fn validate_name(name: &str) -> Result<(), MeltDown> {
+        if name.is_empty() {
+            return Err(MeltDown::new(MeltType::ValidationFailed, "Tenant name cannot be empty."));
+        }
+
+        if !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(MeltDown::new(MeltType::ValidationFailed, "Tenant name can only contain letters, numbers, and underscores."));
+        }
+
+        if name.chars().next().map_or(false, |c| c.is_numeric()) {
+            return Err(MeltDown::new(MeltType::ValidationFailed, "Tenant name must start with a letter or underscore."));
+        }
+
+        if Self::reserved_names().iter().any(|reserved| reserved.eq_ignore_ascii_case(name)) {
+            return Err(MeltDown::new(MeltType::ValidationFailed, format!("Tenant name '{}' is reserved", name)).with_user_message("This tenant name is reserved. Please choose another name."));
+        }
+
+        Ok(())
+    }
+
+    fn reserved_names() -> Vec<String> {
+        let configured = ["DATABASE_URL", "VESSEL_DATABASE_URL"]
+            .iter()
+            .filter_map(|key| env::var(key).ok())
+            .filter_map(|url| url.split('?').next().and_then(|url| url.rsplit_once('/')).map(|(_, database)| database.to_string()))
+            .filter(|database| !database.is_empty());
+
+        RESERVED_DATABASE_NAMES.iter().map(|name| name.to_string()).chain(configured).collect()
+    }
+
+    pub async fn find_by_name(name: &str) -> Result<Option<Tenant>, MeltDown> {
+        let mut conn = establish_connection().await?;
+
+        match tenants::table.filter(tenants::name.eq(name)).select(Tenant::as_select()).first(&mut conn).await.optional() {
+            Ok(tenant) => Ok(tenant),
+            Err(e) => {
+                let error_message = format!("Error finding tenant by name: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to find tenant by name").with_context("error", &error_message))
+            }
+        }
+    }
+
+    pub async fn find_for_vessel(vessel_id: i32, name: &str) -> Result<Option<Tenant>, MeltDown> {
+        let mut conn = establish_connection().await?;
+
+        match tenants::table
+            .filter(tenants::vessel_id.eq(vessel_id))
+            .filter(tenants::name.eq(name))
+            .select(Tenant::as_select())
+            .first(&mut conn)
+            .await
+            .optional()
+        {
+            Ok(tenant) => Ok(tenant),
+            Err(e) => {
+                let error_message = format!("Error finding tenant for vessel: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to find tenant for vessel").with_context("error", &error_message))
+            }
+        }
+    }
+
+    pub async fn all() -> Result<Vec<Tenant>, MeltDown> {
+        let mut conn = establish_connection().await?;
+
+        match tenants::table.order(tenants::id.asc()).select(Tenant::as_select()).load(&mut conn).await {
+            Ok(tenants) => Ok(tenants),
+            Err(e) => {
+                let error_message = format!("Error loading tenants: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to load tenants").with_context("error", &error_message))
+            }
+        }
+    }
+
+    pub async fn for_vessel(vessel_id: i32) -> Result<Vec<Tenant>, MeltDown> {
+        let mut conn = establish_connection().await?;
+
+        match tenants::table
+            .filter(tenants::vessel_id.eq(vessel_id))
+            .order((tenants::created_at.asc(), tenants::id.asc()))
+            .select(Tenant::as_select())
+            .load(&mut conn)
+            .await
+        {
+            Ok(tenants) => Ok(tenants),
+            Err(e) => {
+                let error_message = format!("Error loading tenants for vessel: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to load tenants").with_context("error", &error_message))
+            }
+        }
+    }
+
+    pub async fn insert(new_tenant: NewTenant) -> Result<Tenant, MeltDown> {
+        let mut conn = establish_connection().await?;
+
+        match diesel::insert_into(tenants::table).values(new_tenant).returning(Tenant::as_returning()).get_result(&mut conn).await {
+            Ok(tenant) => Ok(tenant),
+            Err(e) => {
+                let error_message = format!("Error creating tenant: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to create tenant").with_context("error", &error_message))
+            }
+        }
+    }
+
+    pub async fn delete(id: i32) -> Result<(), MeltDown> {
+        let mut conn = establish_connection().await?;
+
+        match diesel::delete(tenants::table.find(id)).execute(&mut conn).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let error_message = format!("Error deleting tenant: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to delete tenant").with_context("error", &error_message))
+            }
+        }
+    }
+
+    pub async fn create_for_vessel(vessel: &Vessel, form: TenantForm) -> Result<Tenant, MeltDown> {
+        let name = form.name.trim().to_string();
+        Self::validate_name(&name)?;
+
+        let display_name = match form.display_name.trim() {
+            "" => name.clone(),
+            display_name => display_name.to_string(),
+        };
+
+        if Self::find_by_name(&name).await?.is_some() {
+            return Err(MeltDown::new(MeltType::ValidationFailed, "A tenant with this name already exists."));
+        }
+
+        let tenant = Self::insert(NewTenant { vessel_id: vessel.id, name, display_name }).await?;
+
+        cata_log!(Info, format!("Vessel {} created tenant {}, provisioning database", vessel.username, tenant.name));
+
+        if let Err(e) = provision_tenant_database(&tenant.name, vessel).await {
+            cata_log!(Error, format!("Failed to provision database for tenant: {} - {}", tenant.name, e.log_message()));
+            if let Err(delete_error) = Self::delete(tenant.id).await {
+                cata_log!(Error, format!("Failed to remove tenant {} after provisioning failed: {}", tenant.name, delete_error.log_message()));
+            }
+            return Err(match e.melt_type {
+                MeltType::ValidationFailed => e,
+                _ => e.with_user_message("The tenant database could not be provisioned. Please try again."),
+            });
+        }
+
+        Ok(tenant)
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use crate::{
+    cata_log,
+    meltdown::*,
+    vessel::{
+        database::{
+            db::establish_connection,
+            schema::{tenant_sparks, tenants},
+        },
+        structs::{NewTenantSpark, TenantSpark},
+    },
+};
+
+impl TenantSpark {
+    pub async fn for_tenants(tenant_ids: &[i32]) -> Result<Vec<TenantSpark>, MeltDown> {
+        let mut conn = establish_connection().await?;
+
+        match tenant_sparks::table.filter(tenant_sparks::tenant_id.eq_any(tenant_ids)).select(TenantSpark::as_select()).load(&mut conn).await {
+            Ok(settings) => Ok(settings),
+            Err(e) => {
+                let error_message = format!("Error loading tenant sparks: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to load tenant sparks").with_context("error", &error_message))
+            }
+        }
+    }
+
+    pub async fn is_enabled_for_tenant(tenant_name: &str, spark: &str) -> Result<bool, MeltDown> {
+        let mut conn = establish_connection().await?;
+
+        match tenants::table
+            .left_join(tenant_sparks::table.on(tenant_sparks::tenant_id.eq(tenants::id).and(tenant_sparks::spark.eq(spark))))
+            .filter(tenants::name.eq(tenant_name))
+            .select(tenant_sparks::enabled.nullable())
+            .first::<Option<bool>>(&mut conn)
+            .await
+            .optional()
+        {
+            Ok(Some(enabled)) => Ok(enabled.unwrap_or(true)),
+            Ok(None) => {
+                cata_log!(Warning, format!("Spark {} checked for unknown tenant {}", spark, tenant_name));
+                Ok(false)
+            }
+            Err(e) => {
+                let error_message = format!("Error checking spark {} for tenant {}: {}", spark, tenant_name, e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to check tenant spark").with_context("error", &error_message))
+            }
+        }
+    }
+
+    pub async fn set_enabled(tenant_id: i32, spark: &str, enabled: bool) -> Result<TenantSpark, MeltDown> {
+        let mut conn = establish_connection().await?;
+
+        let new_setting = NewTenantSpark {
+            tenant_id,
+            spark: spark.to_string(),
+            enabled,
+        };
+
+        match diesel::insert_into(tenant_sparks::table)
+            .values(&new_setting)
+            .on_conflict((tenant_sparks::tenant_id, tenant_sparks::spark))
+            .do_update()
+            .set((tenant_sparks::enabled.eq(enabled), tenant_sparks::updated_at.eq(Utc::now().naive_utc())))
+            .returning(TenantSpark::as_returning())
+            .get_result(&mut conn)
+            .await
+        {
+            Ok(setting) => Ok(setting),
+            Err(e) => {
+                let error_message = format!("Error updating spark {} for tenant {}: {}", spark, tenant_id, e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to update tenant spark").with_context("error", &error_message))
+            }
+        }
+    }
+}
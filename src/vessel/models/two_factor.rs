@@ -0,0 +1,142 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use crate::{
+    cata_log,
+    meltdown::*,
+    services::default::totp_service::{self, SecondFactorCheck},
+    vessel::{
+        database::{db::establish_connection, schema::vessel_two_factor},
+        structs::{NewVesselTwoFactor, VesselTwoFactor},
+    },
+};
+
+impl VesselTwoFactor {
+    pub async fn find_by_vessel_id(vessel_id: i32) -> Result<Option<VesselTwoFactor>, MeltDown> {
+        let mut conn = establish_connection().await?;
+
+        match vessel_two_factor::table
+            .filter(vessel_two_factor::vessel_id.eq(vessel_id))
+            .select(VesselTwoFactor::as_select())
+            .first(&mut conn)
+            .await
+            .optional()
+        {
+            Ok(two_factor) => Ok(two_factor),
+            Err(e) => {
+                let error_message = format!("Error finding vessel two-factor settings: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to load two-factor settings").with_context("error", &error_message))
+            }
+        }
+    }
+
+    pub async fn is_enabled(vessel_id: i32) -> Result<bool, MeltDown> {
+        Ok(Self::find_by_vessel_id(vessel_id).await?.is_some_and(|two_factor| two_factor.enabled))
+    }
+
+    pub async fn begin_enrolment(vessel_id: i32) -> Result<VesselTwoFactor, MeltDown> {
+        if let Some(existing) = Self::find_by_vessel_id(vessel_id).await? {
+            if existing.enabled {
+                return Err(MeltDown::new(MeltType::ValidationFailed, "Two-factor authentication is already enabled"));
+            }
+            return Ok(existing);
+        }
+
+        let mut conn = establish_connection().await?;
+
+        let new_two_factor = NewVesselTwoFactor {
+            vessel_id,
+            secret: totp_service::generate_totp_secret(),
+        };
+
+        match diesel::insert_into(vessel_two_factor::table)
+            .values(&new_two_factor)
+            .returning(VesselTwoFactor::as_returning())
+            .get_result(&mut conn)
+            .await
+        {
+            Ok(two_factor) => Ok(two_factor),
+            Err(e) => {
+                let error_message = format!("Error starting vessel two-factor enrolment: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to start two-factor enrolment").with_context("error", &error_message))
+            }
+        }
+    }
+
+    pub fn provisioning_uri(&self, account_name: &str) -> Result<String, MeltDown> {
+        totp_service::totp_provisioning_uri(&self.secret, "Catalyst Vessel", account_name)
+    }
+
+    pub async fn confirm_enrolment(vessel_id: i32, code: &str) -> Result<Vec<String>, MeltDown> {
+        let pending = Self::find_by_vessel_id(vessel_id)
+            .await?
+            .ok_or_else(|| MeltDown::new(MeltType::ValidationFailed, "Two-factor enrolment has not been started"))?;
+
+        if pending.enabled {
+            return Err(MeltDown::new(MeltType::ValidationFailed, "Two-factor authentication is already enabled"));
+        }
+
+        let step = totp_service::verify_totp_code(&pending.secret, code, None)?.ok_or_else(|| MeltDown::new(MeltType::ValidationFailed, "Invalid verification code"))?;
+
+        let recovery_codes = totp_service::generate_recovery_codes();
+
+        let mut conn = establish_connection().await?;
+
+        diesel::update(vessel_two_factor::table.find(pending.id))
+            .set((
+                vessel_two_factor::enabled.eq(true),
+                vessel_two_factor::recovery_codes.eq(totp_service::recovery_code_hashes(&recovery_codes)),
+                vessel_two_factor::last_used_step.eq(Some(step)),
+                vessel_two_factor::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(&mut conn)
+            .await
+            .map_err(|e| MeltDown::new(MeltType::DatabaseError, "Failed to enable two-factor authentication").with_context("error", e.to_string()))?;
+
+        Ok(recovery_codes)
+    }
+
+    pub async fn verify(vessel_id: i32, code: &str) -> Result<bool, MeltDown> {
+        let two_factor = match Self::find_by_vessel_id(vessel_id).await? {
+            Some(two_factor) if two_factor.enabled => two_factor,
+            _ => return Ok(false),
+        };
+
+        let mut conn = establish_connection().await?;
+        let now = Utc::now().naive_utc();
+
+        let updated = match totp_service::check_second_factor(&two_factor.secret, &two_factor.recovery_codes, two_factor.last_used_step, code)? {
+            SecondFactorCheck::Totp(step) => {
+                diesel::update(vessel_two_factor::table.find(two_factor.id))
+                    .set((vessel_two_factor::last_used_step.eq(Some(step)), vessel_two_factor::updated_at.eq(now)))
+                    .execute(&mut conn)
+                    .await
+            }
+            SecondFactorCheck::RecoveryCode(remaining) => {
+                cata_log!(Warning, format!("Recovery code used for vessel {}, {} remaining", vessel_id, remaining.len()));
+                diesel::update(vessel_two_factor::table.find(two_factor.id))
+                    .set((vessel_two_factor::recovery_codes.eq(serde_json::json!(remaining)), vessel_two_factor::updated_at.eq(now)))
+                    .execute(&mut conn)
+                    .await
+            }
+            SecondFactorCheck::Invalid => return Ok(false),
+        };
+
+        updated
+            .map(|_| true)
+            .map_err(|e| MeltDown::new(MeltType::DatabaseError, "Failed to record two-factor verification").with_context("error", e.to_string()))
+    }
+
+    pub async fn disable(vessel_id: i32) -> Result<(), MeltDown> {
+        let mut conn = establish_connection().await?;
+
+        diesel::delete(vessel_two_factor::table.filter(vessel_two_factor::vessel_id.eq(vessel_id)))
+            .execute(&mut conn)
+            .await
+            .map(|_| ())
+            .map_err(|e| MeltDown::new(MeltType::DatabaseError, "Failed to disable two-factor authentication").with_context("error", e.to_string()))
+    }
+}
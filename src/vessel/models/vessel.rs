@@ -1,4 +1,3 @@
-use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::Utc;
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
@@ -6,10 +5,17 @@ use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use crate::{
     cata_log,
     meltdown::*,
-    services::default::jwt_service,
+    middleware::{jwt::AuthSystem, ClientInfo},
+    services::default::{
+        jwt_service::{self, LoginStep},
+        lockout_service,
+        logger::Secret,
+        password_service, token_registry,
+    },
+    structs::LoginFailureReason,
     vessel::{
         database::{db::establish_connection, schema::vessels},
-        structs::{NewVessel, Vessel, VesselLoginForm, VesselRegisterForm, VesselResponse},
+        structs::{NewTenant, NewVessel, Tenant, Vessel, VesselLoginAttempt, VesselLoginForm, VesselRegisterForm, VesselResponse, VesselTwoFactor},
     },
 };
 
@@ -27,6 +33,19 @@ impl Vessel {
         }
     }
 
+    pub async fn delete(id: i32) -> Result<(), MeltDown> {
+        let mut conn = establish_connection().await?;
+
+        match diesel::delete(vessels::table.find(id)).execute(&mut conn).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let error_message = format!("Error deleting vessel: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to delete vessel").with_context("error", &error_message))
+            }
+        }
+    }
+
     pub async fn find_by_username(username: &str) -> Result<Option<Vessel>, MeltDown> {
         let mut conn = establish_connection().await?;
 
@@ -67,17 +86,21 @@ impl Vessel {
     }
 
     pub async fn tenant_exists(tenant_name: &str) -> Result<bool, MeltDown> {
-        let result = Self::find_by_name(tenant_name).await?;
+        let result = Tenant::find_by_name(tenant_name).await?;
         Ok(result.is_some())
     }
 
-    pub async fn verify_password(&self, password: &str) -> Result<bool, MeltDown> {
-        cata_log!(Info, format!("Verifying password for vessel: {}", self.username));
+    pub async fn owned_tenant(&self, tenant_name: Option<&str>) -> Result<String, MeltDown> {
+        match tenant_name {
+            Some(tenant_name) if Tenant::find_for_vessel(self.id, tenant_name).await?.is_some() => Ok(tenant_name.to_string()),
+            _ => Ok(self.name.clone()),
+        }
+    }
 
-        let hash_prefix = self.password_hash.chars().take(10).collect::<String>();
-        cata_log!(Info, format!("Stored password hash prefix: {}...", hash_prefix));
+    pub async fn verify_password(&self, password: &str) -> Result<bool, MeltDown> {
+        cata_log!(Debug, format!("Verifying password for vessel: {} (stored hash: {})", self.username, Secret::new(&self.password_hash)));
 
-        match verify(password, &self.password_hash) {
+        match password_service::verify_password_hash(password.to_string(), self.password_hash.clone()).await {
             Ok(valid) => {
                 if valid {
                     cata_log!(Info, "Password hash verification successful");
@@ -94,11 +117,44 @@ impl Vessel {
         }
     }
 
-    pub async fn login_user(login_form: VesselLoginForm) -> Result<(Vessel, jwt_service::TokenPair), MeltDown> {
+    pub async fn rehash_password_if_needed(&mut self, password: &str) -> Result<bool, MeltDown> {
+        if !password_service::needs_rehash(&self.password_hash) {
+            return Ok(false);
+        }
+
+        let password_hash = password_service::hash_password(password.to_string()).await?;
+        let mut conn = establish_connection().await?;
+
+        match diesel::update(vessels::table.find(self.id))
+            .set((vessels::password_hash.eq(&password_hash), vessels::updated_at.eq(Utc::now().naive_utc())))
+            .execute(&mut conn)
+            .await
+        {
+            Ok(_) => {
+                cata_log!(Info, format!("Upgraded password hash for vessel: {}", self.username));
+                self.password_hash = password_hash;
+                Ok(true)
+            }
+            Err(e) => {
+                let error_message = format!("Error updating vessel password hash: {}", e);
+                cata_log!(Error, &error_message);
+                Err(MeltDown::new(MeltType::DatabaseError, "Failed to update vessel password hash").with_context("error", &error_message))
+            }
+        }
+    }
+
+    pub async fn login_user(login_form: VesselLoginForm, client: &ClientInfo) -> Result<(Vessel, LoginStep), MeltDown> {
+        if let Some(remaining) = VesselLoginAttempt::lockout_remaining(&login_form.username).await? {
+            cata_log!(Warning, format!("Vessel login for {} rejected, account locked for another {}s", login_form.username, remaining));
+            VesselLoginAttempt::log_attempt(&login_form.username, None, Some(LoginFailureReason::LockedOut), client).await;
+            return Err(lockout_service::account_locked(remaining));
+        }
+
         let vessel = match Self::find_by_username(&login_form.username).await {
             Ok(Some(vessel)) => vessel,
             Ok(None) => {
                 cata_log!(Warning, format!("Login attempt with invalid username: {}", login_form.username));
+                VesselLoginAttempt::log_attempt(&login_form.username, None, Some(LoginFailureReason::UnknownUser), client).await;
                 return Err(MeltDown::invalid_credentials());
             }
             Err(e) => {
@@ -107,12 +163,9 @@ impl Vessel {
             }
         };
 
-        let pw_len = login_form.password.len();
-        let pw_first = if pw_len > 0 { login_form.password.chars().next().unwrap() } else { '?' };
-        let pw_last = if pw_len > 1 { login_form.password.chars().last().unwrap() } else { '?' };
-        cata_log!(Info, format!("Password input info: length={}, first={}, last={}", pw_len, pw_first, pw_last));
+        cata_log!(Debug, format!("Checking credentials for vessel: {} (password: {})", login_form.username, login_form.password));
 
-        let password_match = match vessel.verify_password(&login_form.password).await {
+        let password_match = match vessel.verify_password(login_form.password.expose()).await {
             Ok(is_match) => {
                 if is_match {
                     cata_log!(Info, format!("Password verification successful for vessel: {}", login_form.username));
@@ -130,16 +183,56 @@ impl Vessel {
 
         if !password_match {
             cata_log!(Warning, format!("Failed login attempt for vessel: {}", login_form.username));
+            VesselLoginAttempt::log_attempt(&login_form.username, Some(vessel.id), Some(LoginFailureReason::InvalidPassword), client).await;
             return Err(MeltDown::invalid_credentials());
         }
 
+        VesselLoginAttempt::log_attempt(&login_form.username, Some(vessel.id), None, client).await;
+
+        let mut vessel = vessel;
+        if let Err(e) = vessel.rehash_password_if_needed(login_form.password.expose()).await {
+            cata_log!(Warning, format!("Failed to upgrade password hash for vessel {}: {}", vessel.username, e.log_message()));
+        }
+
         let remember = login_form.remember_me.unwrap_or(false);
         let device_info = Some(format!("Vessel login at {}", Utc::now().to_rfc3339()));
 
-        let token_pair = jwt_service::generate_token_pair_for_vessel(&vessel, remember, device_info)?;
+        if VesselTwoFactor::is_enabled(vessel.id).await? {
+            let (token, claims) = jwt_service::generate_two_factor_token(&vessel, &vessel.name, remember, device_info)?;
+
+            cata_log!(Info, format!("Vessel {} passed password check, awaiting two-factor verification", vessel.username));
+
+            return Ok((vessel, LoginStep::TwoFactorPending { token, claims, enrolment_required: false }));
+        }
+
+        let token_pair = jwt_service::generate_token_pair(&vessel, &vessel.name, remember, device_info)?;
 
         cata_log!(Info, format!("Vessel {} logged in successfully", vessel.username));
 
+        Ok((vessel, LoginStep::Complete(token_pair)))
+    }
+
+    pub async fn complete_two_factor_login(two_factor_token: &str, code: &str) -> Result<(Vessel, jwt_service::TokenPair), MeltDown> {
+        let claims = jwt_service::validate_two_factor_token(two_factor_token)?;
+
+        if claims.auth_system != AuthSystem::Vessel {
+            return Err(MeltDown::new(MeltType::InvalidToken, "Two-factor token was not issued for the vessel system"));
+        }
+
+        let vessel_id = claims.sub.parse::<i32>().map_err(|_| MeltDown::new(MeltType::InvalidToken, "Invalid user ID in token"))?;
+
+        let vessel = Self::find_by_id(vessel_id).await?.ok_or_else(|| MeltDown::new(MeltType::NotFound, "Vessel"))?;
+
+        if !VesselTwoFactor::verify(vessel.id, code).await? {
+            cata_log!(Warning, format!("Invalid two-factor code for vessel: {}", vessel.username));
+            return Err(MeltDown::new(MeltType::InvalidCredentials, "Invalid two-factor code").with_user_message("Invalid verification code."));
+        }
+
+        let tenant_name = vessel.owned_tenant(claims.tenant_name.as_deref()).await?;
+        let token_pair = jwt_service::generate_token_pair(&vessel, &tenant_name, claims.remember, claims.device_info)?;
+
+        cata_log!(Info, format!("Vessel {} completed two-factor login", vessel.username));
+
         Ok((vessel, token_pair))
     }
 
@@ -148,16 +241,12 @@ impl Vessel {
             return Err(MeltDown::new(MeltType::ValidationFailed, "Passwords do not match."));
         }
 
-        if register_form.name.is_empty() {
-            return Err(MeltDown::new(MeltType::ValidationFailed, "Vessel name cannot be empty."));
-        }
+        password_service::validate_password(register_form.password.expose())?;
 
-        if !register_form.name.chars().all(|c| c.is_alphanumeric() || c == '_') {
-            return Err(MeltDown::new(MeltType::ValidationFailed, "Vessel name can only contain letters, numbers, and underscores."));
-        }
+        Tenant::validate_name(&register_form.name)?;
 
-        if register_form.name.chars().next().map_or(false, |c| c.is_numeric()) {
-            return Err(MeltDown::new(MeltType::ValidationFailed, "Vessel name must start with a letter or underscore."));
+        if Tenant::find_by_name(&register_form.name).await?.is_some() {
+            return Err(MeltDown::new(MeltType::ValidationFailed, "A tenant with this name already exists."));
         }
 
         match Self::find_by_username(&register_form.username).await {
@@ -171,17 +260,10 @@ impl Vessel {
             }
         }
 
-        let pw_len = register_form.password.len();
-        let pw_first = if pw_len > 0 { register_form.password.chars().next().unwrap() } else { '?' };
-        let pw_last = if pw_len > 1 { register_form.password.chars().last().unwrap() } else { '?' };
-        cata_log!(Info, format!("Registration password info: length={}, first={}, last={}", pw_len, pw_first, pw_last));
+        cata_log!(Debug, format!("Hashing registration password for vessel: {} (password: {})", register_form.username, register_form.password));
 
-        let password_hash = match hash(&register_form.password, DEFAULT_COST) {
-            Ok(hashed) => {
-                let hash_prefix = hashed.chars().take(10).collect::<String>();
-                cata_log!(Info, format!("Generated password hash prefix: {}...", hash_prefix));
-                hashed
-            }
+        let password_hash = match password_service::hash_password(register_form.password.into_inner()).await {
+            Ok(hashed) => hashed,
             Err(e) => {
                 let error_message = format!("Error hashing password: {}", e);
                 cata_log!(Error, &error_message);
@@ -201,10 +283,14 @@ impl Vessel {
 
         match Self::create(new_vessel).await {
             Ok(vessel) => {
-                cata_log!(Info, "Vessel registered successfully");
+                cata_log!(Info, format!("Vessel registered successfully: {}", vessel.username));
 
-                let hash_prefix = vessel.password_hash.chars().take(10).collect::<String>();
-                cata_log!(Info, format!("Stored password hash prefix: {}...", hash_prefix));
+                Tenant::insert(NewTenant {
+                    vessel_id: vessel.id,
+                    name: vessel.name.clone(),
+                    display_name: vessel.display_name.clone(),
+                })
+                .await?;
 
                 cata_log!(Info, format!("Provisioning database for tenant: {}", vessel.name));
 
@@ -242,10 +328,32 @@ impl Vessel {
             }
         };
 
-        let token_pair = jwt_service::generate_token_pair_for_vessel(&vessel, token_info.remember, token_info.device_info)?;
+        let tenant_name = vessel.owned_tenant(Some(&token_info.tenant_name)).await?;
+        let token_pair = jwt_service::generate_token_pair(&vessel, &tenant_name, token_info.remember, token_info.device_info)?;
 
         cata_log!(Info, format!("Refreshed tokens for vessel {}", user_id));
 
         Ok((vessel, token_pair))
     }
+
+    pub async fn select_tenant(refresh_token: &str, tenant_name: &str) -> Result<(Vessel, jwt_service::TokenPair), MeltDown> {
+        let token_info = jwt_service::validate_refresh_token(refresh_token)?;
+
+        if token_info.auth_system != AuthSystem::Vessel {
+            return Err(MeltDown::new(MeltType::InvalidToken, "Refresh token was not issued for the vessel system"));
+        }
+
+        let vessel = Self::find_by_id(token_info.user_id).await?.ok_or_else(|| MeltDown::new(MeltType::NotFound, "Vessel"))?;
+
+        if Tenant::find_for_vessel(vessel.id, tenant_name).await?.is_none() {
+            return Err(MeltDown::new(MeltType::NotFound, format!("Tenant {} for vessel {}", tenant_name, vessel.id)).with_user_message("Tenant not found."));
+        }
+
+        token_registry::mark_refresh_token_used(&token_info.tenant_name, vessel.id, &token_info.jti);
+        let token_pair = jwt_service::generate_token_pair(&vessel, tenant_name, token_info.remember, token_info.device_info)?;
+
+        cata_log!(Info, format!("Vessel {} switched to tenant {}", vessel.username, tenant_name));
+
+        Ok((vessel, token_pair))
+    }
 }
@@ -7,12 +7,15 @@ use rocket::{
     routes, Route,
 };
 use rocket_dyn_templates::Template;
+use serde_json::json;
 
 use crate::{
     cata_log,
     meltdown::*,
     middleware::*,
-    vessel::structs::{Vessel, VesselLoginForm, VesselRegisterForm},
+    services::default::jwt_service::{validate_two_factor_token, LoginStep, TokenPair, IMPERSONATOR_ACCESS_COOKIE, IMPERSONATOR_REFRESH_COOKIE},
+    structs::TwoFactorCodeForm,
+    vessel::structs::{Vessel, VesselImpersonation, VesselLoginForm, VesselRegisterForm},
 };
 
 fn extract_tenant_name_from_path(path: &str) -> Option<String> {
@@ -43,7 +46,7 @@ pub async fn get_login_tenant(tenant: &str, app_context: AppContext<'_>, jwt: Op
 
 fn handle_vessel_login(app_context: AppContext<'_>, jwt: Option<JWT>) -> Result<Template, Flash<Redirect>> {
     if let Some(jwt) = jwt {
-        if jwt.is_vessel_auth() && jwt.get_role() == "vessel" {
+        if jwt.is_vessel_owner() {
             cata_log!(Info, format!("User {} is already logged in to vessel system, redirecting to dashboard", jwt.get_username()));
             return Err(Flash::success(Redirect::to("/vessel/dashboard"), "Already logged in to vessel system."));
         } else if jwt.is_tenant_auth() {
@@ -66,16 +69,23 @@ fn handle_vessel_login(app_context: AppContext<'_>, jwt: Option<JWT>) -> Result<
 }
 
 #[post("/vessel/auth/login", data = "<login_form>")]
-pub async fn post_login_default(login_form: Form<VesselLoginForm>, cookies: &CookieJar<'_>, app_context: AppContext<'_>, jwt: Option<JWT>) -> Result<Flash<Redirect>, Flash<Redirect>> {
-    handle_vessel_post_login(login_form, cookies, app_context, jwt, None).await
+pub async fn post_login_default(login_form: Form<VesselLoginForm>, cookies: &CookieJar<'_>, app_context: AppContext<'_>, jwt: Option<JWT>, client: ClientInfo) -> Result<Flash<Redirect>, Flash<Redirect>> {
+    handle_vessel_post_login(login_form, cookies, app_context, jwt, client, None).await
 }
 
 #[post("/<tenant>/vessel/auth/login", data = "<login_form>")]
-pub async fn post_login_tenant(tenant: &str, login_form: Form<VesselLoginForm>, cookies: &CookieJar<'_>, app_context: AppContext<'_>, jwt: Option<JWT>) -> Result<Flash<Redirect>, Flash<Redirect>> {
-    handle_vessel_post_login(login_form, cookies, app_context, jwt, Some(tenant)).await
+pub async fn post_login_tenant(tenant: &str, login_form: Form<VesselLoginForm>, cookies: &CookieJar<'_>, app_context: AppContext<'_>, jwt: Option<JWT>, client: ClientInfo) -> Result<Flash<Redirect>, Flash<Redirect>> {
+    handle_vessel_post_login(login_form, cookies, app_context, jwt, client, Some(tenant)).await
 }
 
-async fn handle_vessel_post_login(login_form: Form<VesselLoginForm>, cookies: &CookieJar<'_>, app_context: AppContext<'_>, jwt: Option<JWT>, tenant: Option<&str>) -> Result<Flash<Redirect>, Flash<Redirect>> {
+async fn handle_vessel_post_login(
+    login_form: Form<VesselLoginForm>,
+    cookies: &CookieJar<'_>,
+    app_context: AppContext<'_>,
+    jwt: Option<JWT>,
+    client: ClientInfo,
+    tenant: Option<&str>,
+) -> Result<Flash<Redirect>, Flash<Redirect>> {
     let login = login_form.into_inner();
 
     cata_log!(Info, format!("Vessel login attempt at path: {} (tenant: {:?})", app_context.request_uri(), tenant));
@@ -100,8 +110,15 @@ async fn handle_vessel_post_login(login_form: Form<VesselLoginForm>, cookies: &C
         }
     }
 
-    match Vessel::login_user(login).await {
-        Ok((vessel, token_pair)) => {
+    match Vessel::login_user(login, &client).await {
+        Ok((vessel, LoginStep::TwoFactorPending { token, .. })) => {
+            cata_log!(Info, format!("Vessel {} requires two-factor verification", vessel.username));
+
+            cookies.add(Cookie::build(Cookie::new("two_factor_token", token)).http_only(true).secure(true).build());
+
+            Ok(Flash::success(Redirect::to("/vessel/auth/two_factor"), "Enter the code from your authenticator app."))
+        }
+        Ok((vessel, LoginStep::Complete(token_pair))) => {
             cata_log!(
                 Info,
                 format!(
@@ -116,9 +133,7 @@ async fn handle_vessel_post_login(login_form: Form<VesselLoginForm>, cookies: &C
                 cata_log!(Warning, "JWT does not contain a tenant name!");
             }
 
-            cookies.add(Cookie::build(Cookie::new("access_token", token_pair.access_token)).http_only(true).secure(true).build());
-            cookies.add(Cookie::build(Cookie::new("refresh_token", token_pair.refresh_token)).http_only(true).secure(true).build());
-            cookies.add(Cookie::build(Cookie::new("user_id", vessel.id.to_string())).http_only(true).secure(true).build());
+            set_vessel_auth_cookies(cookies, token_pair, vessel.id);
 
             let redirect_url = "/vessel/dashboard";
             cata_log!(Info, format!("Redirecting to vessel dashboard: {}", redirect_url));
@@ -132,6 +147,64 @@ async fn handle_vessel_post_login(login_form: Form<VesselLoginForm>, cookies: &C
     }
 }
 
+pub fn set_vessel_auth_cookies(cookies: &CookieJar<'_>, token_pair: TokenPair, vessel_id: i32) {
+    cookies.add(Cookie::build(Cookie::new("access_token", token_pair.access_token)).http_only(true).secure(true).build());
+    cookies.add(Cookie::build(Cookie::new("refresh_token", token_pair.refresh_token)).http_only(true).secure(true).build());
+    cookies.add(Cookie::build(Cookie::new("user_id", vessel_id.to_string())).http_only(true).secure(true).build());
+}
+
+#[get("/vessel/auth/two_factor")]
+pub async fn get_two_factor(cookies: &CookieJar<'_>, app_context: AppContext<'_>) -> Result<Template, Flash<Redirect>> {
+    let pending = cookies.get("two_factor_token").map(|cookie| cookie.value().to_string());
+
+    match pending.as_deref().map(validate_two_factor_token) {
+        Some(Ok(claims)) if claims.auth_system == AuthSystem::Vessel => {
+            let context = json!({
+                "title": "Two-factor verification",
+                "username": claims.username,
+                "form_action": "/vessel/auth/two_factor"
+            });
+            Ok(app_context.render_with("vessel/two_factor", context))
+        }
+        _ => {
+            cookies.remove(Cookie::new("two_factor_token", ""));
+            Err(Flash::error(Redirect::to("/vessel/auth/login"), "Your login session expired. Please log in again."))
+        }
+    }
+}
+
+#[post("/vessel/auth/two_factor", data = "<two_factor_form>")]
+pub async fn post_two_factor(two_factor_form: Form<TwoFactorCodeForm>, cookies: &CookieJar<'_>, app_context: AppContext<'_>) -> Result<Flash<Redirect>, Flash<Redirect>> {
+    let form = two_factor_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Err(Flash::error(Redirect::to("/vessel/auth/two_factor"), csrf_error.user_message()));
+    }
+
+    let pending = match cookies.get("two_factor_token") {
+        Some(cookie) => cookie.value().to_string(),
+        None => return Err(Flash::error(Redirect::to("/vessel/auth/login"), "Your login session expired. Please log in again.")),
+    };
+
+    match Vessel::complete_two_factor_login(&pending, &form.code).await {
+        Ok((vessel, token_pair)) => {
+            cookies.remove(Cookie::new("two_factor_token", ""));
+            set_vessel_auth_cookies(cookies, token_pair, vessel.id);
+
+            Ok(Flash::success(Redirect::to("/vessel/dashboard"), "Successfully logged in."))
+        }
+        Err(error) if error.melt_type == MeltType::InvalidCredentials => {
+            cata_log!(Warning, format!("Vessel two-factor verification failed: {}", error.log_message()));
+            Err(Flash::error(Redirect::to("/vessel/auth/two_factor"), error.user_message()))
+        }
+        Err(error) => {
+            cata_log!(Warning, format!("Vessel two-factor login failed: {}", error.log_message()));
+            cookies.remove(Cookie::new("two_factor_token", ""));
+            Err(Flash::error(Redirect::to("/vessel/auth/login"), "Your login session expired. Please log in again."))
+        }
+    }
+}
+
 #[get("/vessel/auth/logout")]
 pub fn get_logout(cookies: &CookieJar<'_>, jwt: Option<JWT>) -> Flash<Redirect> {
     cata_log!(Info, "Vessel logout initiated");
@@ -196,6 +269,42 @@ pub fn get_logout(cookies: &CookieJar<'_>, jwt: Option<JWT>) -> Flash<Redirect>
     Flash::success(Redirect::to(login_redirect), "Successfully logged out.")
 }
 
+#[get("/vessel/impersonation/end")]
+pub async fn get_end_impersonation(cookies: &CookieJar<'_>, jwt: Option<JWT>) -> Flash<Redirect> {
+    let impersonator = jwt.as_ref().and_then(|jwt| jwt.impersonated_by().cloned());
+
+    if let (Some(jwt), Some(impersonator)) = (jwt.as_ref(), impersonator.as_ref()) {
+        if let Err(error) = VesselImpersonation::end(jwt.get_jti()).await {
+            cata_log!(Error, format!("Failed to record end of impersonation {}: {}", jwt.get_jti(), error.log_message()));
+        }
+        cata_log!(Warning, format!("Vessel owner {} stopped impersonating {} (jti: {})", impersonator, jwt.get_username(), jwt.get_jti()));
+    }
+
+    let stashed_access = cookies.get(IMPERSONATOR_ACCESS_COOKIE).map(|cookie| cookie.value().to_string());
+    let stashed_refresh = cookies.get(IMPERSONATOR_REFRESH_COOKIE).map(|cookie| cookie.value().to_string());
+
+    cookies.remove(Cookie::new(IMPERSONATOR_ACCESS_COOKIE, ""));
+    cookies.remove(Cookie::new(IMPERSONATOR_REFRESH_COOKIE, ""));
+    cookies.remove(Cookie::new("access_token", ""));
+    cookies.remove(Cookie::new("refresh_token", ""));
+    cookies.remove(Cookie::new("user_id", ""));
+
+    let vessel = match impersonator {
+        Some(username) => Vessel::find_by_username(&username).await.ok().flatten(),
+        None => None,
+    };
+
+    match (stashed_access, stashed_refresh, vessel) {
+        (Some(access_token), Some(refresh_token), Some(vessel)) => {
+            cookies.add(Cookie::build(Cookie::new("access_token", access_token)).http_only(true).secure(true).build());
+            cookies.add(Cookie::build(Cookie::new("refresh_token", refresh_token)).http_only(true).secure(true).build());
+            cookies.add(Cookie::build(Cookie::new("user_id", vessel.id.to_string())).http_only(true).secure(true).build());
+            Flash::success(Redirect::to("/vessel/dashboard"), "Impersonation ended.")
+        }
+        _ => Flash::success(Redirect::to("/vessel/auth/login"), "Impersonation ended. Please log in again."),
+    }
+}
+
 #[get("/vessel/auth/register")]
 pub async fn get_register(app_context: AppContext<'_>) -> Template {
     cata_log!(Info, "Rendering vessel registration page");
@@ -246,9 +355,7 @@ pub async fn refresh_token(cookies: &CookieJar<'_>) -> Result<(), Flash<Redirect
                 cata_log!(Warning, "Refresh: JWT does not contain a tenant name!");
             }
 
-            cookies.add(Cookie::build(Cookie::new("access_token", token_pair.access_token)).http_only(true).secure(true).build());
-            cookies.add(Cookie::build(Cookie::new("refresh_token", token_pair.refresh_token)).http_only(true).secure(true).build());
-            cookies.add(Cookie::build(Cookie::new("user_id", vessel.id.to_string())).http_only(true).secure(true).build());
+            set_vessel_auth_cookies(cookies, token_pair, vessel.id);
             Ok(())
         }
         Err(error) => {
@@ -264,5 +371,17 @@ pub async fn refresh_token(cookies: &CookieJar<'_>) -> Result<(), Flash<Redirect
 }
 
 pub fn auth_routes() -> Vec<Route> {
-    routes![get_login_default, get_login_tenant, post_login_default, post_login_tenant, get_logout, get_register, post_register, refresh_token]
+    routes![
+        get_login_default,
+        get_login_tenant,
+        post_login_default,
+        post_login_tenant,
+        get_two_factor,
+        post_two_factor,
+        get_logout,
+        get_end_impersonation,
+        get_register,
+        post_register,
+        refresh_token
+    ]
 }
@@ -1,8 +1,33 @@
-use rocket::{get, routes, Route};
+use rocket::{
+    form::Form,
+    get,
+    http::{Cookie, CookieJar},
+    post,
+    response::{Flash, Redirect},
+    routes,
+    serde::json::Json,
+    Route,
+};
 use rocket_dyn_templates::Template;
-use serde_json::json;
+use serde_json::{json, Value};
 
-use crate::{cata_log, middleware::*};
+use crate::{
+    cata_log,
+    meltdown::*,
+    middleware::*,
+    services::{
+        default::{totp_service, IMPERSONATOR_ACCESS_COOKIE, IMPERSONATOR_REFRESH_COOKIE},
+        sparks::{compiled_spark, is_spark_secret, mask_url_credentials, spark_statuses, MASKED_VALUE},
+    },
+    structs::{TenantSparkSettingForm, TenantSparkSettings, TwoFactorCodeForm, TwoFactorDisableForm, Users},
+    vessel::{
+        routes::set_vessel_auth_cookies,
+        structs::{ImpersonationForm, Tenant, TenantForm, TenantSelectForm, TenantSpark, TenantSparkToggleForm, Vessel, VesselImpersonation, VesselLoginAttempt, VesselTwoFactor},
+    },
+};
+
+const RECENT_LOGINS_LIMIT: i64 = 10;
+const RECENT_IMPERSONATIONS_LIMIT: i64 = 10;
 
 #[get("/vessel/dashboard")]
 pub async fn get_dashboard(jwt: JWT, app_context: AppContext<'_>) -> Template {
@@ -10,14 +35,363 @@ pub async fn get_dashboard(jwt: JWT, app_context: AppContext<'_>) -> Template {
 
     let tenant_name = jwt.get_tenant_name().cloned().unwrap_or_else(|| "unknown".to_string());
 
+    let tenant_admins = Users::get_active_admins(&tenant_name).await.unwrap_or_else(|error| {
+        cata_log!(Error, format!("Failed to load tenant admins (tenant: {}): {}", tenant_name, error.log_message()));
+        Vec::new()
+    });
+
+    let impersonations = VesselImpersonation::recent_for_vessel(jwt.user_id(), RECENT_IMPERSONATIONS_LIMIT).await.unwrap_or_else(|error| {
+        cata_log!(Error, format!("Failed to load impersonation history: {}", error.log_message()));
+        Vec::new()
+    });
+
     let context = json!({
         "jwt_username": jwt.get_username(),
-        "tenant_name": tenant_name
+        "tenant_name": tenant_name,
+        "tenant_admins": tenant_admins.iter().map(|admin| json!({ "id": admin.id, "username": admin.username })).collect::<Vec<_>>(),
+        "impersonations": impersonations
     });
 
     app_context.render_with("vessel/dashboard", context)
 }
 
+#[post("/vessel/impersonate", data = "<impersonation_form>")]
+pub async fn post_impersonate(jwt: JWT, impersonation_form: Form<ImpersonationForm>, cookies: &CookieJar<'_>, client: ClientInfo, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = impersonation_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to("/vessel/dashboard"), csrf_error.user_message());
+    }
+
+    let vessel = match Vessel::find_by_id(jwt.user_id()).await {
+        Ok(Some(vessel)) => vessel,
+        _ => return Flash::error(Redirect::to("/vessel/dashboard"), "Vessel account not found."),
+    };
+
+    let tenant_name = jwt.get_tenant_name().cloned().unwrap_or_else(|| vessel.name.clone());
+
+    let (token, impersonation) = match VesselImpersonation::start(&vessel, &tenant_name, form.user_id, &client).await {
+        Ok(started) => started,
+        Err(error) => {
+            cata_log!(Warning, format!("Vessel {} failed to impersonate user {}: {}", vessel.username, form.user_id, error.log_message()));
+            return Flash::error(Redirect::to("/vessel/dashboard"), error.user_message());
+        }
+    };
+
+    for (from, to) in [("access_token", IMPERSONATOR_ACCESS_COOKIE), ("refresh_token", IMPERSONATOR_REFRESH_COOKIE)] {
+        if let Some(cookie) = cookies.get(from) {
+            cookies.add(Cookie::build(Cookie::new(to, cookie.value().to_string())).http_only(true).secure(true).build());
+        }
+    }
+
+    cookies.remove(Cookie::new("refresh_token", ""));
+    cookies.add(Cookie::build(Cookie::new("access_token", token)).http_only(true).secure(true).build());
+    cookies.add(Cookie::build(Cookie::new("user_id", impersonation.user_id.to_string())).http_only(true).secure(true).build());
+
+    Flash::success(
+        Redirect::to(format!("/{}/admin/dashboard", impersonation.tenant_name)),
+        format!("You are now acting as {}. Every request is logged.", impersonation.username),
+    )
+}
+
+#[get("/vessel/tenants")]
+pub async fn get_tenants(jwt: JWT, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
+    let tenants = Tenant::for_vessel(jwt.user_id()).await?;
+
+    let context = json!({
+        "jwt_username": jwt.get_username(),
+        "tenant_name": jwt.get_tenant_name(),
+        "tenants": tenants
+    });
+
+    Ok(app_context.render_with("vessel/tenants", context))
+}
+
+#[post("/vessel/tenants", data = "<tenant_form>")]
+pub async fn post_create_tenant(jwt: JWT, tenant_form: Form<TenantForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = tenant_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to("/vessel/tenants"), csrf_error.user_message());
+    }
+
+    let vessel = match Vessel::find_by_id(jwt.user_id()).await {
+        Ok(Some(vessel)) => vessel,
+        _ => return Flash::error(Redirect::to("/vessel/tenants"), "Vessel account not found."),
+    };
+
+    match Tenant::create_for_vessel(&vessel, form).await {
+        Ok(tenant) => Flash::success(Redirect::to("/vessel/tenants"), format!("Tenant {} created.", tenant.name)),
+        Err(error) => {
+            cata_log!(Warning, format!("Vessel {} failed to create tenant: {}", vessel.username, error.log_message()));
+            Flash::error(Redirect::to("/vessel/tenants"), error.user_message())
+        }
+    }
+}
+
+#[post("/vessel/tenants/select", data = "<select_form>")]
+pub async fn post_select_tenant(jwt: JWT, select_form: Form<TenantSelectForm>, cookies: &CookieJar<'_>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = select_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to("/vessel/tenants"), csrf_error.user_message());
+    }
+
+    let refresh_token = match cookies.get("refresh_token") {
+        Some(cookie) => cookie.value().to_string(),
+        None => return Flash::error(Redirect::to("/vessel/auth/login"), "Session expired. Please log in again."),
+    };
+
+    match Vessel::select_tenant(&refresh_token, &form.tenant).await {
+        Ok((vessel, token_pair)) => {
+            set_vessel_auth_cookies(cookies, token_pair, vessel.id);
+            Flash::success(Redirect::to("/vessel/dashboard"), format!("Now working in tenant {}.", form.tenant))
+        }
+        Err(error) => {
+            cata_log!(Warning, format!("Vessel {} failed to switch to tenant {}: {}", jwt.get_username(), form.tenant, error.log_message()));
+            Flash::error(Redirect::to("/vessel/tenants"), error.user_message())
+        }
+    }
+}
+
+async fn spark_overview(vessel_id: i32) -> Result<Value, MeltDown> {
+    let tenants = Tenant::for_vessel(vessel_id).await?;
+    let settings = TenantSpark::for_tenants(&tenants.iter().map(|tenant| tenant.id).collect::<Vec<_>>()).await?;
+
+    let mut overrides = Vec::with_capacity(tenants.len());
+    for tenant in &tenants {
+        overrides.push(TenantSparkSettings::get_all(&tenant.name).await.unwrap_or_else(|error| {
+            cata_log!(Error, format!("Failed to load spark settings (tenant: {}): {}", tenant.name, error.log_message()));
+            Vec::new()
+        }));
+    }
+
+    let sparks = spark_statuses()
+        .into_iter()
+        .map(|status| {
+            let tenant_states = tenants
+                .iter()
+                .zip(&overrides)
+                .map(|(tenant, tenant_overrides)| {
+                    let enabled = settings
+                        .iter()
+                        .find(|setting| setting.tenant_id == tenant.id && setting.spark == status.name)
+                        .map_or(true, |setting| setting.enabled);
+
+                    let tenant_overrides = tenant_overrides
+                        .iter()
+                        .filter(|setting| setting.spark == status.name)
+                        .map(|setting| {
+                            let value = if is_spark_secret(&status.name, &setting.key) {
+                                MASKED_VALUE.to_string()
+                            } else {
+                                mask_url_credentials(&setting.value)
+                            };
+                            json!({ "key": setting.key, "value": value })
+                        })
+                        .collect::<Vec<_>>();
+
+                    json!({ "name": tenant.name, "enabled": enabled, "overrides": tenant_overrides })
+                })
+                .collect::<Vec<_>>();
+
+            let mut spark = json!(status);
+            spark["tenants"] = json!(tenant_states);
+            spark
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!(sparks))
+}
+
+#[get("/vessel/sparks")]
+pub async fn get_sparks(jwt: JWT, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
+    let context = json!({
+        "jwt_username": jwt.get_username(),
+        "tenant_name": jwt.get_tenant_name(),
+        "sparks": spark_overview(jwt.user_id()).await?
+    });
+
+    Ok(app_context.render_with("vessel/sparks", context))
+}
+
+#[get("/vessel/sparks/status")]
+pub async fn get_sparks_status(jwt: JWT) -> Json<Value> {
+    match spark_overview(jwt.user_id()).await {
+        Ok(sparks) => Json(json!({
+            "success": true,
+            "data": {
+                "sparks": sparks
+            }
+        })),
+        Err(error) => {
+            cata_log!(Error, format!("Failed to load spark status: {}", error.log_message()));
+            Json(json!({
+                "success": false,
+                "error": {
+                    "code": "database_error",
+                    "message": error.user_message()
+                }
+            }))
+        }
+    }
+}
+
+#[post("/vessel/sparks/<spark>/toggle", data = "<toggle_form>")]
+pub async fn post_toggle_spark(jwt: JWT, spark: &str, toggle_form: Form<TenantSparkToggleForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = toggle_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to("/vessel/sparks"), csrf_error.user_message());
+    }
+
+    if compiled_spark(spark).is_none() {
+        return Flash::error(Redirect::to("/vessel/sparks"), "Spark not found.");
+    }
+
+    let tenant = match Tenant::find_for_vessel(jwt.user_id(), &form.tenant).await {
+        Ok(Some(tenant)) => tenant,
+        _ => return Flash::error(Redirect::to("/vessel/sparks"), "Tenant not found."),
+    };
+
+    match TenantSpark::set_enabled(tenant.id, spark, form.enabled).await {
+        Ok(setting) => {
+            let state = if setting.enabled { "enabled" } else { "disabled" };
+            cata_log!(Info, format!("Vessel {} {} spark {} for tenant {}", jwt.get_username(), state, spark, tenant.name));
+            Flash::success(Redirect::to("/vessel/sparks"), format!("Spark {} {} for tenant {}.", spark, state, tenant.name))
+        }
+        Err(error) => Flash::error(Redirect::to("/vessel/sparks"), error.user_message()),
+    }
+}
+
+#[post("/vessel/sparks/<spark>/settings", data = "<setting_form>")]
+pub async fn post_spark_setting(jwt: JWT, spark: &str, setting_form: Form<TenantSparkSettingForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = setting_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to("/vessel/sparks"), csrf_error.user_message());
+    }
+
+    if compiled_spark(spark).is_none() {
+        return Flash::error(Redirect::to("/vessel/sparks"), "Spark not found.");
+    }
+
+    let tenant = match Tenant::find_for_vessel(jwt.user_id(), &form.tenant).await {
+        Ok(Some(tenant)) => tenant,
+        _ => return Flash::error(Redirect::to("/vessel/sparks"), "Tenant not found."),
+    };
+
+    let key = form.key.trim();
+    if key.is_empty() || key.split('.').any(str::is_empty) {
+        return Flash::error(Redirect::to("/vessel/sparks"), "Setting key cannot be empty.");
+    }
+
+    match TenantSparkSettings::update(spark, key, form.value.trim(), &tenant.name).await {
+        Ok(()) => {
+            cata_log!(Info, format!("Vessel {} updated spark {} setting {} for tenant {}", jwt.get_username(), spark, key, tenant.name));
+            Flash::success(Redirect::to("/vessel/sparks"), format!("Setting {} updated for tenant {}.", key, tenant.name))
+        }
+        Err(error) => Flash::error(Redirect::to("/vessel/sparks"), error.user_message()),
+    }
+}
+
+#[get("/vessel/security")]
+pub async fn get_security(jwt: JWT, app_context: AppContext<'_>) -> Result<Template, MeltDown> {
+    let vessel_id = jwt.user_id();
+    let recent_logins = VesselLoginAttempt::recent_for_username(jwt.get_username(), RECENT_LOGINS_LIMIT).await?;
+
+    if VesselTwoFactor::is_enabled(vessel_id).await? {
+        let context = json!({
+            "jwt_username": jwt.get_username(),
+            "two_factor_enabled": true,
+            "recent_logins": recent_logins
+        });
+        return Ok(app_context.render_with("vessel/security", context));
+    }
+
+    let enrolment = VesselTwoFactor::begin_enrolment(vessel_id).await?;
+    let provisioning_uri = enrolment.provisioning_uri(jwt.get_username())?;
+
+    let context = json!({
+        "jwt_username": jwt.get_username(),
+        "two_factor_enabled": false,
+        "qr_svg": totp_service::totp_qr_svg(&provisioning_uri)?,
+        "secret": enrolment.secret,
+        "recent_logins": recent_logins
+    });
+
+    Ok(app_context.render_with("vessel/security", context))
+}
+
+#[post("/vessel/security/two_factor/enable", data = "<enable_form>")]
+pub async fn post_enable_two_factor(jwt: JWT, enable_form: Form<TwoFactorCodeForm>, app_context: AppContext<'_>) -> Result<Template, Flash<Redirect>> {
+    let form = enable_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Err(Flash::error(Redirect::to("/vessel/security"), csrf_error.user_message()));
+    }
+
+    match VesselTwoFactor::confirm_enrolment(jwt.user_id(), &form.code).await {
+        Ok(recovery_codes) => {
+            cata_log!(Info, format!("Vessel {} enabled two-factor authentication", jwt.get_username()));
+
+            let context = json!({
+                "title": "Recovery codes",
+                "recovery_codes": recovery_codes,
+                "continue_url": "/vessel/dashboard"
+            });
+            Ok(app_context.render_with("auth/recovery_codes", context))
+        }
+        Err(error) => {
+            cata_log!(Warning, format!("Vessel two-factor enrolment failed: {}", error.log_message()));
+            Err(Flash::error(Redirect::to("/vessel/security"), error.user_message()))
+        }
+    }
+}
+
+#[post("/vessel/security/two_factor/disable", data = "<disable_form>")]
+pub async fn post_disable_two_factor(jwt: JWT, disable_form: Form<TwoFactorDisableForm>, app_context: AppContext<'_>) -> Flash<Redirect> {
+    let form = disable_form.into_inner();
+
+    if let Err(csrf_error) = verify_csrf_for_state_change(&app_context, &form.authenticity_token) {
+        return Flash::error(Redirect::to("/vessel/security"), csrf_error.user_message());
+    }
+
+    let vessel = match Vessel::find_by_id(jwt.user_id()).await {
+        Ok(Some(vessel)) => vessel,
+        _ => return Flash::error(Redirect::to("/vessel/security"), "Vessel account not found."),
+    };
+
+    if !matches!(vessel.verify_password(&form.password).await, Ok(true)) {
+        return Flash::error(Redirect::to("/vessel/security"), MeltDown::invalid_credentials().user_message());
+    }
+
+    if !matches!(VesselTwoFactor::verify(vessel.id, &form.code).await, Ok(true)) {
+        return Flash::error(Redirect::to("/vessel/security"), "Invalid verification code.");
+    }
+
+    match VesselTwoFactor::disable(vessel.id).await {
+        Ok(()) => {
+            cata_log!(Info, format!("Vessel {} disabled two-factor authentication", vessel.username));
+            Flash::success(Redirect::to("/vessel/security"), "Two-factor authentication disabled.")
+        }
+        Err(error) => Flash::error(Redirect::to("/vessel/security"), error.user_message()),
+    }
+}
+
 pub fn dashboard_routes() -> Vec<Route> {
-    routes![get_dashboard]
+    routes![
+        get_dashboard,
+        post_impersonate,
+        get_tenants,
+        post_create_tenant,
+        post_select_tenant,
+        get_sparks,
+        get_sparks_status,
+        post_toggle_spark,
+        post_spark_setting,
+        get_security,
+        post_enable_two_factor,
+        post_disable_two_factor
+    ]
 }
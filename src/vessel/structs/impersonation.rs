@@ -0,0 +1,42 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use rocket::form::FromForm;
+use serde::Serialize;
+
+use crate::vessel::database::schema::vessel_impersonations;
+
+#[derive(Queryable, Selectable, Serialize, Debug, Clone)]
+#[diesel(table_name = vessel_impersonations)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct VesselImpersonation {
+    pub id: i32,
+    pub vessel_id: i32,
+    pub tenant_name: String,
+    pub user_id: i32,
+    pub username: String,
+    pub token_jti: String,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub started_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+    pub ended_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = vessel_impersonations)]
+pub struct NewVesselImpersonation {
+    pub vessel_id: i32,
+    pub tenant_name: String,
+    pub user_id: i32,
+    pub username: String,
+    pub token_jti: String,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(FromForm, Debug)]
+pub struct ImpersonationForm {
+    pub user_id: i32,
+    pub authenticity_token: String,
+}
@@ -0,0 +1,30 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::vessel::database::schema::vessel_login_attempts;
+
+#[derive(Queryable, Selectable, Serialize, Debug, Clone)]
+#[diesel(table_name = vessel_login_attempts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct VesselLoginAttempt {
+    pub id: i32,
+    pub username: String,
+    pub vessel_id: Option<i32>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub success: bool,
+    pub failure_reason: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = vessel_login_attempts)]
+pub struct NewVesselLoginAttempt {
+    pub username: String,
+    pub vessel_id: Option<i32>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub success: bool,
+    pub failure_reason: Option<String>,
+}
@@ -1,3 +1,13 @@
+pub mod impersonation;
+pub mod login_attempts;
+pub mod tenant;
+pub mod tenant_spark;
+pub mod two_factor;
 pub mod vessel;
 
+pub use impersonation::*;
+pub use login_attempts::*;
+pub use tenant::*;
+pub use tenant_spark::*;
+pub use two_factor::*;
 pub use vessel::*;
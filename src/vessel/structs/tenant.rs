@@ -0,0 +1,38 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use rocket::form::FromForm;
+use serde::Serialize;
+
+use crate::vessel::database::schema::tenants;
+
+#[derive(Queryable, Selectable, Serialize, Debug, Clone)]
+#[diesel(table_name = tenants)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Tenant {
+    pub id: i32,
+    pub vessel_id: i32,
+    pub name: String,
+    pub display_name: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = tenants)]
+pub struct NewTenant {
+    pub vessel_id: i32,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(FromForm, Debug)]
+pub struct TenantForm {
+    pub name: String,
+    pub display_name: String,
+    pub authenticity_token: String,
+}
+
+#[derive(FromForm, Debug)]
+pub struct TenantSelectForm {
+    pub tenant: String,
+    pub authenticity_token: String,
+}
@@ -0,0 +1,32 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use rocket::form::FromForm;
+use serde::Serialize;
+
+use crate::vessel::database::schema::tenant_sparks;
+
+#[derive(Queryable, Selectable, Serialize, Debug, Clone)]
+#[diesel(table_name = tenant_sparks)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TenantSpark {
+    pub id: i32,
+    pub tenant_id: i32,
+    pub spark: String,
+    pub enabled: bool,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = tenant_sparks)]
+pub struct NewTenantSpark {
+    pub tenant_id: i32,
+    pub spark: String,
+    pub enabled: bool,
+}
+
+#[derive(FromForm, Debug)]
+pub struct TenantSparkToggleForm {
+    pub tenant: String,
+    pub enabled: bool,
+    pub authenticity_token: String,
+}
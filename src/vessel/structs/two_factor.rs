@@ -0,0 +1,26 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde_json::Value as JsonValue;
+
+use crate::vessel::database::schema::vessel_two_factor;
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = vessel_two_factor)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct VesselTwoFactor {
+    pub id: i32,
+    pub vessel_id: i32,
+    pub secret: String,
+    pub enabled: bool,
+    pub recovery_codes: JsonValue,
+    pub last_used_step: Option<i64>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = vessel_two_factor)]
+pub struct NewVesselTwoFactor {
+    pub vessel_id: i32,
+    pub secret: String,
+}
@@ -3,7 +3,7 @@ use diesel::prelude::*;
 use rocket::form::FromForm;
 use serde::{Deserialize, Serialize};
 
-use crate::vessel::database::schema::vessels;
+use crate::{services::default::logger::Secret, vessel::database::schema::vessels};
 
 #[derive(Queryable, Selectable, Serialize, Deserialize, Debug, Clone)]
 #[diesel(table_name = vessels)]
@@ -37,7 +37,7 @@ pub struct NewVessel {
 #[derive(FromForm, Debug)]
 pub struct VesselLoginForm {
     pub username: String,
-    pub password: String,
+    pub password: Secret<String>,
     pub remember_me: Option<bool>,
     pub authenticity_token: String,
 }
@@ -48,8 +48,8 @@ pub struct VesselRegisterForm {
     pub display_name: String,
     pub username: String,
     pub email: String,
-    pub password: String,
-    pub confirm_password: String,
+    pub password: Secret<String>,
+    pub confirm_password: Secret<String>,
     pub first_name: String,
     pub last_name: String,
     pub authenticity_token: String,